@@ -25,6 +25,11 @@
 //! - `from_toml`: Allows to create a hashmap from `TOML` values, also having an additional `Datetime` `CfgValue`.
 //! - `from_json`: Allows to create a hashmap from `JSON` values, also having an additional `Null` `CfgValue`.
 //! - `generator`: Includes additional methods for `CfgValue`s that allows for generating numbers (int or float) using a value.
+//! - `sync`: Includes `SharedCfgMap`, a wrapper that allows a `CfgMap` to be shared and changed between threads.
+//! - `typed`: Includes `CfgMap::sync`, which allows editing part of a `CfgMap` as a typed struct. Also enables `from_json`.
+//! - `regex`: Adds `Condition::IsStrMatching`, which checks strings using a regex.
+//! - `net-validate`: Adds conditions checking networking fields, such as `Condition::IsUrl` and `Condition::IsPort`.
+//! - `async`: Adds `CfgMap::from_file_async` and `CfgMap::from_reader_async`, which load documents using `tokio`.
 //! 
 //! ## Tutorial (of sorts):
 //! 
@@ -110,6 +115,7 @@
 //! 
 //! All `HashMap` methods are also available, since `CfgMap` implements `Deref` and `DerefMut` for `HashMap<String, CfgValue>`.
 //! For example, you can call `.iter()` on it, even though that is not directly implemented.
+//! Changing the `HashMap` directly bypasses locks, pinned types and other checks, which `CfgMap::checked` prevents.
 //! 
 //! ## Complete example
 //! ```ignore
@@ -166,13 +172,17 @@
 //!assert!(cmap.get("person/1/name").check_that(IsExactlyStr("b".into())));
 //! ```
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 mod conditions;
-pub use conditions::{Checkable, Condition};
+pub use conditions::{Checkable, Condition, ExecuteOptions};
+mod condition_parse;
+pub use condition_parse::ConditionParseError;
 use std::concat;
+use std::convert::TryFrom;
+use std::fmt;
 use std::mem;
-use std::ops::Deref;
-use std::ops::DerefMut;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
 
 #[macro_use]
 mod macros;
@@ -186,6 +196,78 @@ use toml::value::Value as TomlValue;
 #[cfg(feature = "from_yaml")]
 use yaml_rust::Yaml as YamlValue;
 
+mod error;
+pub use error::CfgError;
+mod pin;
+mod ownership;
+pub use ownership::Conflict;
+mod observed;
+pub use observed::ObservedCfgMap;
+mod frozen;
+pub use frozen::FrozenCfgMap;
+mod checked;
+pub use checked::CheckedCfgMap;
+mod tracking;
+mod refs;
+mod include;
+mod interpolate;
+mod deprecation;
+mod migrations;
+mod secret;
+pub mod lint;
+mod pointer;
+mod patch;
+pub use patch::{PatchError, SimulateError};
+mod fingerprint;
+mod diff;
+mod runtime;
+mod flat;
+mod overlay;
+pub use overlay::Overlay;
+mod walk;
+pub use walk::Walk;
+pub use diff::CfgDiff;
+mod reload;
+pub use reload::{ReloadGate, ReloadKind, ReloadPlan, ReloadPolicy, ReloadRejected};
+mod store;
+pub use store::CfgStore;
+mod merge;
+pub use merge::{MergeResolver, Merger};
+mod path;
+pub use path::CfgPath;
+mod explain;
+pub use explain::Explanation;
+mod check;
+pub use check::{CheckError, Severity, ValidationReport};
+mod schema;
+pub use schema::{Schema, SchemaError};
+mod compat;
+pub use compat::Incompatibility;
+mod coverage;
+pub use coverage::Coverage;
+mod json_schema;
+pub use json_schema::{Unexportable, JSON_SCHEMA_DRAFT};
+mod display;
+mod units;
+#[cfg(feature = "net-validate")]
+mod net;
+
+#[cfg(any(feature = "from_json", feature = "from_toml", feature = "from_yaml"))]
+mod convert;
+
+#[cfg(any(feature = "from_json", feature = "from_toml", feature = "from_yaml"))]
+mod reader;
+#[cfg(any(feature = "from_json", feature = "from_toml", feature = "from_yaml"))]
+pub use reader::Format;
+#[cfg(any(feature = "from_json", feature = "from_toml", feature = "from_yaml"))]
+mod writer;
+#[cfg(any(feature = "from_json", feature = "from_toml", feature = "from_yaml"))]
+pub use writer::WriteOptions;
+#[cfg(feature = "from_toml")]
+mod snapshot;
+#[cfg(feature = "from_toml")]
+pub use snapshot::{__assert_cfg_snapshot, UPDATE_SNAPSHOTS};
+
 #[cfg(feature = "from_json")]
 mod from_json;
 
@@ -195,15 +277,50 @@ mod from_toml;
 #[cfg(feature = "from_yaml")]
 mod from_yaml;
 
+#[cfg(feature = "sync")]
+mod shared;
+#[cfg(feature = "sync")]
+pub use shared::SharedCfgMap;
+
+#[cfg(feature = "typed")]
+mod typed;
+#[cfg(feature = "typed")]
+pub use typed::Synced;
+
 #[cfg(feature = "from_toml")]
 use toml::value::Datetime;
 
 #[cfg(feature = "generator")]
 use rand::Rng;
 
+/// The prefix that marks a string as encrypted, to be decrypted by `CfgMap::decrypt_with`.
+pub const ENCRYPTED_PREFIX: &str = "enc:";
+
+/// The reserved key under which `CfgMap::attach_runtime_info` stores its information.
+pub const META_KEY: &str = "__meta";
+
+/// Checks whether `path` is the runtime information, or within it.
+pub(crate) fn is_meta_path(path: &str) -> bool {
+    path == META_KEY || is_within(path, META_KEY)
+}
+
+/// Checks whether `path` is nested within `ancestor`.
+fn is_within(path: &str, ancestor: &str) -> bool {
+    path.strip_prefix(ancestor).is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Checks whether changing the value at `path` could change any of the `locks`,
+/// which is the case if `path` is locked, within a locked path, or contains one.
+fn affects_lock(locks: &HashSet<String>, path: &str) -> bool {
+    locks.iter().any(|lock| lock == path || is_within(path, lock) || is_within(lock, path))
+}
+
 // The type contained within `CfgValue::Int`
 pub(crate) type _Int = i64;
 
+// The type contained within `CfgValue::UInt`
+pub(crate) type _UInt = u64;
+
 // The type contained within `CfgValue::Float`
 pub(crate) type _Float = f64;
 
@@ -220,6 +337,13 @@ impl From<bool> for CfgValue {
     }
 }
 
+/// Converts into an `Int` if the integer fits, and otherwise into a `UInt`.
+impl From<u64> for CfgValue {
+    fn from(x: u64) -> Self {
+        i64::try_from(x).map_or(CfgValue::UInt(x), CfgValue::Int)
+    }
+}
+
 impl From<Vec<CfgValue>> for CfgValue {
     fn from(l: Vec<CfgValue>) -> Self {
         CfgValue::List(l)
@@ -242,6 +366,10 @@ pub enum CfgValue {
     /// Represents an integer value.
     Int(_Int),
 
+    /// Represents an unsigned integer that's too large for an `Int`, such as those read from JSON or YAML.
+    /// Integers that fit within an `Int` are always read as one.
+    UInt(_UInt),
+
     /// Represents a float value.
     Float(_Float),
 
@@ -256,6 +384,11 @@ pub enum CfgValue {
 
     /// Represents a list of values. These values can have differing types.
     List(Vec<CfgValue>),
+
+    /// Represents a value from a document that no other `CfgValue` can represent, such as a yaml map with keys
+    /// that aren't strings. It's kept as the name of its format, such as `yaml`, along with its source in that
+    /// format, so that writing the map back in the same format doesn't drop it.
+    Opaque(String, Vec<u8>),
     
     /// Represents a `Datetime`. Only available if using `from_toml`.
     #[cfg(feature = "from_toml")]
@@ -274,7 +407,166 @@ pub enum CfgValue {
     Alias(usize),
 }
 
+/// The variant of a `CfgValue`, without its contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CfgType {
+    /// The type of `CfgValue::Int`.
+    Int,
+
+    /// The type of `CfgValue::UInt`.
+    UInt,
+
+    /// The type of `CfgValue::Float`.
+    Float,
+
+    /// The type of `CfgValue::Str`.
+    Str,
+
+    /// The type of `CfgValue::Bool`.
+    Bool,
+
+    /// The type of `CfgValue::Map`.
+    Map,
+
+    /// The type of `CfgValue::List`.
+    List,
+
+    /// The type of `CfgValue::Opaque`.
+    Opaque,
+
+    /// The type of `CfgValue::Datetime`. Only available if using `from_toml`.
+    #[cfg(feature = "from_toml")]
+    Datetime,
+
+    /// The type of `CfgValue::Null`. Only available if using `from_json` or `from_yaml`.
+    #[cfg(any(feature = "from_json", feature = "from_yaml"))]
+    Null,
+
+    /// The type of `CfgValue::BadValue`. Only available if using `from_yaml`.
+    #[cfg(feature = "from_yaml")]
+    BadValue,
+
+    /// The type of `CfgValue::Alias`. Only available if using `from_yaml`.
+    #[cfg(feature = "from_yaml")]
+    Alias,
+}
+
+impl fmt::Display for CfgType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CfgType::Int => "int",
+            CfgType::UInt => "uint",
+            CfgType::Float => "float",
+            CfgType::Str => "string",
+            CfgType::Bool => "bool",
+            CfgType::Map => "map",
+            CfgType::List => "list",
+            CfgType::Opaque => "opaque",
+            #[cfg(feature = "from_toml")]
+            CfgType::Datetime => "datetime",
+            #[cfg(any(feature = "from_json", feature = "from_yaml"))]
+            CfgType::Null => "null",
+            #[cfg(feature = "from_yaml")]
+            CfgType::BadValue => "bad value",
+            #[cfg(feature = "from_yaml")]
+            CfgType::Alias => "alias",
+        };
+        f.write_str(name)
+    }
+}
+
 impl CfgValue {
+    /// Gets the indices of every element that doesn't satisfy `condition`, if the value is a list.
+    /// Use `Condition::first_violation` to find where within an element it's violated.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{Condition::*, CfgValue::*, list, value};
+    /// 
+    /// let ports = list![80, "443", 8080, 8443.0];
+    /// assert_eq!(ports.find_violations(&IsInt), vec![1, 3]);
+    /// ```
+    pub fn find_violations(&self, condition: &Condition) -> Vec<usize> {
+        self.as_list().into_iter().flatten()
+            .enumerate()
+            .filter(|(_, elem)| !condition.execute(elem).to_bool())
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Checks whether the value is an encrypted string, meaning that it starts with `ENCRYPTED_PREFIX`.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::CfgValue::*;
+    /// 
+    /// assert!(Str("enc:AES256:b64data".into()).is_encrypted());
+    /// assert!(!Str("plain".into()).is_encrypted());
+    /// ```
+    pub fn is_encrypted(&self) -> bool {
+        self.as_str().is_some_and(|s| s.starts_with(ENCRYPTED_PREFIX))
+    }
+
+    /// Parses the value into a duration, if it's a string made of numbers followed by their units,
+    /// such as `30s`, `1.5h` or `1h 30m`. The units are `ns`, `us` (or `µs`), `ms`, `s`, `m`, `h` and `d`.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::CfgValue::*;
+    /// use std::time::Duration;
+    /// 
+    /// assert_eq!(Str("1h 30m".into()).to_duration(), Some(Duration::from_secs(5400)));
+    /// assert_eq!(Str("30".into()).to_duration(), None);
+    /// ```
+    pub fn to_duration(&self) -> Option<std::time::Duration> {
+        self.as_str().and_then(|s| units::parse_duration(s))
+    }
+
+    /// Parses the value into a number of bytes, if it's a string made of a number followed by its unit,
+    /// such as `512B`, `10MB` or `1.5 GiB`. Units are case insensitive, where `KB`, `MB`, `GB`, `TB` and `PB`
+    /// are powers of 1000, while `KiB`, `MiB`, `GiB`, `TiB` and `PiB` are powers of 1024.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::CfgValue::*;
+    /// 
+    /// assert_eq!(Str("10MB".into()).to_byte_size(), Some(10_000_000));
+    /// assert_eq!(Str("512 KiB".into()).to_byte_size(), Some(524_288));
+    /// ```
+    pub fn to_byte_size(&self) -> Option<u64> {
+        self.as_str().and_then(|s| units::parse_byte_size(s))
+    }
+
+    /// Gets the type of the value.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgType, CfgValue::*};
+    /// 
+    /// assert_eq!(Int(5).cfg_type(), CfgType::Int);
+    /// assert_eq!(List(vec![]).cfg_type(), CfgType::List);
+    /// ```
+    pub fn cfg_type(&self) -> CfgType {
+        match self {
+            CfgValue::Int(_) => CfgType::Int,
+            CfgValue::UInt(_) => CfgType::UInt,
+            CfgValue::Float(_) => CfgType::Float,
+            CfgValue::Str(_) => CfgType::Str,
+            CfgValue::Bool(_) => CfgType::Bool,
+            CfgValue::Map(_) => CfgType::Map,
+            CfgValue::List(_) => CfgType::List,
+            CfgValue::Opaque(..) => CfgType::Opaque,
+            #[cfg(feature = "from_toml")]
+            CfgValue::Datetime(_) => CfgType::Datetime,
+            #[cfg(any(feature = "from_json", feature = "from_yaml"))]
+            CfgValue::Null => CfgType::Null,
+            #[cfg(feature = "from_yaml")]
+            CfgValue::BadValue => CfgType::BadValue,
+            #[cfg(feature = "from_yaml")]
+            CfgValue::Alias(_) => CfgType::Alias,
+        }
+    }
+
     /// Assumes the value is a `CfgMap` and attempts to execute `.get()` on it.
     /// Returns `None` if the value isn't a `CfgMap`, or for any reasons `.get()`
     /// may return `None`.
@@ -318,11 +610,11 @@ impl CfgValue {
             Some(*self.as_int().unwrap())
         }
         else if self.check_that(validate(1)) {
-            Some(*self.as_list().unwrap().get(0).unwrap().as_int().unwrap())
+            Some(*self.as_list().unwrap().first().unwrap().as_int().unwrap())
         }
         else if self.check_that(validate(2)) {
             let list = self.as_list().unwrap();
-            let min = *list.get(0).unwrap().as_int().unwrap();
+            let min = *list.first().unwrap().as_int().unwrap();
             let max = *list.get(1).unwrap().as_int().unwrap();
             Some(rand::thread_rng().gen_range(min, max))
         }
@@ -360,11 +652,11 @@ impl CfgValue {
             Some(*self.as_float().unwrap())
         }
         else if self.check_that(validate(1)) {
-            Some(*self.as_list().unwrap().get(0).unwrap().as_float().unwrap())
+            Some(*self.as_list().unwrap().first().unwrap().as_float().unwrap())
         }
         else if self.check_that(validate(2)) {
             let list = self.as_list().unwrap();
-            let min = *list.get(0).unwrap().as_float().unwrap();
+            let min = *list.first().unwrap().as_float().unwrap();
             let max = *list.get(1).unwrap().as_float().unwrap();
             Some(rand::thread_rng().gen_range(min, max))
         }
@@ -376,11 +668,14 @@ impl CfgValue {
     /// Returns the contents of the enum converted into an integer, if possible.
     /// 
     /// If the enum represents a float, it will be converted into an integer.
+    /// If it represents an unsigned integer, it's only converted if it fits.
     pub fn to_int(&self) -> Option<_Int> {
         if let CfgValue::Int(x) = self {
             Some(*x)
         } else if let CfgValue::Float(x) = self {
             Some(*x as _Int)
+        } else if let CfgValue::UInt(x) = self {
+            _Int::try_from(*x).ok()
         } else { None }
     }
 
@@ -392,15 +687,85 @@ impl CfgValue {
             Some(*x)
         } else if let CfgValue::Int(x) = self {
             Some(*x as _Float)
+        } else if let CfgValue::UInt(x) = self {
+            Some(*x as _Float)
         } else { None }
     }
 
+    /// Returns the contents of the enum converted into a boolean, if possible.
+    /// 
+    /// Besides booleans, this accepts the integers `0` and `1`, along with the strings `true`, `false`, `yes`,
+    /// `no`, `on`, `off`, `1` and `0` in any case, which is how booleans tend to be written in environment
+    /// variables and INI files. Use `as_bool` to only accept booleans.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::CfgValue::*;
+    /// 
+    /// assert_eq!(Bool(true).to_bool(), Some(true));
+    /// assert_eq!(Int(0).to_bool(), Some(false));
+    /// assert_eq!(Str("Yes".into()).to_bool(), Some(true));
+    /// assert_eq!(Str("OFF".into()).to_bool(), Some(false));
+    /// assert_eq!(Int(2).to_bool(), None);
+    /// assert_eq!(Str("maybe".into()).to_bool(), None);
+    /// ```
+    pub fn to_bool(&self) -> Option<_Bool> {
+        match self {
+            CfgValue::Bool(x) => Some(*x),
+            CfgValue::Int(0) => Some(false),
+            CfgValue::Int(1) => Some(true),
+            CfgValue::Str(x) => match x.trim().to_ascii_lowercase().as_str() {
+                "true" | "yes" | "on" | "1" => Some(true),
+                "false" | "no" | "off" | "0" => Some(false),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the contents of the enum converted into a string, if it's a scalar value.
+    /// 
+    /// Integers, floats, booleans and datetimes are rendered as text, where floats always keep their
+    /// decimal point. Returns `None` for maps, lists and any other value without a textual form.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*};
+    /// 
+    /// assert_eq!(Int(5).to_str_coerced(), Some("5".into()));
+    /// assert_eq!(Float(2.0).to_str_coerced(), Some("2.0".into()));
+    /// assert_eq!(Bool(true).to_str_coerced(), Some("true".into()));
+    /// assert_eq!(Str("text".into()).to_str_coerced(), Some("text".into()));
+    /// assert_eq!(Map(CfgMap::new()).to_str_coerced(), None);
+    /// ```
+    pub fn to_str_coerced(&self) -> Option<String> {
+        match self {
+            CfgValue::Str(x) => Some(x.clone()),
+            CfgValue::Int(x) => Some(x.to_string()),
+            CfgValue::UInt(x) => Some(x.to_string()),
+            CfgValue::Float(x) => Some(format!("{:?}", x)),
+            CfgValue::Bool(x) => Some(x.to_string()),
+            #[cfg(feature = "from_toml")]
+            CfgValue::Datetime(x) => Some(x.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Computes a stable fingerprint of the value.
+    /// 
+    /// See `CfgMap::fingerprint` for more details.
+    pub fn fingerprint(&self) -> u64 {
+        fingerprint::of_value(self)
+    }
+
     is_type!(is_int, CfgValue::Int);
+    is_type!(is_uint, CfgValue::UInt);
     is_type!(is_float, CfgValue::Float);
     is_type!(is_str, CfgValue::Str);
     is_type!(is_bool, CfgValue::Bool);
     is_type!(is_map, CfgValue::Map);
     is_type!(is_list, CfgValue::List);
+    is_type!(is_opaque, CfgValue::Opaque);
 
     #[cfg(any(feature = "from_json", feature = "from_yaml"))]
     is_type!(is_null [0], CfgValue::Null);
@@ -415,70 +780,128 @@ impl CfgValue {
     is_type!(is_alias, CfgValue::Alias);
 
     as_type!(as_int, _Int, CfgValue::Int);
+    as_type!(as_uint, _UInt, CfgValue::UInt);
     as_type!(as_float, _Float, CfgValue::Float);
     as_type!(as_str, _Str, CfgValue::Str);
     as_type!(as_bool, _Bool, CfgValue::Bool);
     as_type!(as_map, CfgMap, CfgValue::Map);
     as_type!(as_list, Vec<CfgValue>, CfgValue::List);
 
+    #[cfg(feature = "from_toml")]
+    as_type!(as_datetime, Datetime, CfgValue::Datetime);
+
+    /// Returns the format and source of the value if it's a `CfgValue::Opaque`, and `None` otherwise.
+    pub fn as_opaque(&self) -> Option<(&str, &[u8])> {
+        match self {
+            CfgValue::Opaque(format, source) => Some((format, source)),
+            _ => None,
+        }
+    }
+
     as_mut_type!(as_int_mut, _Int, CfgValue::Int);
+    as_mut_type!(as_uint_mut, _UInt, CfgValue::UInt);
     as_mut_type!(as_float_mut, _Float, CfgValue::Float);
     as_mut_type!(as_str_mut, _Str, CfgValue::Str);
     as_mut_type!(as_bool_mut, _Bool, CfgValue::Bool);
     as_mut_type!(as_map_mut, CfgMap, CfgValue::Map);
     as_mut_type!(as_list_mut, Vec<CfgValue>, CfgValue::List);
+
+    #[cfg(feature = "from_toml")]
+    as_mut_type!(as_datetime_mut, Datetime, CfgValue::Datetime);
 }
 
 impl conditions::Checkable for CfgValue {
     fn check_that(&self, c: conditions::Condition) -> bool {
-        return c.execute(self).to_bool();
+        c.execute(self).to_bool()
     }
 }
 
 impl conditions::Checkable for Option<CfgValue> {
     fn check_that(&self, condition: conditions::Condition) -> bool {
-        self.as_ref().map_or(false, |val| val.check_that(condition))
+        self.as_ref().is_some_and(|val| val.check_that(condition))
     }
 }
 
 impl conditions::Checkable for Option<&CfgValue> {
     fn check_that(&self, condition: conditions::Condition) -> bool {
-        self.as_ref().map_or(false, |val| val.check_that(condition))
+        self.as_ref().is_some_and(|val| val.check_that(condition))
     }
 }
 
 impl conditions::Checkable for Option<&mut CfgValue> {
     fn check_that(&self, condition: conditions::Condition) -> bool {
-        self.as_ref().map_or(false, |val| val.check_that(condition))
+        self.as_ref().is_some_and(|val| val.check_that(condition))
     }
 }
 
-fn split_once(in_string: &str, pat: char) -> (String, Option<String>) {
-    if in_string.find(pat).is_none() {
-        return (in_string.into(), None);
+fn split_once(in_string: &str, pat: char) -> (&str, Option<&str>) {
+    match in_string.split_once(pat) {
+        Some((first, second)) => (first, Some(second)),
+        None => (in_string, None),
     }
+}
 
-    let mut splitter = in_string.splitn(2, pat);
-    let first = splitter.next().unwrap().to_string();
-    let second = splitter.next().unwrap().to_string();
+fn rsplit_once(in_string: &str, pat: char) -> (Option<&str>, &str) {
+    match in_string.rsplit_once(pat) {
+        Some((first, second)) => (Some(first), second),
+        None => (None, in_string),
+    }
+}
 
-    (first, Some(second))
+/// Joins a path with a key, treating an empty path as the root.
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() { key.to_string() } else { format!("{}/{}", path, key) }
 }
 
-fn rsplit_once(in_string: &str, pat: char) -> (Option<String>, String) {
-    if in_string.find(pat).is_none() {
-        return (None, in_string.into());
+/// Returns the direct children of a value, along with their keys.
+/// List elements are keyed by their index.
+fn children_of(value: &CfgValue) -> Vec<(String, &CfgValue)> {
+    match value {
+        CfgValue::Map(map) => map.iter().map(|(k, v)| (k.clone(), v)).collect(),
+        CfgValue::List(list) => list.iter().enumerate().map(|(i, v)| (i.to_string(), v)).collect(),
+        _ => Vec::new()
     }
+}
+
+/// Matches the pattern `segments` against `children`, pushing every match onto `out`.
+fn collect_matches<'a>(children: Vec<(String, &'a CfgValue)>, path: &str, segments: &[&str], out: &mut Vec<(String, &'a CfgValue)>) {
+    let (segment, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => return,
+    };
 
-    let mut splitter = in_string.rsplitn(2, pat);
-    let first = splitter.next().unwrap().to_string();
-    let second = splitter.next().unwrap().to_string();
+    for (key, value) in children {
+        let full = join_path(path, &key);
 
-    (Some(second), first)
+        if *segment == "**" {
+            // `**` can match zero levels...
+            if rest.is_empty() {
+                out.push((full.clone(), value));
+            } else {
+                collect_matches(vec![(key.clone(), value)], path, rest, out);
+            }
+
+            // ...or one or more levels.
+            collect_matches(children_of(value), &full, segments, out);
+        } else if *segment == "*" || *segment == key {
+            if rest.is_empty() {
+                out.push((full, value));
+            } else {
+                collect_matches(children_of(value), &full, rest, out);
+            }
+        }
+    }
 }
 
+/// The `HashMap` within a `CfgMap`, which it derefs to.
+///
+/// This always uses the standard hasher. Since `CfgMap` derefs to it, its hasher is part of the public API,
+/// so a feature swapping it for a faster one would break code expecting a `HashMap<String, CfgValue>`
+/// whenever any crate in the dependency graph enabled it.
+pub type CfgHashMap = HashMap<String, CfgValue>;
+
 impl Deref for CfgMap {
-    type Target = HashMap<String, CfgValue>;
+    type Target = CfgHashMap;
 
     fn deref(&self) -> &Self::Target {
         &self.internal_map
@@ -487,7 +910,7 @@ impl Deref for CfgMap {
 
 impl DerefMut for CfgMap {
     fn deref_mut (&mut self) -> &mut Self::Target {
-        &mut self.internal_map
+        self.internal_map_mut()
     }
 }
 
@@ -501,43 +924,380 @@ impl From<Option<CfgValue>> for CfgValue {
 
 /// A configuration map, containing helper functions and effectively being a wrapper
 /// around a `HashMap`s.
-#[derive(Debug, Clone, PartialEq)]
+/// 
+/// Two maps are equal if their contents and `default` are equal. Pinned types, locks, sources and accessed paths aren't compared.
+/// 
+/// Values marked with `mark_secret` are printed as `***` by the `Debug` implementation.
+/// 
+/// Cloning a map is cheap, since the clone shares its contents with the original. A map only copies its
+/// contents the first time it's changed while shared, and only for the maps along the changed path.
+#[derive(Clone, Default)]
 pub struct CfgMap {
-    /// An internal map representing the configuration.
-    internal_map: HashMap<String, CfgValue>,
+    /// An internal map representing the configuration, which is shared between clones until either is changed.
+    internal_map: Arc<CfgHashMap>,
+
+    /// A path to the default subobject, which may or may not end with a `/`. See `set_default_path`.
+    pub default: String,
+
+    /// Paths to further default subobjects, which options fall back to in order if they aren't found
+    /// within `default` either, such as for user, workspace and system defaults.
+    pub fallbacks: Vec<String>,
+
+    /// State attached by opt-in features, allocated the first time one is used.
+    state: Option<Box<MapState>>,
+}
+
+/// State attached to a `CfgMap` by opt-in features. This is kept behind a pointer,
+/// so that maps that don't use any of these features stay small.
+#[derive(Debug, Clone, Default)]
+struct MapState {
+    /// The types pinned to paths using `pin_type`.
+    pins: HashMap<String, CfgType>,
+
+    /// The paths locked using `lock_path`.
+    locks: HashSet<String>,
+
+    /// The writes made to each path using `add_from`, in order, along with their source.
+    writes: HashMap<String, Vec<(String, CfgValue)>>,
+
+    /// The paths read since `track_access` was called, if it was.
+    accessed: Option<tracking::PathLog>,
+
+    /// The paths deprecated using `deprecate`.
+    deprecations: HashMap<String, deprecation::Deprecation>,
+
+    /// The deprecated paths that were already reported.
+    warned: tracking::PathLog,
+
+    /// The function set using `on_deprecated`.
+    deprecation_handler: Option<deprecation::Handler>,
 
-    /// A path to the default subobject.
-    pub default: String
+    /// The patterns marked as secret using `mark_secret`.
+    secrets: Vec<String>,
+
+    /// The migrations registered using `register_migration`.
+    migrations: migrations::Migrations,
+
+    /// The defaults added using `add_default`, kept apart from the rest of the map.
+    defaults: CfgMap,
+}
+
+impl fmt::Debug for CfgMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secrets = self.secret_paths();
+        secret::RedactedMap { map: self, path: "", secrets: &secrets }.fmt(f)
+    }
+}
+
+impl PartialEq for CfgMap {
+    fn eq(&self, other: &CfgMap) -> bool {
+        (Arc::ptr_eq(&self.internal_map, &other.internal_map) || self.internal_map == other.internal_map)
+            && self.default == other.default
+            && self.fallbacks == other.fallbacks
+    }
 }
 
 impl CfgMap {
 
     /// Creates a new empty CfgMap.
     pub fn new() -> CfgMap {
-        CfgMap { internal_map: HashMap::new(), default: String::new() }
+        CfgMap::default()
+    }
+
+    /// Gets the state attached by opt-in features, if any was.
+    fn state(&self) -> Option<&MapState> {
+        self.state.as_deref()
+    }
+
+    /// Gets the state attached by opt-in features, allocating it if needed.
+    fn state_mut(&mut self) -> &mut MapState {
+        self.state.get_or_insert_with(Default::default)
+    }
+
+    /// Checks that `value` can be written at `path`, the same way as `add`, which fails if `path` affects a locked
+    /// path or `value` changes the type of a pinned path.
+    pub(crate) fn check_write(&self, path: &str, value: &CfgValue) -> Result<(), CfgError> {
+        if self.affects_lock(path) {
+            return Err(CfgError::Locked(path.to_string()));
+        }
+        self.check_pins(path, value)
+    }
+
+    /// Checks that the contents of `replacement` can replace the contents of the map, which fails if they'd change
+    /// a locked path, or the type of a pinned path.
+    fn check_replacement(&self, replacement: &CfgMap) -> Result<(), CfgError> {
+        let state = match self.state() {
+            Some(state) => state,
+            None => return Ok(()),
+        };
+
+        if let Some(lock) = state.locks.iter().find(|lock| self.get_untracked(lock) != replacement.get_untracked(lock)) {
+            return Err(CfgError::Locked(lock.clone()));
+        }
+
+        for (pinned, &expected) in &state.pins {
+            if let Some(found) = replacement.get_untracked(pinned).map(CfgValue::cfg_type).filter(|&found| found != expected) {
+                return Err(CfgError::TypeMismatch { path: pinned.clone(), expected, found });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that `value`, about to be added at `path`, doesn't change the type of a pinned path.
+    fn check_pins(&self, path: &str, value: &CfgValue) -> Result<(), CfgError> {
+        match self.state() {
+            Some(state) => pin::check(&state.pins, path, value),
+            None => Ok(()),
+        }
     }
 
     /// Initialises a `CfgMap` using the `map` that's passed in.
     pub fn with_hashmap(map: HashMap<String, CfgValue>) -> CfgMap {
-        CfgMap { internal_map: map, default: String::new() }
+        CfgMap::with_internal_map(map)
+    }
+
+    /// Initialises a `CfgMap` using `map` as its internal map.
+    pub(crate) fn with_internal_map(map: CfgHashMap) -> CfgMap {
+        CfgMap { internal_map: Arc::new(map), ..CfgMap::default() }
+    }
+
+    /// Gets the internal map, the same as dereferencing the map would.
+    pub fn raw_map(&self) -> &CfgHashMap {
+        &self.internal_map
+    }
+
+    /// Gets the internal map for changing it directly. This bypasses paths, locks, pinned types, sources,
+    /// and every other check done by the methods of `CfgMap`, so use it with care.
+    /// 
+    /// Dereferencing the map mutably does the same. Use `checked` for a map where this is the only way to
+    /// change the internal map directly, so it's easy to find every place that bypasses these checks.
+    pub fn raw_map_mut(&mut self) -> &mut CfgHashMap {
+        self.internal_map_mut()
+    }
+
+    /// Gets a map sharing the contents of this one, without any of its pins, locks or other state.
+    pub(crate) fn contents(&self) -> CfgMap {
+        CfgMap { internal_map: self.internal_map.clone(), ..CfgMap::default() }
+    }
+
+    /// Gets the internal map for changing it, copying it first if it's shared with a clone.
+    pub(crate) fn internal_map_mut(&mut self) -> &mut CfgHashMap {
+        Arc::make_mut(&mut self.internal_map)
     }
 
     #[cfg(feature = "from_json")]
     /// Initialises a `CfgMap` from a json `Value`.
+    /// 
+    /// ## Panics
+    /// 
+    /// Panics if the value isn't an `Object`, or has maps and lists nested more than 128 levels deep.
+    /// Use `CfgMap::try_from` to handle these cases instead. Integers above `i64::MAX` become `CfgValue::UInt`.
     pub fn from_json(value: JsonValue) -> CfgMap {
-        from_json::json_to_cfg(value)
+        from_json::json_to_cfg(value).unwrap_or_else(|e| panic!("{}", e))
     }
 
     #[cfg(feature = "from_toml")]
     /// Initialises a `CfgMap` from a toml `Value`.
+    /// 
+    /// ## Panics
+    /// 
+    /// Panics if the value isn't a `Table`, or has tables and arrays nested more than 128 levels deep.
+    /// Use `CfgMap::try_from` to handle these cases instead.
     pub fn from_toml(value: TomlValue) -> CfgMap {
-        from_toml::toml_to_cfg(value)
+        from_toml::toml_to_cfg(value).unwrap_or_else(|e| panic!("{}", e))
     }
 
     #[cfg(feature = "from_yaml")]
     /// Initialises a `CfgMap` from a yaml `Value`.
+    /// 
+    /// ## Panics
+    /// 
+    /// Panics if the value isn't a `Hash`, has a non-string key at its root, contains an invalid real, or has hashes
+    /// and arrays nested more than 128 levels deep. Use `CfgMap::try_from` to handle these cases instead.
+    /// Nested hashes with non-string keys become `CfgValue::Opaque`.
     pub fn from_yaml(value: YamlValue) -> CfgMap {
-        from_yaml::yaml_to_cfg(value)
+        from_yaml::yaml_to_cfg(value).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    #[cfg(any(feature = "from_json", feature = "from_toml", feature = "from_yaml"))]
+    /// Reads a `CfgMap` from a document in the given `format`, such as a file or stdin.
+    /// See the reader for each format for more details.
+    /// 
+    /// ## Examples
+    /// ```
+    /// # #[cfg(feature = "from_json")] {
+    /// use cfgmap::{CfgMap, Checkable, Condition::*, Format};
+    /// 
+    /// let document = r#"{ "db": { "port": 5432 } }"#;
+    /// let cmap = CfgMap::from_reader(document.as_bytes(), Format::Json).unwrap();
+    /// 
+    /// assert!(cmap.get("db/port").check_that(IsExactlyInt(5432)));
+    /// # }
+    /// ```
+    pub fn from_reader<R: std::io::Read>(reader: R, format: Format) -> Result<CfgMap, CfgError> {
+        reader::read(reader, format)
+    }
+
+    #[cfg(feature = "from_json")]
+    /// Reads a `CfgMap` from a json document, parsing it while it's read rather than reading it into a string first.
+    /// Reading from a file or socket is faster if it's wrapped in a `BufReader`.
+    /// 
+    /// Returns `CfgError::LoadFailed` if the document can't be read or isn't valid json.
+    pub fn from_json_reader<R: std::io::Read>(reader: R) -> Result<CfgMap, CfgError> {
+        reader::read_json(reader)
+    }
+
+    #[cfg(feature = "from_toml")]
+    /// Reads a `CfgMap` from a toml document. The toml parser can't parse documents while they're read,
+    /// so the whole document is read into a string first.
+    /// 
+    /// Returns `CfgError::LoadFailed` if the document can't be read or isn't valid toml.
+    pub fn from_toml_reader<R: std::io::Read>(reader: R) -> Result<CfgMap, CfgError> {
+        reader::read_toml(reader)
+    }
+
+    #[cfg(feature = "from_yaml")]
+    /// Reads a `CfgMap` from the first document within a yaml stream. The yaml parser can't parse documents
+    /// while they're read, so the whole stream is read into a string first.
+    /// 
+    /// Returns `CfgError::LoadFailed` if the stream can't be read or isn't valid yaml, and `CfgError::NotAMap`
+    /// if it doesn't contain any documents.
+    pub fn from_yaml_reader<R: std::io::Read>(reader: R) -> Result<CfgMap, CfgError> {
+        reader::read_yaml(reader)
+    }
+
+    #[cfg(any(feature = "from_json", feature = "from_toml", feature = "from_yaml"))]
+    /// Reads a `CfgMap` from the file at `path`, in the given `format`. See `from_reader` for more details.
+    /// 
+    /// The path is recorded as the source of every value read, the same as `add_from`, so `provenance` can tell
+    /// which file a value came from after maps are merged.
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P, format: Format) -> Result<CfgMap, CfgError> {
+        let file = std::fs::File::open(path.as_ref()).map_err(|e| CfgError::LoadFailed(e.to_string()))?;
+        let mut map = reader::read(std::io::BufReader::new(file), format)?;
+        map.record_source(&path.as_ref().display().to_string());
+        Ok(map)
+    }
+
+    #[cfg(any(feature = "from_json", feature = "from_toml", feature = "from_yaml"))]
+    /// Reads a `CfgMap` from a document in the given `format`, the same as `from_reader`, and validates it against
+    /// `schema`, so that an invalid map never reaches the rest of the application.
+    /// 
+    /// Returns `CfgError::Invalid` with the full `ValidationReport` if any rule with `Severity::Error` fails.
+    /// 
+    /// ## Examples
+    /// ```
+    /// # #[cfg(feature = "from_json")] {
+    /// use cfgmap::{CfgError, CfgMap, CfgValue::*, Format, Schema};
+    /// 
+    /// let mut rules = CfgMap::new();
+    /// rules.add("port", Str("int in 1..65535".into()));
+    /// let schema = Schema::from_map(&rules).unwrap();
+    /// 
+    /// let cmap = CfgMap::from_reader_validated(r#"{ "port": 80 }"#.as_bytes(), Format::Json, &schema).unwrap();
+    /// assert_eq!(cmap.get("port"), Some(&Int(80)));
+    /// 
+    /// let error = CfgMap::from_reader_validated(r#"{ "port": 0 }"#.as_bytes(), Format::Json, &schema).unwrap_err();
+    /// assert!(matches!(error, CfgError::Invalid(report) if report.failures[0].path() == "port"));
+    /// # }
+    /// ```
+    pub fn from_reader_validated<R: std::io::Read>(reader: R, format: Format, schema: &Schema) -> Result<CfgMap, CfgError> {
+        let map = reader::read(reader, format)?;
+        schema.validate(&map).into_result().map_err(CfgError::Invalid)?;
+        Ok(map)
+    }
+
+    #[cfg(any(feature = "from_json", feature = "from_toml", feature = "from_yaml"))]
+    /// Reads a `CfgMap` from the file at `path`, in the given `format`, and validates it against `schema`.
+    /// See `from_reader_validated` for more details.
+    pub fn from_file_validated<P: AsRef<std::path::Path>>(path: P, format: Format, schema: &Schema) -> Result<CfgMap, CfgError> {
+        let map = CfgMap::from_file(path, format)?;
+        schema.validate(&map).into_result().map_err(CfgError::Invalid)?;
+        Ok(map)
+    }
+
+    #[cfg(all(feature = "async", any(feature = "from_json", feature = "from_toml", feature = "from_yaml")))]
+    /// Reads a `CfgMap` from a document in the given `format` without blocking, the same as `from_reader`.
+    /// The whole document is read before it's parsed. Only available if using `async`.
+    /// 
+    /// ## Examples
+    /// ```
+    /// # #[cfg(feature = "from_json")] {
+    /// use cfgmap::{CfgMap, Checkable, Condition::*, Format};
+    /// 
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// let document = r#"{ "db": { "port": 5432 } }"#;
+    /// let cmap = CfgMap::from_reader_async(document.as_bytes(), Format::Json).await.unwrap();
+    /// 
+    /// assert!(cmap.get("db/port").check_that(IsExactlyInt(5432)));
+    /// # });
+    /// # }
+    /// ```
+    pub async fn from_reader_async<R: tokio::io::AsyncRead + Unpin>(reader: R, format: Format) -> Result<CfgMap, CfgError> {
+        reader::read_async(reader, format).await
+    }
+
+    #[cfg(all(feature = "async", any(feature = "from_json", feature = "from_toml", feature = "from_yaml")))]
+    /// Reads a `CfgMap` from the file at `path`, in the given `format`, without blocking. This needs a `tokio`
+    /// runtime, the same as `tokio::fs`. The path is recorded as the source of every value read, the same as
+    /// `from_file`. See `from_reader_async` for more details.
+    pub async fn from_file_async<P: AsRef<std::path::Path>>(path: P, format: Format) -> Result<CfgMap, CfgError> {
+        let file = tokio::fs::File::open(path.as_ref()).await.map_err(|e| CfgError::LoadFailed(e.to_string()))?;
+        let mut map = reader::read_async(tokio::io::BufReader::new(file), format).await?;
+        map.record_source(&path.as_ref().display().to_string());
+        Ok(map)
+    }
+
+    #[cfg(any(feature = "from_json", feature = "from_toml", feature = "from_yaml"))]
+    /// Writes the map as a document in the given `format`, using the default `WriteOptions`. Keys are written
+    /// in sorted order, so that the same map is always written the same way. Only the contents of the map are written,
+    /// without the runtime information attached using `attach_runtime_info`.
+    /// 
+    /// Returns `CfgError::Unrepresentable` if a value can't be represented by `format`, such as `Null` in toml,
+    /// in which case nothing is written, or `CfgError::SaveFailed` if writing fails.
+    /// 
+    /// ## Examples
+    /// ```
+    /// # #[cfg(feature = "from_toml")] {
+    /// use cfgmap::{CfgMap, CfgValue::*, Format};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("name", Str("app".into()));
+    /// cmap.add("db", Map(CfgMap::new()));
+    /// cmap.add("db/port", Int(5432));
+    /// 
+    /// let mut document = Vec::new();
+    /// cmap.to_writer(&mut document, Format::Toml).unwrap();
+    /// 
+    /// assert_eq!(String::from_utf8(document).unwrap(), "name = \"app\"\n\n[db]\nport = 5432\n");
+    /// # }
+    /// ```
+    pub fn to_writer<W: std::io::Write>(&self, writer: W, format: Format) -> Result<(), CfgError> {
+        writer::write(self, writer, format, &WriteOptions::default())
+    }
+
+    #[cfg(any(feature = "from_json", feature = "from_toml", feature = "from_yaml"))]
+    /// Writes the map the same way as `to_writer`, formatted using `options`.
+    pub fn to_writer_with<W>(&self, writer: W, format: Format, options: &WriteOptions) -> Result<(), CfgError>
+        where W: std::io::Write
+    {
+        writer::write(self, writer, format, options)
+    }
+
+    #[cfg(any(feature = "from_json", feature = "from_toml", feature = "from_yaml"))]
+    /// Writes the map to the file at `path`, in the given `format`, the same way as `to_writer`.
+    /// 
+    /// The map is written to a temporary file next to `path` first, which is then renamed over `path`,
+    /// so the file at `path` is never left partially written.
+    pub fn to_file<P: AsRef<std::path::Path>>(&self, path: P, format: Format) -> Result<(), CfgError> {
+        writer::write_file(self, path.as_ref(), format, &WriteOptions::default())
+    }
+
+    #[cfg(any(feature = "from_json", feature = "from_toml", feature = "from_yaml"))]
+    /// Writes the map to the file at `path` the same way as `to_file`, formatted using `options`.
+    pub fn to_file_with<P>(&self, path: P, format: Format, options: &WriteOptions) -> Result<(), CfgError>
+        where P: AsRef<std::path::Path>
+    {
+        writer::write_file(self, path.as_ref(), format, options)
     }
 
     /// Adds a new entry in the configuration.
@@ -549,13 +1309,17 @@ impl CfgMap {
     /// This key can also index into lists. So, for example `a/0/b` would try checking if `"a"`
     /// is a list, and index into it. Otherwise it will try to find an internal map with the key `0`.
     /// 
+    /// If the last segment of the path is `-` and the path before it points to a list, the
+    /// value is appended to the end of that list instead, similar to JSON Pointer.
+    /// So `plugins/-` would push the value onto the list at `plugins`.
+    /// 
     /// In order to add a default value to a normal submap - you would need to do this manually,
     /// as this function will always use `get_mut`.
     /// 
     /// ## Examples
     /// 
     /// ```
-    /// use cfgmap::{CfgMap, CfgValue::*};
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
     /// 
     /// let mut cmap = CfgMap::new();
     /// 
@@ -568,334 +1332,304 @@ impl CfgMap {
     /// // Works - returns the old value.
     /// let r = cmap.add("k1", Float(8.0));
     /// assert_eq!(Ok(Some(Int(5))), r);
+    /// 
+    /// // Appends to the end of a list.
+    /// cmap.add("plugins", List(vec![Str("a".into())]));
+    /// assert_eq!(Ok(None), cmap.add("plugins/-", Str("b".into())));
+    /// assert!(cmap.get("plugins/1").check_that(IsExactlyStr("b".into())));
     /// ```
     /// 
     /// ## Return values
     /// 
-    /// - `Err` if the path as specified by `key` isn't found. In the case above for example, `get_mut("a")` returns a `None`.
+    /// - `Err(CfgError::InvalidPath)` if the path as specified by `key` isn't found. In the case above for example, `get_mut("a")` returns a `None`.
+    ///   This is also the case when appending with `-` to something that isn't a list.
+    /// - `Err(CfgError::TypeMismatch)` if the value, or anything within it, doesn't match a type pinned with `pin_type`.
+    /// - `Err(CfgError::Locked)` if the path is locked with `lock_path`, or contains a locked path.
     /// - `Ok(Some(CfgValue))` if the path as specified by key already contained a value, and was overwritten. In this case, the old value is returned.
     /// - `Ok(None)` otherwise.
-    pub fn add(&mut self, key: &str, value: CfgValue) -> Result<Option<CfgValue>, ()> {
-        let (path, key) = rsplit_once(key, '/');
+    pub fn add(&mut self, key: &str, value: CfgValue) -> Result<Option<CfgValue>, CfgError> {
+        self.check_write(key, &value)?;
 
-        if path.is_none(){
-            Ok(self.internal_map.insert(key.to_string(), value))
-        }
-        else {
-            let subtree = self.get_mut(&path.unwrap());
+        let (path, last) = rsplit_once(key, '/');
 
-            if subtree.check_that(Condition::IsMap) {
-                subtree.unwrap().as_map_mut().unwrap().add(&key, value)
-            }
-            else {
-                Err(())
+        match path {
+            None => Ok(self.internal_map_mut().insert(last.to_string(), value)),
+            Some(path) => match self.get_mut_unlocked(path) {
+                Some(CfgValue::Map(map)) => map.add(last, value),
+                Some(CfgValue::List(list)) if last == "-" => {
+                    list.push(value);
+                    Ok(None)
+                },
+                _ => Err(CfgError::InvalidPath(key.to_string()))
             }
         }
     }
 
-    /// Gets a reference to a value from within the configuration.
+    /// Adds a new entry in the configuration, the same way as `add`, but creates any missing maps
+    /// along the path first, similar to `mkdir -p`.
     /// 
-    /// The `key` can be of the form of the path `"a/b/...y/z/"`, in which case it will
-    /// go through the inner submaps `"a/b/..."` until a submap isn't found, or the end is reached.
-    /// This is for convenience sake, as doing this manually can prove to be verbose.
-    /// 
-    /// This key can also index into lists. So, for example `a/0/b` would try checking if `"a"`
-    /// is a list, and index into it. Otherwise it will try to find an internal map with the key `0`.
-    /// 
-    /// Returns `None` if the key doesn't exist.
+    /// Returns an error if the path goes through a value that isn't a map or list, as well as
+    /// for any of the reasons `add` would. Maps that were created before the error are kept.
     /// 
     /// ## Examples
     /// ```
     /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
     /// 
     /// let mut cmap = CfgMap::new();
-    /// let mut submap = CfgMap::new();
     /// 
-    /// submap.add("key", Int(5));
-    ///
-    /// cmap.add("sub", Map(submap));
+    /// assert!(cmap.add("a/b/c", Int(5)).is_err());
+    /// assert_eq!(Ok(None), cmap.add_force("a/b/c", Int(5)));
+    /// assert!(cmap.get("a/b").check_that(IsMap));
     /// 
-    /// assert!(cmap.get("sub").check_that(IsMap));
-    /// assert!(cmap.get("sub/key").check_that(IsExactlyInt(5)));
+    /// // `c` isn't a map, so nothing can be added within it.
+    /// assert!(cmap.add_force("a/b/c/d", Int(5)).is_err());
     /// ```
-    pub fn get(&self, key: &str) -> Option<&CfgValue> {
-        let (h, t) = split_once(key, '/');
-
-        if t.is_none() {
-            self.internal_map.get(key)
-        }
-        else {
-            let next = self.internal_map.get(&h);
-
-            if let Some(CfgValue::Map(map)) = next {
-                map.get(&t.unwrap())
-            } else if let Some(CfgValue::List(list)) = next {
-                // Get the next segment of the path, and parse as a list index.
-                let (index,new_t) = split_once(&t.unwrap(), '/');
-                let index = index.parse::<usize>();
-
-                // If it's an invalid usize, then the whole path is invalid.
-                if index.is_err() {
-                    None
-                }
-                else if new_t.is_none() {
-                    list.get(index.unwrap())
-                } else {
-                    list.get(index.unwrap()).and_then(|op| {
-                        op.as_map()
-                    }).and_then(|map| {
-                        map.get(&new_t.unwrap())
-                    })
-                }
-            } else {
-                None
-            }
-        }
+    pub fn add_force(&mut self, key: &str, value: CfgValue) -> Result<Option<CfgValue>, CfgError> {
+        self.add_parents(key)?;
+        self.add(key, value)
     }
 
-    /// Gets a mutable reference to a value from within the configuration.
-    /// 
-    /// Returns `None` if the key doesn't exist.
-    /// 
-    /// The `key` can be of the form of the path `"a/b/...y/z/"`, in which case it will
-    /// go through the inner submaps `"a/b/..."` until a submap isn't found, or the end is reached.
-    /// This is for convenience sake, as doing this manually can prove to be verbose.
+    /// Gets a mutable reference to the value at `key`, first adding the value returned by `f` if there isn't one.
+    /// Any missing maps along the path are created, the same way as `add_force`.
     /// 
-    /// This key can also index into lists. So, for example `a/0/b` would try checking if `"a"`
-    /// is a list, and index into it. Otherwise it will try to find an internal map with the key `0`.
+    /// Returns an error if the path is locked, or for any of the reasons `add_force` would.
     /// 
     /// ## Examples
     /// ```
     /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
     /// 
     /// let mut cmap = CfgMap::new();
-    /// let mut submap = CfgMap::new();
-    ///
-    /// cmap.add("sub", Map(submap));
     /// 
-    /// let mut submap = cmap.get_mut("sub");
-    /// assert!(submap.check_that(IsMap));
+    /// if let Int(count) = cmap.get_or_insert_with("stats/count", || Int(0)).unwrap() {
+    ///     *count += 1;
+    /// }
     /// 
-    /// submap.unwrap().as_map_mut().unwrap().add("key", Int(5));
-    /// assert!(cmap.get_mut("sub/key").check_that(IsExactlyInt(5)));
+    /// // The existing value is used, so `f` isn't called.
+    /// cmap.get_or_insert_with("stats/count", || unreachable!()).unwrap();
+    /// assert!(cmap.get("stats/count").check_that(IsExactlyInt(1)));
     /// ```
-    pub fn get_mut(&mut self, key: &str) -> Option<&mut CfgValue> {
-        let (h, t) = split_once(key, '/');
-
-        if t.is_none() {
-            self.internal_map.get_mut(key)
+    pub fn get_or_insert_with<F: FnOnce() -> CfgValue>(&mut self, key: &str, f: F) -> Result<&mut CfgValue, CfgError> {
+        if self.affects_lock(key) {
+            return Err(CfgError::Locked(key.to_string()));
         }
-        else {
-            let next = self.internal_map.get_mut(&h);
-
-            if let Some(CfgValue::Map(map)) = next {
-                map.get_mut(&t.unwrap())
-            } else if let Some(CfgValue::List(list)) = next {
-                // Get the next segment of the path, and parse as a list index.
-                let (index,new_t) = split_once(&t.unwrap(), '/');
-                let index = index.parse::<usize>();
-
-                // If it's an invalid usize, then the whole path is invalid.
-                if index.is_err() {
-                    None
-                }
-                else if new_t.is_none() {
-                    list.get_mut(index.unwrap())
-                } else {
-                    list.get_mut(index.unwrap()).and_then(|op| {
-                        op.as_map_mut()
-                    }).and_then(|map| {
-                        map.get_mut(&new_t.unwrap())
-                    })
-                }
-            } else {
-                None
-            }
+
+        if self.get(key).is_none() {
+            self.add_force(key, f())?;
         }
+
+        // Appending with `-` doesn't leave a value at `key` itself.
+        self.get_mut_unlocked(key).ok_or_else(|| CfgError::InvalidPath(key.to_string()))
     }
 
-    /// Deletes a key from the map, and returns the value associated with it.
+    /// Adds a new entry in the configuration, the same way as `add`, recording `source` as the one that wrote it.
     /// 
-    /// Returns `None` if the key doesn't exist.
-    /// 
-    /// The `key` can be of the form of the path `"a/b/...y/z/"`, in which case it will
-    /// go through the inner submaps `"a/b/..."` until a submap isn't found, or the end is reached.
-    /// This is for convenience sake, as doing this manually can prove to be verbose.
-    /// 
-    /// This key can also index into lists. So, for example `a/0/b` would try checking if `"a"`
-    /// is a list, and index into it. Otherwise it will try to find an internal map with the key `0`.
+    /// The source can be anything that names where the value came from, such as a file or an environment
+    /// variable. Use `source_of` to find which source last wrote a path, and `detect_conflicts` to find paths
+    /// that different sources disagree on. Nothing is recorded if adding fails.
     /// 
     /// ## Examples
     /// ```
-    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// use cfgmap::{CfgMap, CfgValue::*};
     /// 
     /// let mut cmap = CfgMap::new();
-    ///
-    /// cmap.add("sub", Map(CfgMap::new()));
-    /// cmap.add("sub/int", Int(5));
-    /// 
-    /// let num = cmap.remove("sub/int");
-    /// let nothing = cmap.remove("sub/nothing");
+    /// cmap.add_from("config.toml", "port", Int(80));
+    /// cmap.add_from("env", "port", Int(8080));
     /// 
-    /// assert!(cmap.get("sub/int").is_none());
-    /// assert!(num.check_that(IsExactlyInt(5)));
-    /// assert!(nothing.is_none());
+    /// assert_eq!(cmap.source_of("port"), Some("env"));
+    /// assert_eq!(cmap.detect_conflicts()[0].path, "port");
     /// ```
-    pub fn remove(&mut self, key: &str) -> Option<CfgValue> {
-        self.remove_entry(key).map(|(_, value)| value)
+    pub fn add_from(&mut self, source: &str, key: &str, value: CfgValue) -> Result<Option<CfgValue>, CfgError> {
+        let old = self.add(key, value.clone())?;
+        self.state_mut().writes.entry(key.to_string()).or_default().push((source.to_string(), value));
+        Ok(old)
     }
 
-    /// Deletes a key from the map, and returns the value associated with it, if the value obeys the 
-    /// conditions as passed. Useful for when you want to make sure to avoid deleting another value.
+    /// Gets the name of the source that last wrote `path` using `add_from`.
     /// 
-    /// Returns `None` if the key doesn't exist, or the value associated with the key doesn't obey the condition.
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*};
     /// 
-    /// The `key` can be of the form of the path `"a/b/...y/z/"`, in which case it will
-    /// go through the inner submaps `"a/b/..."` until a submap isn't found, or the end is reached.
-    /// This is for convenience sake, as doing this manually can prove to be verbose.
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add_from("defaults", "port", Int(80));
+    /// cmap.add("host", Str("localhost".into()));
     /// 
-    /// This key can also index into lists. So, for example `a/0/b` would try checking if `"a"`
-    /// is a list, and index into it. Otherwise it will try to find an internal map with the key `0`.
+    /// assert_eq!(cmap.source_of("port"), Some("defaults"));
+    /// assert_eq!(cmap.source_of("host"), None);
+    /// ```
+    pub fn source_of(&self, path: &str) -> Option<&str> {
+        self.state()?.writes.get(path)
+            .and_then(|writes| writes.last())
+            .map(|(source, _)| source.as_str())
+    }
+
+    /// Gets the name of the source that the value at `path` came from, which is the source that last wrote it,
+    /// or the closest map or list containing it, using `add_from`. Maps read using `from_file` record the file's
+    /// path as the source of each of their values.
     /// 
     /// ## Examples
     /// ```
-    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// use cfgmap::{CfgMap, CfgValue::*};
     /// 
-    /// let mut cmap = CfgMap::new();
-    ///
-    /// cmap.add("sub", Map(CfgMap::new()));
-    /// cmap.add("sub/int", Int(5));
+    /// let mut db = CfgMap::new();
+    /// db.add("host", Str("localhost".into()));
+    /// db.add("port", Int(5432));
     /// 
-    /// let float = cmap.remove_if("sub/int", IsFloat);
-    /// assert!(cmap.get("sub/int").is_some());
-    /// assert!(float.is_none());
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add_from("prod.yaml", "db", Map(db));
+    /// cmap.add_from("env", "db/port", Int(6543));
     /// 
-    /// let int = cmap.remove_if("sub/int", IsInt);
-    /// assert!(cmap.get("sub/int").is_none());
-    /// assert!(int.check_that(IsExactlyInt(5)));
+    /// assert_eq!(cmap.provenance("db/port"), Some("env"));
+    /// assert_eq!(cmap.provenance("db/host"), Some("prod.yaml"));
+    /// assert_eq!(cmap.source_of("db/host"), None);
     /// ```
-    pub fn remove_if(&mut self, key: &str, condition: Condition) -> Option<CfgValue> {
-        if self.get(key).check_that(condition) { self.remove(key) } else { None }
+    pub fn provenance(&self, path: &str) -> Option<&str> {
+        let mut path = path;
+        loop {
+            if let Some(source) = self.source_of(path) {
+                return Some(source);
+            }
+            path = rsplit_once(path, '/').0?;
+        }
     }
 
-    /// Deletes a key from the map, and returns the key and value associated with it.
+    #[cfg(any(feature = "from_json", feature = "from_toml", feature = "from_yaml"))]
+    /// Records `source` as the one that wrote every leaf value within the map, the same as `add_from`.
+    fn record_source(&mut self, source: &str) {
+        let leaves: Vec<(String, CfgValue)> = self.walk().map(|(path, value)| (path, value.clone())).collect();
+        let writes = &mut self.state_mut().writes;
+        for (path, value) in leaves {
+            writes.entry(path).or_default().push((source.to_string(), value));
+        }
+    }
+
+    /// Gets every path that was written using `add_from` by more than one source, with differing values.
+    /// Conflicts are sorted by path, and contain every write to the path in order.
     /// 
-    /// Returns `None` if the key doesn't exist.
+    /// A single source overwriting its own value isn't a conflict, and neither are
+    /// different sources writing the same value.
     /// 
-    /// The `key` can be of the form of the path `"a/b/...y/z/"`, in which case it will
-    /// go through the inner submaps `"a/b/..."` until a submap isn't found, or the end is reached.
-    /// This is for convenience sake, as doing this manually can prove to be verbose.
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*};
     /// 
-    /// This key can also index into lists. So, for example `a/0/b` would try checking if `"a"`
-    /// is a list, and index into it. Otherwise it will try to find an internal map with the key `0`.
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add_from("file", "port", Int(80));
+    /// cmap.add_from("env", "port", Int(8080));
+    /// cmap.add_from("file", "debug", Bool(true));
+    /// cmap.add_from("env", "debug", Bool(true));
+    /// 
+    /// let conflicts = cmap.detect_conflicts();
+    /// assert_eq!(conflicts.len(), 1);
+    /// assert_eq!(conflicts[0].path, "port");
+    /// assert_eq!(conflicts[0].writes[1], ("env".to_string(), Int(8080)));
+    /// ```
+    pub fn detect_conflicts(&self) -> Vec<Conflict> {
+        self.state().map_or_else(Vec::new, |state| ownership::conflicts(&state.writes))
+    }
+
+    /// Pins the type of the value at `path`, so that `add` rejects any value of a different type
+    /// at that path, including when replacing a map or list that contains it.
+    /// 
+    /// Returns an error if the path already contains a value of a different type.
+    /// Pinning a path that doesn't exist yet is allowed. Values changed through `get_mut` aren't checked.
     /// 
     /// ## Examples
     /// ```
-    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// use cfgmap::{CfgMap, CfgType, CfgValue::*};
     /// 
     /// let mut cmap = CfgMap::new();
-    ///
-    /// cmap.add("sub", Map(CfgMap::new()));
-    /// cmap.add("sub/int", Int(5));
+    /// cmap.add("server", Map(CfgMap::new()));
+    /// cmap.add("server/port", Int(80));
+    /// cmap.pin_type("server/port", CfgType::Int).unwrap();
     /// 
-    /// let (key, num) = cmap.remove_entry("sub/int").unwrap();
-    /// let nothing = cmap.remove("sub/nothing");
+    /// assert!(cmap.add("server/port", Int(8080)).is_ok());
+    /// assert!(cmap.add("server/port", Str("8080".into())).is_err());
+    /// assert!(cmap.add("server", Map(CfgMap::new())).is_ok());
     /// 
-    /// assert!(cmap.get("sub/int").is_none());
-    /// assert_eq!(key, "int");
-    /// assert!(num.check_that(IsExactlyInt(5)));
-    /// assert!(nothing.is_none());
+    /// assert!(cmap.pin_type("server", CfgType::List).is_err());
     /// ```
-    pub fn remove_entry(&mut self, key: &str) -> Option<(String, CfgValue)> {
-        let (path, key) = rsplit_once(key, '/');
-
-        if path.is_none(){
-            self.internal_map.remove_entry(&key)
-        }
-        else {
-            let subtree = self.get_mut(&path.unwrap());
-
-            if subtree.check_that(Condition::IsMap) {
-                subtree.unwrap().as_map_mut().unwrap().remove_entry(&key)
-            }
-            else {
-                None
+    pub fn pin_type(&mut self, path: &str, expected: CfgType) -> Result<(), CfgError> {
+        if let Some(found) = self.get_untracked(path).map(CfgValue::cfg_type) {
+            if found != expected {
+                return Err(CfgError::TypeMismatch { path: path.to_string(), expected, found });
             }
         }
+
+        self.state_mut().pins.insert(path.to_string(), expected);
+        Ok(())
     }
 
-    /// Deletes a key from the map, and returns the key and value associated with it, if the value obeys the 
-    /// conditions as passed. Useful for when you want to make sure to avoid deleting another value.
-    /// 
-    /// Returns `None` if the key doesn't exist, or the value associated with the key doesn't obey the condition.
-    /// 
-    /// The `key` can be of the form of the path `"a/b/...y/z/"`, in which case it will
-    /// go through the inner submaps `"a/b/..."` until a submap isn't found, or the end is reached.
-    /// This is for convenience sake, as doing this manually can prove to be verbose.
-    /// 
-    /// This key can also index into lists. So, for example `a/0/b` would try checking if `"a"`
-    /// is a list, and index into it. Otherwise it will try to find an internal map with the key `0`.
+    /// Removes the type pinned to `path`, returning it if there was one.
     /// 
     /// ## Examples
     /// ```
-    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// use cfgmap::{CfgMap, CfgType, CfgValue::*};
     /// 
     /// let mut cmap = CfgMap::new();
-    ///
-    /// cmap.add("sub", Map(CfgMap::new()));
-    /// cmap.add("sub/int", Int(5));
+    /// cmap.pin_type("port", CfgType::Int).unwrap();
     /// 
-    /// let float = cmap.remove_entry_if("sub/int", IsFloat);
-    /// assert!(cmap.get("sub/int").is_some());
-    /// assert!(float.is_none());
-    /// 
-    /// let (key, int) = cmap.remove_entry_if("sub/int", IsInt).unwrap();
-    /// assert!(cmap.get("sub/int").is_none());
-    /// assert_eq!(key, "int");
-    /// assert!(int.check_that(IsExactlyInt(5)));
+    /// assert_eq!(cmap.unpin_type("port"), Some(CfgType::Int));
+    /// assert!(cmap.add("port", Str("80".into())).is_ok());
     /// ```
-    pub fn remove_entry_if(&mut self, key: &str, condition: Condition) -> Option<(String, CfgValue)> {
-        if self.get(key).check_that(condition) { self.remove_entry(key) } else { None }
+    pub fn unpin_type(&mut self, path: &str) -> Option<CfgType> {
+        self.state.as_mut()?.pins.remove(path)
     }
 
-
-    /// Checks whether a certain path exists.
+    /// Locks the value at `path` and everything within it, so that it can't be changed. A trailing `/` is ignored.
     /// 
-    /// The `key` can be of the form of the path `"a/b/...y/z/"`, in which case it will
-    /// go through the inner submaps `"a/b/..."` until a submap isn't found, or the end is reached.
-    /// This is for convenience sake, as doing this manually can prove to be verbose.
+    /// Once locked, `add` and `move_path` return an error for the path, `get_mut`, `get_pointer_mut` and `remove`
+    /// return `None`, `map_values_mut` and `retain_paths` skip it, and `apply_patch` fails if the patch would change it.
+    /// This also applies to any map or list containing the locked path, as changing it could change the locked value.
+    /// Siblings of the locked path can still be changed. Locks can't be removed, and are kept when the map is cloned.
     /// 
-    /// This key can also index into lists. So, for example `a/0/b` would try checking if `"a"`
-    /// is a list, and index into it. Otherwise it will try to find an internal map with the key `0`.
+    /// Note that changing the root map directly through the underlying `HashMap` isn't prevented.
     /// 
     /// ## Examples
     /// ```
     /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
     /// 
     /// let mut cmap = CfgMap::new();
-    /// let mut submap = CfgMap::new();
-    ///
-    /// cmap.add("num", Int(10));
-    /// submap.add("num", Int(20));
-    /// cmap.add("sub", Map(submap));
+    /// cmap.add("security", Map(CfgMap::new()));
+    /// cmap.add("security/tls", Bool(true));
+    /// cmap.add("name", Str("app".into()));
+    /// cmap.lock_path("security/");
     /// 
-    /// assert!(cmap.contains_key("num"));
-    /// assert!(cmap.contains_key("sub/num"));
+    /// assert!(cmap.add("security/tls", Bool(false)).is_err());
+    /// assert!(cmap.get_mut("security/tls").is_none());
+    /// assert!(cmap.remove("security").is_none());
+    /// assert!(cmap.add("name", Str("other".into())).is_ok());
+    /// 
+    /// assert!(cmap.get("security/tls").check_that(IsTrue));
+    /// assert!(cmap.is_locked("security/tls"));
     /// ```
-    pub fn contains_key(&self, key: &str) -> bool {
-        self.get(key).is_some()
+    pub fn lock_path(&mut self, path: &str) {
+        self.state_mut().locks.insert(path.trim_end_matches('/').to_string());
     }
 
-    /// Gets a reference to an option within the configuration.
+    /// Checks whether `path` was locked using `lock_path`, or is within a locked path.
     /// 
-    /// It first tries to get 
-    /// `category/option` within the normal values. If this doesn't exist, it will then 
-    /// try to retrieve `option` from the default path instead (`self.default/option`).
+    /// ## Examples
+    /// ```
+    /// use cfgmap::CfgMap;
     /// 
-    /// Note that if `default` wasn't set on construction, this function will instead retrieve
-    /// the value from the root directory (`option`) directly.
+    /// let mut cmap = CfgMap::new();
+    /// cmap.lock_path("security");
     /// 
-    /// Returns `None` if the key doesn't exist in either map.
+    /// assert!(cmap.is_locked("security"));
+    /// assert!(cmap.is_locked("security/tls"));
+    /// assert!(!cmap.is_locked("securityx"));
+    /// ```
+    pub fn is_locked(&self, path: &str) -> bool {
+        self.state().is_some_and(|state| state.locks.iter().any(|lock| lock == path || is_within(path, lock)))
+    }
+
+    /// Checks whether changing the value at `path` could change a locked value.
+    fn affects_lock(&self, path: &str) -> bool {
+        self.state().is_some_and(|state| affects_lock(&state.locks, path))
+    }
+
+    /// Gets a reference to a value from within the configuration.
     /// 
     /// The `key` can be of the form of the path `"a/b/...y/z/"`, in which case it will
     /// go through the inner submaps `"a/b/..."` until a submap isn't found, or the end is reached.
@@ -904,102 +1638,2252 @@ impl CfgMap {
     /// This key can also index into lists. So, for example `a/0/b` would try checking if `"a"`
     /// is a list, and index into it. Otherwise it will try to find an internal map with the key `0`.
     /// 
+    /// Returns `None` if the key doesn't exist.
+    /// 
     /// ## Examples
     /// ```
-    /// use cfgmap::{CfgMap, CfgValue::*, Checkable, Condition::*};
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
     /// 
     /// let mut cmap = CfgMap::new();
     /// let mut submap = CfgMap::new();
     /// 
-    /// submap.add("OP1", Int(5));
-    /// cmap.add("OP1", Int(8));
-    /// 
+    /// submap.add("key", Int(5));
+    ///
     /// cmap.add("sub", Map(submap));
     /// 
-    /// assert!(cmap.get_option("sub", "OP1").check_that(IsExactlyInt(5)));
-    /// assert!(cmap.get_option("sub", "OP1").check_that(IsExactlyInt(5)));
-    /// assert!(cmap.get_option("sub", "OP2").is_none());
+    /// assert!(cmap.get("sub").check_that(IsMap));
+    /// assert!(cmap.get("sub/key").check_that(IsExactlyInt(5)));
     /// ```
-    pub fn get_option(&self, category: &str, option: &str) -> Option<&CfgValue> {
-        let fullkey = format!("{}/{}", category, option);
-        let default = format!("{}{}", self.default, option);
-        self.get(&fullkey).or(self.get(&default))
+    pub fn get(&self, key: &str) -> Option<&CfgValue> {
+        let aliased = self.check_deprecated(key);
+        let key = aliased.as_deref().unwrap_or(key);
+        let value = self.get_untracked(key);
+
+        if let (Some(accessed), Some(_)) = (self.state().and_then(|state| state.accessed.as_ref()), value) {
+            accessed.record(key);
+        }
+
+        value
     }
 
-    /// Updates the option with the new value `to`.
-    /// 
-    /// It first tries to get 
-    /// `category/option` within the normal values. If this doesn't exist, it will then 
-    /// try to retrieve `option` from the default path instead (`self.default/option`).
-    /// 
-    /// Note that if `default` wasn't set on construction, this function will instead retrieve
-    /// the value from the root directory (`option`) directly.
-    /// 
-    /// The `key` can be of the form of the path `"a/b/...y/z/"`, in which case it will
-    /// go through the inner submaps `"a/b/..."` until a submap isn't found, or the end is reached.
-    /// This is for convenience sake, as doing this manually can prove to be verbose.
-    /// 
-    /// This key can also index into lists. So, for example `a/0/b` would try checking if `"a"`
-    /// is a list, and index into it. Otherwise it will try to find an internal map with the key `0`.
+    /// Gets a reference to a value using a path parsed ahead of time, the same way as `get`.
     /// 
     /// ## Examples
     /// ```
-    /// use cfgmap::{CfgMap, CfgValue::*, Checkable, Condition::*};
+    /// use cfgmap::{CfgMap, CfgPath, CfgValue::*, Condition::*, Checkable, path};
     /// 
     /// let mut cmap = CfgMap::new();
-    /// let mut submap = CfgMap::new();
-    /// 
-    /// submap.add("OP1", Int(5));
-    /// cmap.add("OP1", Int(8));
-    /// 
-    /// cmap.add("sub", Map(submap));
-    /// 
-    /// let ol1 = cmap.update_option("sub", "OP1", Int(10));
-    /// let ol2 = cmap.update_option("foo", "OP1", Int(16));
-    /// let ol3 = cmap.update_option("sub", "OP2", Int(99));
+    /// cmap.add("servers", List(vec![Map(CfgMap::new())]));
+    /// cmap.add("servers/0/port", Int(80));
     /// 
-    /// assert!(cmap.get_option("sub", "OP1").check_that(IsExactlyInt(10)));
-    /// assert!(cmap.get_option("foo", "OP1").check_that(IsExactlyInt(16)));
-    /// assert!(cmap.get_option("sub", "OP2").is_none());
+    /// let port = path!("servers" / 0 / "port");
+    /// assert_eq!(port, CfgPath::new("servers/0/port"));
     /// 
-    /// assert_eq!(ol1, Some(Int(5)));
-    /// assert_eq!(ol2, Some(Int(8)));
-    /// assert_eq!(ol3, None);
+    /// assert!(cmap.get_at(&port).check_that(IsExactlyInt(80)));
     /// ```
-    pub fn update_option(&mut self, category: &str, option: &str, to: CfgValue) -> Option<CfgValue> {
-        let fullkey = format!("{}/{}", category, option);
-        let default = format!("{}{}", self.default, option);
-
-        if let Some(x) = self.get_mut(&fullkey) {
-            Some(mem::replace(x, to))
-        } else if let Some(x) = self.get_mut(&default) {
-            Some(mem::replace(x, to))
+    pub fn get_at(&self, path: &CfgPath) -> Option<&CfgValue> {
+        // Deprecations and access tracking need the full path, but otherwise the parsed segments are used.
+        if self.needs_paths() {
+            self.get(path.as_str())
         } else {
-            None
+            self.follow(path.segments())
         }
     }
-}
 
-#[cfg(feature = "from_json")]
-impl From<JsonValue> for CfgMap {
-    fn from(opt: JsonValue) -> Self {
-        CfgMap::from_json(opt)
+    /// Gets a mutable reference to a value using a path parsed ahead of time, the same way as `get_mut`.
+    pub fn get_mut_at(&mut self, path: &CfgPath) -> Option<&mut CfgValue> {
+        self.get_mut(path.as_str())
     }
-}
 
-#[cfg(feature = "from_toml")]
-impl From<TomlValue> for CfgMap {
-    fn from(opt: TomlValue) -> Self {
-        CfgMap::from_toml(opt)
+    /// Adds a value using a path parsed ahead of time, the same way as `add`.
+    pub fn add_at(&mut self, path: &CfgPath, value: CfgValue) -> Result<Option<CfgValue>, CfgError> {
+        self.add(path.as_str(), value)
     }
-}
 
-pub mod prelude {
-    pub use crate::*;
-    pub use crate::{CfgValue::*, Condition::*};
-}
+    /// Checks whether reading a value needs its full path, to report deprecations or track access.
+    fn needs_paths(&self) -> bool {
+        self.state().is_some_and(|state| !state.deprecations.is_empty() || state.accessed.is_some())
+    }
 
-#[cfg(test)]
+    /// Reports `key` if it's deprecated and wasn't reported before. Returns the path
+    /// it's an alias for, if it has a replacement which contains a value.
+    fn check_deprecated(&self, key: &str) -> Option<String> {
+        let state = self.state()?;
+        let (deprecated, deprecation) = deprecation::find(&state.deprecations, key)?;
+
+        // Without a handler, nothing is reported, so the path can still be reported once one is set.
+        if let Some(deprecation::Handler(handler)) = &state.deprecation_handler {
+            if state.warned.record(deprecated) {
+                handler(deprecated, &deprecation.message);
+            }
+        }
+
+        self.alias_of(key, deprecated, deprecation)
+    }
+
+    /// Gets the path that `key`, within the `deprecated` path, is an alias for. This is the same path
+    /// within the replacement of `deprecation`, as long as it contains a value.
+    fn alias_of(&self, key: &str, deprecated: &str, deprecation: &deprecation::Deprecation) -> Option<String> {
+        deprecation.replacement.as_ref()
+            .map(|replacement| format!("{}{}", replacement, &key[deprecated.len()..]))
+            .filter(|aliased| self.get_untracked(aliased).is_some())
+    }
+
+    /// Explains how the value at `path` is read, such as where it came from, and what restricts it.
+    /// Unlike `get`, this doesn't report deprecations, or record the path as read when tracking access.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgType, CfgValue::*};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add_from("env", "port", Int(8080));
+    /// cmap.lock_path("port");
+    /// cmap.deprecate("http_port", "use `port` instead", Some("port"));
+    /// 
+    /// let explanation = cmap.explain("http_port");
+    /// assert_eq!(explanation.read_from.as_deref(), Some("port"));
+    /// assert_eq!(explanation.value_type, Some(CfgType::Int));
+    /// assert_eq!(explanation.source.as_deref(), Some("env"));
+    /// assert_eq!(explanation.deprecation.as_deref(), Some("use `port` instead"));
+    /// assert!(explanation.locked);
+    /// ```
+    pub fn explain(&self, path: &str) -> Explanation {
+        let deprecation = self.state().and_then(|state| deprecation::find(&state.deprecations, path));
+        let aliased = deprecation.and_then(|(deprecated, deprecation)| self.alias_of(path, deprecated, deprecation));
+        let read = aliased.as_deref().unwrap_or(path);
+
+        Explanation {
+            path: path.to_string(),
+            read_from: aliased.clone(),
+            value_type: self.get_untracked(read).map(CfgValue::cfg_type),
+            source: self.source_of(read).map(str::to_string),
+            deprecation: deprecation.map(|(_, deprecation)| deprecation.message.clone()),
+            locked: self.is_locked(read),
+            pinned: self.state().and_then(|state| state.pins.get(read).copied()),
+            secret: self.is_secret(read),
+        }
+    }
+
+    /// Explains how an option is read the same way as `explain`, but following `get_option`.
+    /// If the option is missing from `category`, the default option is explained instead,
+    /// and `read_from` contains its path.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.default = "default/".into();
+    /// cmap.add("default", Map(CfgMap::new()));
+    /// cmap.add_from("defaults.toml", "default/port", Int(80));
+    /// cmap.add("server", Map(CfgMap::new()));
+    /// 
+    /// let explanation = cmap.explain_option("server", "port");
+    /// assert_eq!(explanation.path, "server/port");
+    /// assert_eq!(explanation.read_from.as_deref(), Some("default/port"));
+    /// assert_eq!(explanation.source.as_deref(), Some("defaults.toml"));
+    /// ```
+    pub fn explain_option(&self, category: &str, option: &str) -> Explanation {
+        let fullkey = format!("{}/{}", category, option);
+        let explanation = self.explain(&fullkey);
+
+        if explanation.value_type.is_some() {
+            return explanation;
+        }
+
+        for default in self.default_paths(option) {
+            let fallback = self.explain(&default);
+            if fallback.value_type.is_some() {
+                return Explanation { path: fullkey, read_from: Some(fallback.read_from.clone().unwrap_or(default)), ..fallback };
+            }
+        }
+        explanation
+    }
+
+    /// Gets the path to the default subobject, without a trailing `/`. This is empty if the defaults are at the root.
+    pub fn default_path(&self) -> &str {
+        self.default.trim_end_matches('/')
+    }
+
+    /// Sets the path to the default subobject, removing any trailing `/`.
+    /// 
+    /// Returns `CfgError::InvalidPath` if there's no map at `path`, leaving the default path as it was.
+    /// An empty path sets the defaults to the root of the map.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgError, CfgMap, CfgValue::*, Checkable, Condition::*};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("defaults", Map(CfgMap::new()));
+    /// cmap.add("defaults/port", Int(80));
+    /// 
+    /// cmap.set_default_path("defaults/").unwrap();
+    /// assert_eq!(cmap.default_path(), "defaults");
+    /// assert!(cmap.get_option("server", "port").check_that(IsExactlyInt(80)));
+    /// 
+    /// assert_eq!(cmap.set_default_path("defaults/port"), Err(CfgError::InvalidPath("defaults/port".into())));
+    /// assert_eq!(cmap.default_path(), "defaults");
+    /// ```
+    pub fn set_default_path(&mut self, path: &str) -> Result<(), CfgError> {
+        let path = path.trim_end_matches('/');
+        if !path.is_empty() && self.get_untracked(path).and_then(CfgValue::as_map).is_none() {
+            return Err(CfgError::InvalidPath(path.to_string()));
+        }
+        self.default = path.to_string();
+        Ok(())
+    }
+
+    /// Returns the paths that `option` is read from when it's missing from its category, in order, which are
+    /// within `default` followed by each of the `fallbacks`.
+    pub(crate) fn default_paths(&self, option: &str) -> Vec<String> {
+        std::iter::once(join_path(self.default_path(), option))
+            .chain(self.fallbacks.iter().map(|fallback| join_path(fallback.trim_end_matches('/'), option)))
+            .collect()
+    }
+
+    /// Checks that the value at `path` satisfies `condition`, the same way as `get(path).check_that(condition)`,
+    /// but telling apart a missing value from a value that doesn't satisfy the condition.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, CheckError, Condition::*, list, value};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("ports", list![80, "443"]);
+    /// 
+    /// assert_eq!(cmap.check_path("ports", &IsList), Ok(()));
+    /// assert_eq!(cmap.check_path("hosts", &IsList), Err(CheckError::Missing { path: "hosts".into() }));
+    /// 
+    /// let error = cmap.check_path("ports", &IsListWith(Box::new(IsInt))).unwrap_err();
+    /// assert_eq!(error, CheckError::Failed {
+    ///     path: "ports".into(),
+    ///     condition: "IsListWith(IsInt)".into(),
+    ///     violation: "ports/1".into(),
+    /// });
+    /// assert_eq!(error.to_string(), "`ports` doesn't satisfy `IsListWith(IsInt)`, because of `ports/1`");
+    /// ```
+    pub fn check_path(&self, path: &str, condition: &Condition) -> Result<(), CheckError> {
+        let value = self.get(path).ok_or_else(|| CheckError::Missing { path: path.to_string() })?;
+
+        match condition.first_violation(value) {
+            None => Ok(()),
+            Some(violation) => Err(CheckError::Failed {
+                path: path.to_string(),
+                condition: condition.to_string(),
+                violation: if violation.is_empty() { path.to_string() } else { join_path(path, &violation) },
+            }),
+        }
+    }
+
+    /// Checks every rule using `check_path`, returning every failure at once, rather than only the first,
+    /// so that every problem with the configuration can be reported together. Every rule is an error, so use
+    /// a `Schema` for rules that should only warn.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("port", Str("80".into()));
+    /// cmap.add("host", Str("localhost".into()));
+    /// 
+    /// let report = cmap.validate_all(&[
+    ///     ("port", IsInt),
+    ///     ("host", IsStr),
+    ///     ("workers", IsInt),
+    /// ]);
+    /// 
+    /// assert!(!report.is_valid());
+    /// assert_eq!(report.to_string(), "`port` doesn't satisfy `IsInt`\n`workers` is missing");
+    /// ```
+    pub fn validate_all(&self, rules: &[(&str, Condition)]) -> ValidationReport {
+        ValidationReport {
+            failures: rules.iter().filter_map(|(path, condition)| self.check_path(path, condition).err()).collect(),
+            ..ValidationReport::default()
+        }
+    }
+
+    /// Gets a reference to a value, without recording it as read when tracking access.
+    pub(crate) fn get_untracked(&self, key: &str) -> Option<&CfgValue> {
+        self.follow(key.split('/'))
+    }
+
+    /// Gets a reference to the value at the path made up of `segments`, the same way as `get_untracked`.
+    /// This allows following paths made up of several strings, without having to join them.
+    fn follow<S: path::Segment, I: Iterator<Item = S>>(&self, segments: I) -> Option<&CfgValue> {
+        let mut segments = segments.peekable();
+        let mut map = self;
+
+        // Paths are followed one map at a time, so that deeply nested paths can't overflow the call stack.
+        loop {
+            let h = segments.next()?;
+
+            if segments.peek().is_none() {
+                return map.internal_map.get(h.key());
+            }
+
+            map = match map.internal_map.get(h.key()) {
+                Some(CfgValue::Map(next)) => next,
+                Some(CfgValue::List(list)) => {
+                    // Get the next segment of the path, and parse as a list index.
+                    // If it's an invalid usize, then the whole path is invalid.
+                    let index = segments.next()?.index()?;
+
+                    match segments.peek() {
+                        None => return list.get(index),
+                        Some(_) => list.get(index)?.as_map()?,
+                    }
+                },
+                _ => return None
+            };
+        }
+    }
+
+    /// Gets a mutable reference to a value from within the configuration.
+    /// 
+    /// Returns `None` if the key doesn't exist.
+    /// 
+    /// The `key` can be of the form of the path `"a/b/...y/z/"`, in which case it will
+    /// go through the inner submaps `"a/b/..."` until a submap isn't found, or the end is reached.
+    /// This is for convenience sake, as doing this manually can prove to be verbose.
+    /// 
+    /// This key can also index into lists. So, for example `a/0/b` would try checking if `"a"`
+    /// is a list, and index into it. Otherwise it will try to find an internal map with the key `0`.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// let mut submap = CfgMap::new();
+    ///
+    /// cmap.add("sub", Map(submap));
+    /// 
+    /// let mut submap = cmap.get_mut("sub");
+    /// assert!(submap.check_that(IsMap));
+    /// 
+    /// submap.unwrap().as_map_mut().unwrap().add("key", Int(5));
+    /// assert!(cmap.get_mut("sub/key").check_that(IsExactlyInt(5)));
+    /// ```
+    /// 
+    /// Returns `None` if the path is locked, or contains a locked path. See `lock_path` for more details.
+    /// Pinned types can't be checked through the returned reference, so use `add` to replace a value with
+    /// one of another type.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut CfgValue> {
+        if self.affects_lock(key) {
+            return None;
+        }
+
+        self.get_mut_unlocked(key)
+    }
+
+    /// Gets a mutable reference to a value, ignoring any locks.
+    fn get_mut_unlocked(&mut self, key: &str) -> Option<&mut CfgValue> {
+        self.get_mut_through(key, true)
+    }
+
+    /// Sets the value at `key`, or removes it if `value` is `None`, ignoring any locks or pinned types, including
+    /// those of nested maps. Used to put back values that were there before, so the map is left as it was.
+    pub(crate) fn restore(&mut self, key: &str, value: Option<CfgValue>) {
+        let (path, last) = rsplit_once(key, '/');
+
+        let map = match path {
+            None => self,
+            Some(path) => match self.get_mut_through(path, false) {
+                Some(CfgValue::Map(map)) => map,
+                _ => return,
+            },
+        };
+
+        match value {
+            Some(value) => { map.internal_map_mut().insert(last.to_string(), value); },
+            None => { map.internal_map_mut().remove(last); },
+        }
+    }
+
+    /// Gets a mutable reference to a value, ignoring the locks of this map, as well as those of nested maps
+    /// unless `nested_locks` is set.
+    fn get_mut_through(&mut self, key: &str, nested_locks: bool) -> Option<&mut CfgValue> {
+        let mut map = self;
+        let mut key = key;
+
+        // Paths are followed one map at a time, the same way as `follow`.
+        loop {
+            let (h, t) = split_once(key, '/');
+
+            let t = match t {
+                None => return map.internal_map_mut().get_mut(key),
+                Some(t) => t,
+            };
+
+            let (next, rest) = match map.internal_map_mut().get_mut(h) {
+                Some(CfgValue::Map(next)) => (next, t),
+                Some(CfgValue::List(list)) => {
+                    // Get the next segment of the path, and parse as a list index.
+                    let (index, new_t) = split_once(t, '/');
+
+                    // If it's an invalid usize, then the whole path is invalid.
+                    let index = index.parse::<usize>().ok()?;
+
+                    match new_t {
+                        None => return list.get_mut(index),
+                        Some(new_t) => (list.get_mut(index)?.as_map_mut()?, new_t),
+                    }
+                },
+                _ => return None
+            };
+
+            // Nested maps can have locks of their own.
+            if nested_locks && next.affects_lock(rest) {
+                return None;
+            }
+
+            map = next;
+            key = rest;
+        }
+    }
+
+    /// Gets a reference to a value using a JSON Pointer, as described in RFC 6901.
+    /// 
+    /// Unlike the path syntax used by `get`, the pointer has to start with a `/`, and keys containing
+    /// `/` or `~` can be referenced by escaping them as `~1` and `~0` respectively. List indexes are
+    /// strict, meaning that `01` or `-` won't index into a list.
+    /// 
+    /// Returns `None` if the pointer is malformed, or doesn't point to a value. Since the root of
+    /// a `CfgMap` isn't a `CfgValue`, the empty pointer `""` will also return `None`.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut user = CfgMap::new();
+    /// user.raw_map_mut().insert("a/b".into(), Int(5));
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("paths", Map(CfgMap::new()));
+    /// cmap.add("paths/~user", Map(user));
+    /// cmap.add("list", List(vec![Int(1), Int(2)]));
+    /// 
+    /// assert!(cmap.get_pointer("/paths/~0user/a~1b").check_that(IsExactlyInt(5)));
+    /// assert!(cmap.get_pointer("/list/1").check_that(IsExactlyInt(2)));
+    /// assert!(cmap.get_pointer("/list/01").is_none());
+    /// assert!(cmap.get_pointer("list/1").is_none());
+    /// ```
+    pub fn get_pointer(&self, pointer: &str) -> Option<&CfgValue> {
+        pointer::parse(pointer).and_then(|tokens| pointer::get(self, &tokens))
+    }
+
+    /// Gets a mutable reference to a value using a JSON Pointer, as described in RFC 6901.
+    /// 
+    /// See `get_pointer` for more details.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("list", List(vec![Int(1), Int(2)]));
+    /// 
+    /// *cmap.get_pointer_mut("/list/0").unwrap() = Int(10);
+    /// assert!(cmap.get("list/0").check_that(IsExactlyInt(10)));
+    /// ```
+    pub fn get_pointer_mut(&mut self, pointer: &str) -> Option<&mut CfgValue> {
+        let tokens = pointer::parse(pointer)?;
+
+        if self.affects_lock(&tokens.join("/")) {
+            return None;
+        }
+
+        pointer::get_mut(self, &tokens)
+    }
+
+    /// Deletes a key from the map, and returns the value associated with it.
+    /// 
+    /// Returns `None` if the key doesn't exist.
+    /// 
+    /// The `key` can be of the form of the path `"a/b/...y/z/"`, in which case it will
+    /// go through the inner submaps `"a/b/..."` until a submap isn't found, or the end is reached.
+    /// This is for convenience sake, as doing this manually can prove to be verbose.
+    /// 
+    /// This key can also index into lists. So, for example `a/0/b` would try checking if `"a"`
+    /// is a list, and index into it. Otherwise it will try to find an internal map with the key `0`.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    ///
+    /// cmap.add("sub", Map(CfgMap::new()));
+    /// cmap.add("sub/int", Int(5));
+    /// 
+    /// let num = cmap.remove("sub/int");
+    /// let nothing = cmap.remove("sub/nothing");
+    /// 
+    /// assert!(cmap.get("sub/int").is_none());
+    /// assert!(num.check_that(IsExactlyInt(5)));
+    /// assert!(nothing.is_none());
+    /// ```
+    pub fn remove(&mut self, key: &str) -> Option<CfgValue> {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    /// Deletes a key from the map, and returns the value associated with it, if the value obeys the 
+    /// conditions as passed. Useful for when you want to make sure to avoid deleting another value.
+    /// 
+    /// Returns `None` if the key doesn't exist, or the value associated with the key doesn't obey the condition.
+    /// 
+    /// The `key` can be of the form of the path `"a/b/...y/z/"`, in which case it will
+    /// go through the inner submaps `"a/b/..."` until a submap isn't found, or the end is reached.
+    /// This is for convenience sake, as doing this manually can prove to be verbose.
+    /// 
+    /// This key can also index into lists. So, for example `a/0/b` would try checking if `"a"`
+    /// is a list, and index into it. Otherwise it will try to find an internal map with the key `0`.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    ///
+    /// cmap.add("sub", Map(CfgMap::new()));
+    /// cmap.add("sub/int", Int(5));
+    /// 
+    /// let float = cmap.remove_if("sub/int", IsFloat);
+    /// assert!(cmap.get("sub/int").is_some());
+    /// assert!(float.is_none());
+    /// 
+    /// let int = cmap.remove_if("sub/int", IsInt);
+    /// assert!(cmap.get("sub/int").is_none());
+    /// assert!(int.check_that(IsExactlyInt(5)));
+    /// ```
+    pub fn remove_if(&mut self, key: &str, condition: Condition) -> Option<CfgValue> {
+        if self.get_untracked(key).check_that(condition) { self.remove(key) } else { None }
+    }
+
+    /// Deletes a key from the map, and returns the key and value associated with it.
+    /// 
+    /// Returns `None` if the key doesn't exist.
+    /// 
+    /// The `key` can be of the form of the path `"a/b/...y/z/"`, in which case it will
+    /// go through the inner submaps `"a/b/..."` until a submap isn't found, or the end is reached.
+    /// This is for convenience sake, as doing this manually can prove to be verbose.
+    /// 
+    /// This key can also index into lists. So, for example `a/0/b` would try checking if `"a"`
+    /// is a list, and index into it. Otherwise it will try to find an internal map with the key `0`.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    ///
+    /// cmap.add("sub", Map(CfgMap::new()));
+    /// cmap.add("sub/int", Int(5));
+    /// 
+    /// let (key, num) = cmap.remove_entry("sub/int").unwrap();
+    /// let nothing = cmap.remove("sub/nothing");
+    /// 
+    /// assert!(cmap.get("sub/int").is_none());
+    /// assert_eq!(key, "int");
+    /// assert!(num.check_that(IsExactlyInt(5)));
+    /// assert!(nothing.is_none());
+    /// ```
+    pub fn remove_entry(&mut self, key: &str) -> Option<(String, CfgValue)> {
+        if self.affects_lock(key) {
+            return None;
+        }
+
+        let (path, key) = rsplit_once(key, '/');
+
+        match path {
+            None => self.internal_map_mut().remove_entry(key),
+            Some(path) => self.get_mut_unlocked(path)
+                .and_then(|subtree| subtree.as_map_mut())
+                .and_then(|map| map.remove_entry(key))
+        }
+    }
+
+    /// Deletes a key from the map, and returns the key and value associated with it, if the value obeys the 
+    /// conditions as passed. Useful for when you want to make sure to avoid deleting another value.
+    /// 
+    /// Returns `None` if the key doesn't exist, or the value associated with the key doesn't obey the condition.
+    /// 
+    /// The `key` can be of the form of the path `"a/b/...y/z/"`, in which case it will
+    /// go through the inner submaps `"a/b/..."` until a submap isn't found, or the end is reached.
+    /// This is for convenience sake, as doing this manually can prove to be verbose.
+    /// 
+    /// This key can also index into lists. So, for example `a/0/b` would try checking if `"a"`
+    /// is a list, and index into it. Otherwise it will try to find an internal map with the key `0`.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    ///
+    /// cmap.add("sub", Map(CfgMap::new()));
+    /// cmap.add("sub/int", Int(5));
+    /// 
+    /// let float = cmap.remove_entry_if("sub/int", IsFloat);
+    /// assert!(cmap.get("sub/int").is_some());
+    /// assert!(float.is_none());
+    /// 
+    /// let (key, int) = cmap.remove_entry_if("sub/int", IsInt).unwrap();
+    /// assert!(cmap.get("sub/int").is_none());
+    /// assert_eq!(key, "int");
+    /// assert!(int.check_that(IsExactlyInt(5)));
+    /// ```
+    pub fn remove_entry_if(&mut self, key: &str, condition: Condition) -> Option<(String, CfgValue)> {
+        if self.get_untracked(key).check_that(condition) { self.remove_entry(key) } else { None }
+    }
+
+    /// Moves the value at `from` to `to`, creating any missing maps along the way to `to`.
+    /// If `to` already contains a value, it's overwritten.
+    /// 
+    /// Returns an error if there's no value at `from` that can be removed, if `to` is within `from`,
+    /// if `to` goes through a value that isn't a map or list, if the value doesn't match a type pinned at `to`,
+    /// or if either path is locked.
+    /// Nothing is removed if this fails.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("port", Int(80));
+    /// 
+    /// cmap.move_path("port", "server/http/port").unwrap();
+    /// 
+    /// assert!(cmap.get("port").is_none());
+    /// assert!(cmap.get("server/http/port").check_that(IsExactlyInt(80)));
+    /// assert!(cmap.move_path("missing", "other").is_err());
+    /// ```
+    pub fn move_path(&mut self, from: &str, to: &str) -> Result<(), CfgError> {
+        let invalid = |path: &str| CfgError::InvalidPath(path.to_string());
+
+        // Only values within maps can be removed.
+        let removable = match rsplit_once(from, '/').0 {
+            None => true,
+            Some(parent) => self.get_untracked(parent).is_some_and(CfgValue::is_map),
+        };
+
+        let value = match self.get_untracked(from) {
+            Some(value) if removable => value.clone(),
+            _ => return Err(invalid(from)),
+        };
+
+        if self.affects_lock(from) {
+            return Err(CfgError::Locked(from.to_string()));
+        } else if from == to {
+            return Ok(());
+        } else if is_within(to, from) {
+            return Err(invalid(to));
+        }
+
+        self.check_pins(to, &value)?;
+        self.add_parents(to)?;
+        self.add(to, value)?;
+
+        // If `to` contains `from`, overwriting it already removed the original.
+        if !is_within(from, to) {
+            self.remove(from);
+        }
+
+        Ok(())
+    }
+
+    /// Copies the value at `from` to `to`, creating any missing maps along the way to `to`.
+    /// If `to` already contains a value, it's overwritten.
+    /// 
+    /// Returns an error if there's no value at `from`, if `to` goes through a value that isn't a map or list,
+    /// if the value doesn't match a type pinned at `to`, or if `to` is locked.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("defaults", Map(CfgMap::new()));
+    /// cmap.add("defaults/port", Int(80));
+    /// 
+    /// cmap.copy_path("defaults", "servers/main").unwrap();
+    /// cmap.add("servers/main/port", Int(8080));
+    /// 
+    /// assert!(cmap.get("defaults/port").check_that(IsExactlyInt(80)));
+    /// assert!(cmap.get("servers/main/port").check_that(IsExactlyInt(8080)));
+    /// ```
+    pub fn copy_path(&mut self, from: &str, to: &str) -> Result<(), CfgError> {
+        let value = self.get_untracked(from).cloned().ok_or_else(|| CfgError::InvalidPath(from.to_string()))?;
+
+        self.check_pins(to, &value)?;
+        self.add_parents(to)?;
+        self.add(to, value).map(|_| ())
+    }
+
+    /// Adds an empty map at every path leading up to `path` that doesn't exist yet.
+    fn add_parents(&mut self, path: &str) -> Result<(), CfgError> {
+        let segments: Vec<&str> = path.split('/').collect();
+
+        for i in 1..segments.len() {
+            let parent = segments[..i].join("/");
+
+            match self.get_untracked(&parent) {
+                None => { self.add(&parent, CfgValue::Map(CfgMap::new()))?; },
+                Some(CfgValue::Map(_)) | Some(CfgValue::List(_)) => {},
+                Some(_) => return Err(CfgError::InvalidPath(path.to_string())),
+            }
+        }
+
+        Ok(())
+    }
+
+
+    /// Checks whether a certain path exists.
+    /// 
+    /// The `key` can be of the form of the path `"a/b/...y/z/"`, in which case it will
+    /// go through the inner submaps `"a/b/..."` until a submap isn't found, or the end is reached.
+    /// This is for convenience sake, as doing this manually can prove to be verbose.
+    /// 
+    /// This key can also index into lists. So, for example `a/0/b` would try checking if `"a"`
+    /// is a list, and index into it. Otherwise it will try to find an internal map with the key `0`.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// let mut submap = CfgMap::new();
+    ///
+    /// cmap.add("num", Int(10));
+    /// submap.add("num", Int(20));
+    /// cmap.add("sub", Map(submap));
+    /// 
+    /// assert!(cmap.contains_key("num"));
+    /// assert!(cmap.contains_key("sub/num"));
+    /// ```
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Iterates over every leaf value within the map, along with its full path.
+    /// 
+    /// Nested maps and lists are traversed, but not yielded themselves. List elements use their index
+    /// in the path, so the first element of `a` would be yielded as `a/0`. Use `walk_all` to also
+    /// yield nested maps and lists.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("server", Map(CfgMap::new()));
+    /// cmap.add("server/port", Int(80));
+    /// cmap.add("server/hosts", List(vec![Str("a".into()), Str("b".into())]));
+    /// 
+    /// let paths: Vec<String> = cmap.walk().map(|(path, _)| path).collect();
+    /// assert_eq!(paths, vec!["server/hosts/0", "server/hosts/1", "server/port"]);
+    /// ```
+    pub fn walk(&self) -> Walk<'_> {
+        Walk::new(self, false)
+    }
+
+    /// Iterates over every value within the map, along with its full path, including nested maps and lists.
+    /// 
+    /// Nested maps and lists are yielded before their contents.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("server", Map(CfgMap::new()));
+    /// cmap.add("server/port", Int(80));
+    /// 
+    /// let paths: Vec<String> = cmap.walk_all().map(|(path, _)| path).collect();
+    /// assert_eq!(paths, vec!["server", "server/port"]);
+    /// ```
+    pub fn walk_all(&self) -> Walk<'_> {
+        Walk::new(self, true)
+    }
+
+    /// Gets the full paths of every value within the map that satisfies `condition`, including
+    /// nested maps and lists. Paths are returned in the same order as `walk_all`.
+    ///
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*};
+    ///
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("db", Map(CfgMap::new()));
+    /// cmap.add("db/password", Str("hunter2".into()));
+    /// cmap.add("db/port", Int(5432));
+    /// cmap.add("name", Str("app".into()));
+    ///
+    /// assert_eq!(cmap.find_paths(&IsStr), vec!["db/password", "name"]);
+    /// assert_eq!(cmap.find_paths(&(IsMap | IsInt)), vec!["db", "db/port"]);
+    /// ```
+    pub fn find_paths(&self, condition: &Condition) -> Vec<String> {
+        self.walk_all()
+            .filter(|(_, value)| condition.execute(value).to_bool())
+            .map(|(path, _)| path)
+            .collect()
+    }
+
+    /// Calls `f` on every value within the map, along with its full path, allowing it to be modified in place.
+    /// 
+    /// Nested maps and lists are visited too, before their contents. This means that if `f` replaces
+    /// a map or a list, the replacement's contents are the ones that get visited.
+    /// 
+    /// Locked paths, and any maps or lists containing them, aren't passed to `f`. If `f` changes the type of
+    /// a path pinned using `pin_type`, or of one within the value it's given, the value is changed back.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("name", Str("  cfgmap ".into()));
+    /// cmap.add("tags", List(vec![Str(" a".into()), Int(5)]));
+    /// 
+    /// cmap.map_values_mut(|_, value| {
+    ///     if let Str(s) = value {
+    ///         *s = s.trim().to_string();
+    ///     }
+    /// });
+    /// 
+    /// assert!(cmap.get("name").check_that(IsExactlyStr("cfgmap".into())));
+    /// assert!(cmap.get("tags/0").check_that(IsExactlyStr("a".into())));
+    /// ```
+    pub fn map_values_mut<F: FnMut(&str, &mut CfgValue)>(&mut self, mut f: F) {
+        let locks = self.state().map(|state| state.locks.clone()).unwrap_or_default();
+        let pins = self.state().map(|state| state.pins.clone()).unwrap_or_default();
+
+        walk::visit_mut(self, |path, value| if !affects_lock(&locks, path) {
+            // Only values that are, or contain, a pinned path need to be restored.
+            let original = pins.keys().any(|pinned| pinned == path || is_within(pinned, path)).then(|| value.clone());
+            f(path, value);
+
+            if let Some(original) = original {
+                if pin::check(&pins, path, value).is_err() {
+                    *value = original;
+                }
+            }
+        })
+    }
+
+    /// Removes every value within the map for which `keep` returns `false`, no matter how deeply nested.
+    /// 
+    /// `keep` is called with the full path of every value, as well as the value itself. Nested maps and lists
+    /// are checked before their contents, so removing a map also removes everything inside it. Any map or list
+    /// that ends up empty because all of its contents were removed is removed as well. List elements are checked
+    /// using their original index.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("server", Map(CfgMap::new()));
+    /// cmap.add("server/port", Int(80));
+    /// cmap.add("server/_debug", Bool(true));
+    /// cmap.add("internal", Map(CfgMap::new()));
+    /// cmap.add("internal/_trace", Bool(true));
+    /// 
+    /// cmap.retain_paths(|path, _| !path.rsplit('/').next().unwrap().starts_with('_'));
+    /// 
+    /// assert!(cmap.contains_key("server/port"));
+    /// assert!(!cmap.contains_key("server/_debug"));
+    /// assert!(!cmap.contains_key("internal"));
+    /// ```
+    pub fn retain_paths<F: FnMut(&str, &CfgValue) -> bool>(&mut self, mut keep: F) {
+        let locks = self.state().map(|state| state.locks.clone()).unwrap_or_default();
+        walk::retain_map("", self, &mut |path: &str, value: &CfgValue| affects_lock(&locks, path) || keep(path, value))
+    }
+
+    /// Removes every value that isn't known, returning the removed values along with their paths, in the same
+    /// order as `walk_all`. This allows cleaning up old settings that are no longer read.
+    /// 
+    /// A value is known if its path matches one of the `known` patterns, which use the same syntax as `get_all`.
+    /// Values within a known value, and maps or lists containing one, are known too. Locked values aren't removed.
+    /// 
+    /// If there's a `quarantine` path, removed values are moved within it instead, keeping their path, so that
+    /// `old/key` is moved to `quarantine/old/key`. Any values that can't be added to it, such as if it's locked,
+    /// are only returned.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("server", Map(CfgMap::new()));
+    /// cmap.add("server/port", Int(80));
+    /// cmap.add("server/legacy_mode", Bool(true));
+    /// cmap.add("theme", Str("dark".into()));
+    /// 
+    /// let removed = cmap.gc_unknown(&["server/port", "theme"], Some("_unknown"));
+    /// 
+    /// assert_eq!(removed, vec![("server/legacy_mode".to_string(), Bool(true))]);
+    /// assert!(cmap.get("server/legacy_mode").is_none());
+    /// assert!(cmap.get("_unknown/server/legacy_mode").check_that(IsTrue));
+    /// ```
+    pub fn gc_unknown(&mut self, known: &[&str], quarantine: Option<&str>) -> Vec<(String, CfgValue)> {
+        let known: Vec<String> = known.iter()
+            .flat_map(|pattern| self.get_all(pattern))
+            .map(|(path, _)| path)
+            .chain(quarantine.map(|quarantine| quarantine.trim_end_matches('/').to_string()))
+            .collect();
+
+        let mut removed = Vec::new();
+        self.retain_paths(|path, value| {
+            let keep = known.iter().any(|known| known == path || is_within(path, known) || is_within(known, path));
+            if !keep {
+                removed.push((path.to_string(), value.clone()));
+            }
+            keep
+        });
+
+        if let Some(quarantine) = quarantine {
+            for (path, value) in &removed {
+                let _ = self.add_force(&join_path(quarantine.trim_end_matches('/'), path), value.clone());
+            }
+        }
+
+        removed
+    }
+
+    /// Gets every value whose path matches `pattern`, along with its full path.
+    /// 
+    /// The pattern uses the same `/` separated syntax as `get`, with two wildcards:
+    /// - `*` matches any single key (or list index).
+    /// - `**` matches any number of levels, including none.
+    /// 
+    /// So `servers/*/port` would match the port of every server, while `**/password`
+    /// would match every `password` key in the configuration, no matter how deeply nested.
+    /// 
+    /// The results are sorted by path.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("servers", Map(CfgMap::new()));
+    /// cmap.add("servers/a", Map(CfgMap::new()));
+    /// cmap.add("servers/a/port", Int(80));
+    /// cmap.add("servers/b", Map(CfgMap::new()));
+    /// cmap.add("servers/b/port", Int(443));
+    /// cmap.add("servers/b/password", Str("hunter2".into()));
+    /// cmap.add("password", Str("letmein".into()));
+    /// 
+    /// let ports = cmap.get_all("servers/*/port");
+    /// assert_eq!(ports.len(), 2);
+    /// assert!(ports.iter().all(|(_, port)| port.check_that(IsInt)));
+    /// 
+    /// let passwords: Vec<String> = cmap.get_all("**/password").into_iter().map(|(path, _)| path).collect();
+    /// assert_eq!(passwords, vec!["password", "servers/b/password"]);
+    /// ```
+    pub fn get_all(&self, pattern: &str) -> Vec<(String, &CfgValue)> {
+        let segments: Vec<&str> = pattern.split('/').collect();
+        let children = self.internal_map.iter().map(|(k, v)| (k.clone(), v)).collect();
+
+        let mut matches = Vec::new();
+        collect_matches(children, "", &segments, &mut matches);
+
+        matches.sort_by(|a, b| a.0.cmp(&b.0));
+        matches.dedup_by(|a, b| a.0 == b.0);
+        matches
+    }
+
+    /// Temporarily applies `changes` onto the map, returning a guard that restores the original values once dropped.
+    /// 
+    /// Every change is applied using `add`, so the same path syntax is supported, including appending to lists
+    /// with `-`. The guard can be used just like the map itself. This makes it easy to test code under alternative
+    /// configurations without having to clone the whole map.
+    /// 
+    /// Returns `None` if any of the changes couldn't be applied, in which case the map is left untouched.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("port", Int(80));
+    /// 
+    /// {
+    ///     let overlay = cmap.overlay(vec![("port", Int(8080)), ("debug", Bool(true))]).unwrap();
+    ///     assert!(overlay.get("port").check_that(IsExactlyInt(8080)));
+    ///     assert!(overlay.get("debug").check_that(IsTrue));
+    /// }
+    /// 
+    /// assert!(cmap.get("port").check_that(IsExactlyInt(80)));
+    /// assert!(cmap.get("debug").is_none());
+    /// ```
+    pub fn overlay<'a, I>(&mut self, changes: I) -> Option<Overlay<'_>>
+        where I: IntoIterator<Item = (&'a str, CfgValue)>
+    {
+        Overlay::new(self, changes)
+    }
+
+    /// Temporarily applies `changes` onto the map while running `scope`, restoring the original values afterwards.
+    /// 
+    /// See `overlay` for more details.
+    /// 
+    /// Returns `None` without running `scope` if any of the changes couldn't be applied.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("retries", Int(3));
+    /// 
+    /// let retries = cmap.overlay_scoped(vec![("retries", Int(0))], |cmap| {
+    ///     cmap.get("retries").and_then(|r| r.to_int())
+    /// });
+    /// 
+    /// assert_eq!(retries, Some(Some(0)));
+    /// assert_eq!(cmap.get("retries"), Some(&Int(3)));
+    /// ```
+    pub fn overlay_scoped<'a, I, R, F>(&mut self, changes: I, scope: F) -> Option<R>
+        where I: IntoIterator<Item = (&'a str, CfgValue)>, F: FnOnce(&mut CfgMap) -> R
+    {
+        self.overlay(changes).map(|mut overlay| scope(&mut overlay))
+    }
+
+    /// Gets a copy of the map at `prefix`, so that its contents are at the root. A trailing `/` is ignored.
+    /// This is useful for handing part of the configuration to a child component that expects its own settings
+    /// at the root, and can be combined with `flatten` to export it as environment variables.
+    /// 
+    /// Returns `None` if there's no map at `prefix`.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("plugins", Map(CfgMap::new()));
+    /// cmap.add("plugins/lua", Map(CfgMap::new()));
+    /// cmap.add("plugins/lua/path", Str("scripts/".into()));
+    /// 
+    /// let lua = cmap.subenv("plugins/lua/").unwrap();
+    /// assert!(lua.get("path").check_that(IsExactlyStr("scripts/".into())));
+    /// assert!(cmap.subenv("plugins/lua/path").is_none());
+    /// ```
+    pub fn subenv(&self, prefix: &str) -> Option<CfgMap> {
+        self.get(prefix.trim_end_matches('/'))
+            .and_then(CfgValue::as_map)
+            .map(CfgMap::contents)
+    }
+
+    /// Decrypts every encrypted string within the map, as described by `CfgValue::is_encrypted`, using `decrypt`.
+    /// 
+    /// `decrypt` is called with the contents of each encrypted string after `ENCRYPTED_PREFIX`, so for `enc:AES256:...`,
+    /// it gets `AES256:...`, and returns the plain string. Locked values are left as they are.
+    /// 
+    /// Returns an error if `decrypt` fails for any of the strings, in which case the map is left untouched.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("password", Str("enc:rev:2retnuh".into()));
+    /// cmap.add("user", Str("admin".into()));
+    /// 
+    /// // A real decryptor would use the algorithm named before the ciphertext.
+    /// cmap.decrypt_with(|ciphertext| match ciphertext.strip_prefix("rev:") {
+    ///     Some(text) => Ok(text.chars().rev().collect()),
+    ///     None => Err("unknown algorithm"),
+    /// }).unwrap();
+    /// 
+    /// assert!(cmap.get("password").check_that(IsExactlyStr("hunter2".into())));
+    /// assert!(cmap.get("user").check_that(IsExactlyStr("admin".into())));
+    /// 
+    /// cmap.add("token", Str("enc:AES256:b64data".into()));
+    /// assert!(cmap.decrypt_with(|_| Err("no key")).is_err());
+    /// ```
+    pub fn decrypt_with<E, F>(&mut self, mut decrypt: F) -> Result<(), CfgError>
+        where E: fmt::Display, F: FnMut(&str) -> Result<String, E>
+    {
+        let mut decrypted = Vec::new();
+
+        for (path, value) in self.walk() {
+            if let Some(ciphertext) = value.as_str().and_then(|s| s.strip_prefix(ENCRYPTED_PREFIX)) {
+                let plaintext = decrypt(ciphertext)
+                    .map_err(|e| CfgError::DecryptFailed { path: path.clone(), reason: e.to_string() })?;
+                decrypted.push((path, plaintext));
+            }
+        }
+
+        for (path, plaintext) in decrypted {
+            if let Some(value) = self.get_mut(&path) {
+                *value = CfgValue::Str(plaintext);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resolves every reference within the map, replacing it with a copy of the value it refers to.
+    /// 
+    /// A reference is a map with a `$ref` key, whose value is the path it refers to. Any other keys alongside
+    /// `$ref` override the keys of the referenced map. References within the referenced value are resolved too.
+    /// Use `get_resolved` to read through references lazily instead, leaving them in the map.
+    /// 
+    /// Returns an error if a reference can't be resolved, if references form a cycle, or if resolving them would
+    /// change a locked path or the type of a pinned path, in which case the map is left untouched.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut db = CfgMap::new();
+    /// db.add("host", Str("db.local".into()));
+    /// db.add("port", Int(5432));
+    /// 
+    /// let mut reference = CfgMap::new();
+    /// reference.add("$ref", Str("shared/db".into()));
+    /// reference.add("port", Int(6543));
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("shared", Map(CfgMap::new()));
+    /// cmap.add("shared/db", Map(db));
+    /// cmap.add("api", Map(CfgMap::new()));
+    /// cmap.add("api/db", Map(reference));
+    /// 
+    /// cmap.resolve_refs().unwrap();
+    /// 
+    /// assert!(cmap.get("api/db/host").check_that(IsExactlyStr("db.local".into())));
+    /// assert!(cmap.get("api/db/port").check_that(IsExactlyInt(6543)));
+    /// ```
+    pub fn resolve_refs(&mut self) -> Result<(), CfgError> {
+        let resolved = CfgMap { internal_map: Arc::new(refs::resolve(self)?), ..CfgMap::default() };
+        self.check_replacement(&resolved)?;
+        self.internal_map = resolved.internal_map;
+        Ok(())
+    }
+
+    /// Gets a copy of the value at `path` as it would be after `resolve_refs`, without resolving every reference
+    /// within the map. References along `path` are followed as aliases of the paths they refer to, and references
+    /// within the value are resolved in its copy, so the map itself is left as it is.
+    /// 
+    /// Returns `Ok(None)` if there's no value at `path`, or an error if one of the references followed can't be
+    /// resolved or is part of a cycle. References elsewhere in the map aren't checked.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*};
+    /// 
+    /// let mut reference = CfgMap::new();
+    /// reference.add("$ref", Str("shared/db".into()));
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("shared", Map(CfgMap::new()));
+    /// cmap.add("shared/db", Map(CfgMap::new()));
+    /// cmap.add("shared/db/port", Int(5432));
+    /// cmap.add("api", Map(CfgMap::new()));
+    /// cmap.add("api/db", Map(reference));
+    /// 
+    /// assert_eq!(cmap.get_resolved("api/db/port"), Ok(Some(Int(5432))));
+    /// assert_eq!(cmap.get("api/db/port"), None);
+    /// ```
+    pub fn get_resolved(&self, path: &str) -> Result<Option<CfgValue>, CfgError> {
+        refs::get(self, path)
+    }
+
+    /// Interpolates every string within the map, replacing each `${path}` within it with the value at `path`.
+    /// 
+    /// The value has to be a string, integer, float or boolean. Strings are interpolated before being used,
+    /// so interpolations can be nested. Use `$$` for a literal `$`, so `$${path}` becomes `${path}`.
+    /// Locked values are left as they are.
+    /// 
+    /// Returns an error if a path can't be interpolated, or if strings refer to each other in a cycle,
+    /// in which case the map is left untouched.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("base_url", Str("https://example.com".into()));
+    /// cmap.add("endpoints", Map(CfgMap::new()));
+    /// cmap.add("endpoints/users", Str("${base_url}/users".into()));
+    /// cmap.add("endpoints/price", Str("$$5 at ${endpoints/users}".into()));
+    /// 
+    /// cmap.interpolate().unwrap();
+    /// 
+    /// assert!(cmap.get("endpoints/users").check_that(IsExactlyStr("https://example.com/users".into())));
+    /// assert!(cmap.get("endpoints/price").check_that(IsExactlyStr("$5 at https://example.com/users".into())));
+    /// ```
+    pub fn interpolate(&mut self) -> Result<(), CfgError> {
+        for (path, interpolated) in interpolate::interpolate(self)? {
+            if let Some(value) = self.get_mut(&path) {
+                *value = CfgValue::Str(interpolated);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Expands environment variables within every string in the map, replacing each `${NAME}` with the value
+    /// of the variable `NAME`, and each `${NAME:-default}` with the value of `NAME`, or `default` if it isn't set.
+    /// `$$` is expanded to `$` the same way as `interpolate`. Locked values are left as they are.
+    /// 
+    /// Returns an error if a variable without a default isn't set, in which case the map is left untouched.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// std::env::set_var("CFGMAP_EXAMPLE_HOST", "db.local");
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("url", Str("${CFGMAP_EXAMPLE_HOST}:${CFGMAP_EXAMPLE_PORT:-5432}".into()));
+    /// 
+    /// cmap.expand_env().unwrap();
+    /// 
+    /// assert!(cmap.get("url").check_that(IsExactlyStr("db.local:5432".into())));
+    /// ```
+    pub fn expand_env(&mut self) -> Result<(), CfgError> {
+        self.expand_env_with(|name| std::env::var(name).ok())
+    }
+
+    /// Expands environment variables the same way as `expand_env`, but gets them from `var` instead of the
+    /// process environment. `var` returns `None` for variables that aren't set.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("level", Str("${LOG_LEVEL:-info}".into()));
+    /// 
+    /// cmap.expand_env_with(|_| None).unwrap();
+    /// 
+    /// assert!(cmap.get("level").check_that(IsExactlyStr("info".into())));
+    /// ```
+    pub fn expand_env_with<F: FnMut(&str) -> Option<String>>(&mut self, var: F) -> Result<(), CfgError> {
+        for (path, expanded) in interpolate::expand_env(self, var)? {
+            if let Some(value) = self.get_mut(&path) {
+                *value = CfgValue::Str(expanded);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resolves every include within the map, replacing it with the contents of the included file as loaded by `loader`.
+    /// 
+    /// An include is a map with an `$include` key, whose value is passed to `loader`. Any other keys alongside
+    /// `$include` override the keys of the included file. Includes within included files are resolved too,
+    /// up to 16 levels deep.
+    /// 
+    /// Returns an error if `loader` fails, if includes form a cycle, if they're nested too deeply, or if they would
+    /// change a locked path or the type of a pinned path, in which case the map is left untouched.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut include = CfgMap::new();
+    /// include.add("$include", Str("db.toml".into()));
+    /// include.add("port", Int(6543));
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("db", Map(include));
+    /// 
+    /// // A real loader would read and parse the file, such as using `CfgMap::from_toml`.
+    /// cmap.resolve_includes(|file| match file {
+    ///     "db.toml" => {
+    ///         let mut db = CfgMap::new();
+    ///         db.add("host", Str("db.local".into()));
+    ///         db.add("port", Int(5432));
+    ///         Ok(db)
+    ///     },
+    ///     _ => Err(format!("{} not found", file)),
+    /// }).unwrap();
+    /// 
+    /// assert!(cmap.get("db/host").check_that(IsExactlyStr("db.local".into())));
+    /// assert!(cmap.get("db/port").check_that(IsExactlyInt(6543)));
+    /// ```
+    pub fn resolve_includes<E, F>(&mut self, loader: F) -> Result<(), CfgError>
+        where E: fmt::Display, F: FnMut(&str) -> Result<CfgMap, E>
+    {
+        let resolved = CfgMap { internal_map: Arc::new(include::resolve(self, loader)?), ..CfgMap::default() };
+        self.check_replacement(&resolved)?;
+        self.internal_map = resolved.internal_map;
+        Ok(())
+    }
+
+    /// Converts the map into a flat map, where every key is the full path of a value, with each segment
+    /// separated by `sep`. List elements use their index as the segment, so `a/0/b` becomes `a.0.b` when
+    /// `sep` is `"."`.
+    /// 
+    /// Only scalar values end up in the flat map, alongside any empty maps and lists, so that nothing is lost.
+    /// This is useful for exporting configurations as environment variables, metric tags, or into flat
+    /// key-value stores.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("db", Map(CfgMap::new()));
+    /// cmap.add("db/hosts", List(vec![Str("a".into()), Str("b".into())]));
+    /// cmap.add("db/port", Int(5432));
+    /// 
+    /// let flat = cmap.flatten("__");
+    /// assert_eq!(flat.len(), 3);
+    /// assert_eq!(flat["db__hosts__1"], Str("b".into()));
+    /// assert_eq!(flat["db__port"], Int(5432));
+    /// ```
+    pub fn flatten(&self, sep: &str) -> HashMap<String, CfgValue> {
+        flat::flatten(self, sep)
+    }
+
+    /// Converts a flat map, as created by `flatten`, back into a nested `CfgMap`.
+    /// 
+    /// Every key is split by `sep` into a path, creating nested maps as needed. If the keys within
+    /// a nested map are exactly the indexes `0` to `n`, a `List` is created instead.
+    /// 
+    /// Returns `None` if a key is both a value and the prefix of another key, such as with `a` and `a.b`.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// use std::collections::HashMap;
+    /// 
+    /// let mut flat = HashMap::new();
+    /// flat.insert("db.hosts.0".to_string(), Str("a".into()));
+    /// flat.insert("db.port".to_string(), Int(5432));
+    /// 
+    /// let cmap = CfgMap::unflatten(flat, ".").unwrap();
+    /// assert!(cmap.get("db/hosts").check_that(IsExactlyList(vec![Str("a".into())])));
+    /// assert!(cmap.get("db/port").check_that(IsExactlyInt(5432)));
+    /// ```
+    pub fn unflatten(map: HashMap<String, CfgValue>, sep: &str) -> Option<CfgMap> {
+        flat::unflatten(map, sep)
+    }
+
+    /// Records information about the running process under the reserved `__meta` key (`META_KEY`).
+    /// 
+    /// This includes the `hostname` (if it can be found), the `pid`, the time the information was
+    /// attached as seconds since the unix epoch (`loaded_at`), and a list of `sources`, each with the
+    /// `path` and `hash` of one of the files passed in. This helps with debugging long-running processes,
+    /// as it's possible to tell which files a configuration was loaded from, and when.
+    /// 
+    /// Since this information is different for every process, it's ignored by `fingerprint`, `diff` and `lint`.
+    /// Any previous runtime information is replaced.
+    /// 
+    /// Returns `CfgError::LoadFailed` if any of the `sources` couldn't be read, or an error if `__meta` is locked
+    /// or pinned to another type, the same as `add`. The map is left untouched if it returns an error.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("port", Int(80));
+    /// let fingerprint = cmap.fingerprint();
+    /// 
+    /// cmap.attach_runtime_info(&["Cargo.toml"]).unwrap();
+    /// 
+    /// assert!(cmap.get("__meta/pid").check_that(IsExactlyInt(std::process::id().into())));
+    /// assert!(cmap.get("__meta/sources/0/path").check_that(IsExactlyStr("Cargo.toml".into())));
+    /// assert!(cmap.get("__meta/sources/0/hash").check_that(IsStr));
+    /// assert_eq!(cmap.fingerprint(), fingerprint);
+    /// ```
+    pub fn attach_runtime_info<P: AsRef<std::path::Path>>(&mut self, sources: &[P]) -> Result<(), CfgError> {
+        let info = CfgValue::Map(runtime::runtime_info(sources).map_err(|e| CfgError::LoadFailed(e.to_string()))?);
+        self.check_write(META_KEY, &info)?;
+        self.internal_map_mut().insert(META_KEY.into(), info);
+        Ok(())
+    }
+
+    /// Makes every relative path within the map absolute, by joining it onto `base_dir`. This means that
+    /// paths behave the same no matter what the working directory of the process is.
+    /// 
+    /// Only strings at paths matching one of the `keys` patterns are treated as filesystem paths.
+    /// These use the same syntax as `get_all`, so `**/file` matches every `file` key. Locked paths are skipped.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// use std::path::Path;
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("log", Map(CfgMap::new()));
+    /// cmap.add("log/file", Str("logs/app.log".into()));
+    /// cmap.add("log/format", Str("json".into()));
+    /// 
+    /// cmap.resolve_relative_paths("/etc/app", &["**/file"]);
+    /// 
+    /// let expected = Path::new("/etc/app").join("logs/app.log");
+    /// assert!(cmap.get("log/file").check_that(IsExactlyStr(expected.to_string_lossy().into())));
+    /// assert!(cmap.get("log/format").check_that(IsExactlyStr("json".into())));
+    /// ```
+    pub fn resolve_relative_paths<P: AsRef<std::path::Path>>(&mut self, base_dir: P, keys: &[&str]) {
+        let paths: Vec<String> = keys.iter()
+            .flat_map(|pattern| self.get_all(pattern))
+            .map(|(path, _)| path)
+            .collect();
+
+        for path in paths {
+            if let Some(CfgValue::Str(value)) = self.get_mut(&path) {
+                if std::path::Path::new(value.as_str()).is_relative() {
+                    *value = base_dir.as_ref().join(&*value).to_string_lossy().into_owned();
+                }
+            }
+        }
+    }
+
+    /// Computes a stable fingerprint of the whole map.
+    /// 
+    /// The fingerprint is a hash of a canonical encoding of the contents, so two maps with the same
+    /// contents will always have the same fingerprint, regardless of insertion order, platform, or
+    /// process. This makes it suitable to log which configuration a service is running, or to detect drift.
+    /// The `default` path isn't part of the contents, and doesn't affect the fingerprint. Neither does
+    /// any runtime information attached through `attach_runtime_info`.
+    /// 
+    /// To fingerprint a subtree, use `CfgValue::fingerprint` on the value returned by `get`.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*};
+    /// 
+    /// let mut a = CfgMap::new();
+    /// a.add("host", Str("localhost".into()));
+    /// a.add("port", Int(80));
+    /// 
+    /// let mut b = CfgMap::new();
+    /// b.add("port", Int(80));
+    /// b.add("host", Str("localhost".into()));
+    /// 
+    /// assert_eq!(a.fingerprint(), b.fingerprint());
+    /// 
+    /// b.add("port", Int(8080));
+    /// assert_ne!(a.fingerprint(), b.fingerprint());
+    /// assert_eq!(a.get("host").unwrap().fingerprint(), b.get("host").unwrap().fingerprint());
+    /// ```
+    pub fn fingerprint(&self) -> u64 {
+        fingerprint::of_map(self, HashSet::new())
+    }
+
+    /// Computes a stable fingerprint of the map, as though any values matching the `excluded`
+    /// patterns didn't exist. The patterns use the same syntax as `get_all`.
+    /// 
+    /// This is useful to keep secrets from influencing the fingerprint, so that it can be
+    /// shared freely.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*};
+    /// 
+    /// let mut a = CfgMap::new();
+    /// a.add("db", Map(CfgMap::new()));
+    /// a.add("db/host", Str("localhost".into()));
+    /// 
+    /// let mut b = a.clone();
+    /// b.add("db/password", Str("hunter2".into()));
+    /// 
+    /// assert_ne!(a.fingerprint(), b.fingerprint());
+    /// assert_eq!(a.fingerprint(), b.fingerprint_excluding(&["**/password"]));
+    /// ```
+    pub fn fingerprint_excluding(&self, excluded: &[&str]) -> u64 {
+        let excluded = excluded.iter()
+            .flat_map(|pattern| self.get_all(pattern))
+            .map(|(path, _)| path)
+            .collect();
+
+        fingerprint::of_map(self, excluded)
+    }
+
+    /// Compares the map with `other`, returning every path that was added, removed or changed
+    /// going from `self` to `other`.
+    /// 
+    /// Nested maps and lists are recursed into, with list elements being compared by index.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*};
+    /// 
+    /// let mut old = CfgMap::new();
+    /// old.add("server", Map(CfgMap::new()));
+    /// old.add("server/port", Int(80));
+    /// old.add("debug", Bool(true));
+    /// 
+    /// let mut new = old.clone();
+    /// new.add("server/port", Int(8080));
+    /// new.add("server/host", Str("localhost".into()));
+    /// new.remove("debug");
+    /// 
+    /// let diff = old.diff(&new);
+    /// assert_eq!(diff.changed["server/port"], (Int(80), Int(8080)));
+    /// assert_eq!(diff.added["server/host"], Str("localhost".into()));
+    /// assert_eq!(diff.removed["debug"], Bool(true));
+    /// ```
+    pub fn diff(&self, other: &CfgMap) -> CfgDiff {
+        diff::diff(self, other)
+    }
+
+    /// Runs all of the default lint rules on the map, and returns what they found.
+    /// 
+    /// Unlike conditions, lints don't mean that a configuration is invalid, only that something
+    /// about it looks suspicious. See the [`lint`](./lint/index.html) module for the rules themselves.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("db", Map(CfgMap::new()));
+    /// cmap.add("db/password", Str("hunter2".into()));
+    /// 
+    /// let findings = cmap.lint();
+    /// assert_eq!(findings.len(), 1);
+    /// assert_eq!(findings[0].rule, "plaintext-secret");
+    /// assert_eq!(findings[0].path, "db/password");
+    /// ```
+    pub fn lint(&self) -> Vec<lint::LintFinding> {
+        lint::lint(self, &lint::default_rules())
+    }
+
+    /// Runs the lint rules passed on the map, and returns what they found.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, lint::{LintRule, EmptySections}};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("plugins", List(vec![]));
+    /// cmap.add("password", Str("hunter2".into()));
+    /// 
+    /// let rules: Vec<Box<dyn LintRule>> = vec![Box::new(EmptySections)];
+    /// let findings = cmap.lint_with(&rules);
+    /// assert_eq!(findings.len(), 1);
+    /// assert_eq!(findings[0].path, "plugins");
+    /// ```
+    pub fn lint_with(&self, rules: &[Box<dyn lint::LintRule>]) -> Vec<lint::LintFinding> {
+        lint::lint(self, rules)
+    }
+
+    /// Gets a reference to an option within the configuration.
+    /// 
+    /// It first tries to get 
+    /// `category/option` within the normal values. If this doesn't exist, it will then 
+    /// try to retrieve `option` from the default path instead (`self.default/option`).
+    /// 
+    /// Note that if `default` wasn't set on construction, this function will instead retrieve
+    /// the value from the root directory (`option`) directly.
+    /// 
+    /// If the option isn't within the default path either, it's retrieved from each of the `fallbacks` in order,
+    /// and lastly from the defaults added using `add_default`.
+    /// 
+    /// Returns `None` if the key doesn't exist in any of them.
+    /// 
+    /// The `key` can be of the form of the path `"a/b/...y/z/"`, in which case it will
+    /// go through the inner submaps `"a/b/..."` until a submap isn't found, or the end is reached.
+    /// This is for convenience sake, as doing this manually can prove to be verbose.
+    /// 
+    /// This key can also index into lists. So, for example `a/0/b` would try checking if `"a"`
+    /// is a list, and index into it. Otherwise it will try to find an internal map with the key `0`.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Checkable, Condition::*};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// let mut submap = CfgMap::new();
+    /// 
+    /// submap.add("OP1", Int(5));
+    /// cmap.add("OP1", Int(8));
+    /// 
+    /// cmap.add("sub", Map(submap));
+    /// 
+    /// assert!(cmap.get_option("sub", "OP1").check_that(IsExactlyInt(5)));
+    /// assert!(cmap.get_option("sub", "OP1").check_that(IsExactlyInt(5)));
+    /// assert!(cmap.get_option("sub", "OP2").is_none());
+    /// ```
+    pub fn get_option(&self, category: &str, option: &str) -> Option<&CfgValue> {
+        self.get_option_multi(&[category], option)
+    }
+
+    /// Gets a reference to an option the same way as `get_option`, but tries `option` within each of `categories`
+    /// in order before falling back to the defaults. This is useful for options that several sections can
+    /// override, such as handlers overriding the settings of a server.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Checkable, Condition::*};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.default = "default/".into();
+    /// cmap.add("default", Map(CfgMap::new()));
+    /// cmap.add("default/timeout", Int(30));
+    /// cmap.add("http", Map(CfgMap::new()));
+    /// cmap.add("http/timeout", Int(10));
+    /// cmap.add("http/upload", Map(CfgMap::new()));
+    /// 
+    /// assert!(cmap.get_option_multi(&["http/upload", "http"], "timeout").check_that(IsExactlyInt(10)));
+    /// cmap.add("http/upload/timeout", Int(60));
+    /// assert!(cmap.get_option_multi(&["http/upload", "http"], "timeout").check_that(IsExactlyInt(60)));
+    /// assert!(cmap.get_option_multi(&["ftp"], "timeout").check_that(IsExactlyInt(30)));
+    /// assert!(cmap.get_option_multi(&[], "timeout").check_that(IsExactlyInt(30)));
+    /// ```
+    pub fn get_option_multi(&self, categories: &[&str], option: &str) -> Option<&CfgValue> {
+        // Deprecations and access tracking need the full paths, but otherwise they're followed without joining them.
+        if self.needs_paths() {
+            return categories.iter().find_map(|category| self.get(&format!("{}/{}", category, option)))
+                .or_else(|| self.default_paths(option).iter().find_map(|default| self.get(default)))
+                .or_else(|| self.get_default(option));
+        }
+
+        fn dir(dir: &str) -> impl Iterator<Item = &str> {
+            let dir = dir.trim_end_matches('/');
+            dir.split('/').filter(move |_| !dir.is_empty())
+        }
+
+        categories.iter().find_map(|category| self.follow(category.split('/').chain(option.split('/'))))
+            .or_else(|| self.follow(dir(&self.default).chain(option.split('/'))))
+            .or_else(|| self.fallbacks.iter().find_map(|fallback| self.follow(dir(fallback).chain(option.split('/')))))
+            .or_else(|| self.get_default(option))
+    }
+
+    /// Gets every option within `category`, with defaults applied, the same way as `get_option` would.
+    /// 
+    /// The result contains every option within the default path, overridden by every option within `category`.
+    /// Where both contain a map for the same option, the maps are merged the same way, so nested options fall
+    /// back to their defaults too. If `default` wasn't set, the root of the map is used as the default path.
+    /// Options within the `fallbacks` are included too, where earlier fallbacks override later ones, and are
+    /// overridden by the default path. The defaults added using `add_default` are overridden by all of them.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Checkable, Condition::*};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.default = "default/".into();
+    /// cmap.add("default", Map(CfgMap::new()));
+    /// cmap.add("default/port", Int(80));
+    /// cmap.add("default/host", Str("localhost".into()));
+    /// cmap.add("server", Map(CfgMap::new()));
+    /// cmap.add("server/port", Int(8080));
+    /// 
+    /// let server = cmap.options_in("server");
+    /// assert!(server.get("port").check_that(IsExactlyInt(8080)));
+    /// assert!(server.get("host").check_that(IsExactlyStr("localhost".into())));
+    /// ```
+    pub fn options_in(&self, category: &str) -> CfgMap {
+        let dir = |dir: &str| {
+            let dir = dir.trim_end_matches('/');
+            if dir.is_empty() { Some(self) } else { self.get(dir).and_then(CfgValue::as_map) }
+        };
+        let fallbacks = self.fallbacks.iter().rev().filter_map(|fallback| dir(fallback));
+        let stored = self.state().map(|state| &state.defaults);
+
+        let mut options = CfgMap::new();
+        for map in stored.into_iter().chain(fallbacks).chain(dir(&self.default)).chain(self.get(category).and_then(CfgValue::as_map)) {
+            Merger::new().merge_unchecked(&mut options, map);
+        }
+        options
+    }
+
+    /// Resolves the configuration for a profile, such as `dev` or `prod`, by merging the map at `profiles/<name>`
+    /// over the rest of the configuration, and removing the `profiles` section. Maps are merged key by key, the
+    /// same way as `options_in`, so a profile only needs to contain the values it changes.
+    /// 
+    /// Returns `CfgError::InvalidPath` if the profile doesn't exist, or isn't a map.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgError, CfgValue::*, Checkable, Condition::*};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("db", Map(CfgMap::new()));
+    /// cmap.add("db/host", Str("localhost".into()));
+    /// cmap.add("db/pool", Int(5));
+    /// cmap.add_force("profiles/prod/db/host", Str("db.internal".into()));
+    /// 
+    /// let prod = cmap.resolve_profile("prod").unwrap();
+    /// assert!(prod.get("db/host").check_that(IsExactlyStr("db.internal".into())));
+    /// assert!(prod.get("db/pool").check_that(IsExactlyInt(5)));
+    /// assert!(prod.get("profiles").is_none());
+    /// 
+    /// assert_eq!(cmap.resolve_profile("staging"), Err(CfgError::InvalidPath("profiles/staging".into())));
+    /// ```
+    pub fn resolve_profile(&self, name: &str) -> Result<CfgMap, CfgError> {
+        let path = format!("profiles/{}", name);
+        let profile = self.get_untracked(&path).and_then(CfgValue::as_map).ok_or(CfgError::InvalidPath(path))?;
+
+        let mut resolved = self.clone();
+        resolved.internal_map_mut().remove("profiles");
+        Merger::new().merge(&mut resolved, profile)?;
+        Ok(resolved)
+    }
+
+    /// Fills in every value within `defaults` that's missing from the map, without replacing any existing values.
+    /// Where both contain a map for the same key, missing values within it are filled in too, while lists and
+    /// other values already within the map are kept as they are.
+    /// 
+    /// This allows building the full tree of defaults in code, and filling in whatever a parsed file left out.
+    /// 
+    /// Returns `CfgError::Locked` or `CfgError::TypeMismatch` if a missing value would be added to a locked path,
+    /// or change the type of a pinned path, in which case nothing is filled in.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Checkable, Condition::*, list, value};
+    /// 
+    /// let mut defaults = CfgMap::new();
+    /// defaults.add_force("server/port", Int(80));
+    /// defaults.add_force("server/host", Str("localhost".into()));
+    /// defaults.add("plugins", list!["auth", "log"]);
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add_force("server/port", Int(8080));
+    /// cmap.add("plugins", list!["log"]);
+    /// cmap.set_defaults(defaults).unwrap();
+    /// 
+    /// assert!(cmap.get("server/port").check_that(IsExactlyInt(8080)));
+    /// assert!(cmap.get("server/host").check_that(IsExactlyStr("localhost".into())));
+    /// assert!(cmap.get("plugins").check_that(IsListWithLength(1)));
+    /// ```
+    pub fn set_defaults(&mut self, defaults: CfgMap) -> Result<(), CfgError> {
+        let mut filled = self.contents();
+        self.fill_missing("", &mut filled, &defaults)?;
+        self.internal_map = filled.internal_map;
+        Ok(())
+    }
+
+    /// Adds every value within `defaults` that's missing from `into`, which is at `path`, checking each write
+    /// against the locks and pins of the map.
+    fn fill_missing(&self, path: &str, into: &mut CfgMap, defaults: &CfgMap) -> Result<(), CfgError> {
+        for (key, default) in defaults.iter() {
+            let full = join_path(path, key);
+            match (into.internal_map.get(key), default) {
+                (Some(CfgValue::Map(_)), CfgValue::Map(default)) => {
+                    if let Some(CfgValue::Map(existing)) = into.internal_map_mut().get_mut(key) {
+                        self.fill_missing(&full, existing, default)?;
+                    }
+                },
+                (Some(_), _) => {},
+                (None, default) => {
+                    self.check_write(&full, default)?;
+                    into.internal_map_mut().insert(key.clone(), default.clone());
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds a default value, the same way as `add`, but to a separate map of defaults rather than to the map itself.
+    /// 
+    /// Unlike the defaults at the `default` path, these aren't part of the map's keys, so they aren't iterated over,
+    /// written, displayed or compared. They're used by `get_option`, `options_in` and `update_option` when an option
+    /// isn't found anywhere else.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Checkable, Condition::*};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add_default("port", Int(80));
+    /// cmap.add("server", Map(CfgMap::new()));
+    /// 
+    /// assert!(cmap.get_option("server", "port").check_that(IsExactlyInt(80)));
+    /// assert!(cmap.get_default("port").check_that(IsExactlyInt(80)));
+    /// assert!(cmap.get("port").is_none());
+    /// assert!(cmap.is_empty() == false && cmap.len() == 1);
+    /// ```
+    pub fn add_default(&mut self, key: &str, value: CfgValue) -> Result<Option<CfgValue>, CfgError> {
+        self.state_mut().defaults.add(key, value)
+    }
+
+    /// Gets a reference to a default value added using `add_default`.
+    pub fn get_default(&self, key: &str) -> Option<&CfgValue> {
+        self.state()?.defaults.get(key)
+    }
+
+    /// Gets a mutable reference to a default value added using `add_default`.
+    pub fn get_default_mut(&mut self, key: &str) -> Option<&mut CfgValue> {
+        self.state.as_mut()?.defaults.get_mut(key)
+    }
+
+    /// Updates the option with the new value `to`.
+    /// 
+    /// It first tries to get 
+    /// `category/option` within the normal values. If this doesn't exist, it will then 
+    /// try to retrieve `option` from the default path instead (`self.default/option`).
+    /// 
+    /// Note that if `default` wasn't set on construction, this function will instead retrieve
+    /// the value from the root directory (`option`) directly.
+    /// 
+    /// The `key` can be of the form of the path `"a/b/...y/z/"`, in which case it will
+    /// go through the inner submaps `"a/b/..."` until a submap isn't found, or the end is reached.
+    /// This is for convenience sake, as doing this manually can prove to be verbose.
+    /// 
+    /// This key can also index into lists. So, for example `a/0/b` would try checking if `"a"`
+    /// is a list, and index into it. Otherwise it will try to find an internal map with the key `0`.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Checkable, Condition::*};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// let mut submap = CfgMap::new();
+    /// 
+    /// submap.add("OP1", Int(5));
+    /// cmap.add("OP1", Int(8));
+    /// 
+    /// cmap.add("sub", Map(submap));
+    /// 
+    /// let ol1 = cmap.update_option("sub", "OP1", Int(10));
+    /// let ol2 = cmap.update_option("foo", "OP1", Int(16));
+    /// let ol3 = cmap.update_option("sub", "OP2", Int(99));
+    /// 
+    /// assert!(cmap.get_option("sub", "OP1").check_that(IsExactlyInt(10)));
+    /// assert!(cmap.get_option("foo", "OP1").check_that(IsExactlyInt(16)));
+    /// assert!(cmap.get_option("sub", "OP2").is_none());
+    /// 
+    /// assert_eq!(ol1, Ok(Some(Int(5))));
+    /// assert_eq!(ol2, Ok(Some(Int(8))));
+    /// assert_eq!(ol3, Ok(None));
+    /// ```
+    /// 
+    /// Returns `Err(CfgError::TypeMismatch)` if the option found would change the type of a path pinned using
+    /// `pin_type`, or `Err(CfgError::Locked)` if it's locked using `lock_path`, in which case it isn't updated.
+    pub fn update_option(&mut self, category: &str, option: &str, to: CfgValue) -> Result<Option<CfgValue>, CfgError> {
+        let fullkey = format!("{}/{}", category, option);
+
+        for path in std::iter::once(fullkey).chain(self.default_paths(option)) {
+            if self.get_untracked(&path).is_some() {
+                self.check_write(&path, &to)?;
+                return match self.get_mut_unlocked(&path) {
+                    Some(x) => Ok(Some(mem::replace(x, to))),
+                    // Nested maps can have locks of their own.
+                    None => Err(CfgError::Locked(path)),
+                };
+            }
+        }
+        Ok(self.get_default_mut(option).map(|x| mem::replace(x, to)))
+    }
+
+    /// Applies a JSON Patch, as described in RFC 6902, onto the map.
+    /// 
+    /// The `patch` has to be a `List` of operations, each of which is a `Map` with an `op` and a `path`,
+    /// and a `value` or `from` depending on the operation. All paths are JSON Pointers, as used by
+    /// `get_pointer`. The supported operations are `add`, `remove`, `replace`, `move`, `copy` and `test`.
+    /// 
+    /// The patch is applied atomically - if any of the operations fail, the map is left untouched.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("port", Int(80));
+    /// 
+    /// let mut replace = CfgMap::new();
+    /// replace.add("op", "replace".into());
+    /// replace.add("path", "/port".into());
+    /// replace.add("value", Int(443));
+    /// 
+    /// let mut test = CfgMap::new();
+    /// test.add("op", "test".into());
+    /// test.add("path", "/port".into());
+    /// test.add("value", Int(443));
+    /// 
+    /// assert!(cmap.apply_patch(&List(vec![Map(replace.clone()), Map(test.clone())])).is_ok());
+    /// assert!(cmap.get("port").check_that(IsExactlyInt(443)));
+    /// 
+    /// // The test fails, so the replacement never happens.
+    /// replace.add("value", Int(8080));
+    /// test.add("value", Int(80));
+    /// assert!(cmap.apply_patch(&List(vec![Map(replace), Map(test)])).is_err());
+    /// assert!(cmap.get("port").check_that(IsExactlyInt(443)));
+    /// ```
+    pub fn apply_patch(&mut self, patch: &CfgValue) -> Result<(), PatchError> {
+        *self = self.patched(patch)?;
+        Ok(())
+    }
+
+    /// Previews applying `patch` using `apply_patch`, without changing the map. The patched copy is validated
+    /// against `schema`, and the changes the patch would make are returned if it's valid.
+    /// 
+    /// Fails with `SimulateError::Patch` if `apply_patch` would fail, or with `SimulateError::Invalid`, holding
+    /// the report from `Schema::validate`, if the patched copy doesn't satisfy the schema.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable, Schema, SimulateError};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("port", Int(80));
+    /// 
+    /// let mut rules = CfgMap::new();
+    /// rules.add("port", Str("int in 1..65535".into()));
+    /// let schema = Schema::from_map(&rules).unwrap();
+    /// 
+    /// let mut replace = CfgMap::new();
+    /// replace.add("op", "replace".into());
+    /// replace.add("path", "/port".into());
+    /// replace.add("value", Int(443));
+    /// 
+    /// let diff = cmap.simulate(&List(vec![Map(replace.clone())]), &schema).unwrap();
+    /// assert_eq!(diff.changed["port"], (Int(80), Int(443)));
+    /// assert!(cmap.get("port").check_that(IsExactlyInt(80)));
+    /// 
+    /// replace.add("value", Int(0));
+    /// let result = cmap.simulate(&List(vec![Map(replace)]), &schema);
+    /// assert!(matches!(result, Err(SimulateError::Invalid(report)) if report.failures[0].path() == "port"));
+    /// ```
+    pub fn simulate(&self, patch: &CfgValue, schema: &Schema) -> Result<CfgDiff, SimulateError> {
+        let patched = self.patched(patch)?;
+        schema.validate(&patched).into_result()?;
+        Ok(self.diff(&patched))
+    }
+
+    /// Gets a copy of the map with `patch` applied, unless it would change a locked path, or the type of a pinned path.
+    fn patched(&self, patch: &CfgValue) -> Result<CfgMap, PatchError> {
+        let patched = patch::apply(self, patch)?;
+
+        match self.check_replacement(&patched) {
+            Ok(()) => Ok(patched),
+            Err(CfgError::Locked(path)) => Err(PatchError::Locked(path)),
+            Err(CfgError::TypeMismatch { path, expected, found }) => Err(PatchError::TypeMismatch { path, expected, found }),
+            Err(error) => Err(PatchError::Rejected(error)),
+        }
+    }
+
+    /// Marks every value whose path matches `pattern` as secret, so that it's printed as `***` when
+    /// formatting the map using `Debug`. The pattern uses the same syntax as `get_all`, so `**/password` would
+    /// mark every `password` key as secret. Values added after marking are covered as well.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("db", Map(CfgMap::new()));
+    /// cmap.add("db/password", Str("hunter2".into()));
+    /// cmap.mark_secret("**/password");
+    /// 
+    /// let output = format!("{:?}", cmap);
+    /// assert!(output.contains("***"));
+    /// assert!(!output.contains("hunter2"));
+    /// ```
+    pub fn mark_secret(&mut self, pattern: &str) {
+        self.state_mut().secrets.push(pattern.to_string());
+    }
+
+    /// Checks whether the value at `path` was marked as secret using `mark_secret`.
+    /// Values within a secret map or list are considered secret too.
+    pub fn is_secret(&self, path: &str) -> bool {
+        self.secret_paths().iter().any(|secret| secret == path || is_within(path, secret))
+    }
+
+    /// Gets the path of every value currently marked as secret.
+    fn secret_paths(&self) -> HashSet<String> {
+        self.state()
+            .map(|state| state.secrets.iter().flat_map(|pattern| self.get_all(pattern)).map(|(path, _)| path).collect())
+            .unwrap_or_default()
+    }
+
+    /// Deprecates `path`, and everything within it. The first time a deprecated path is read using `get`,
+    /// or the methods built on it such as `get_option`, it's reported along with the `message`.
+    /// Deprecated paths are reported using the function set with `on_deprecated`, and aren't reported at all
+    /// until one is set. If a path is within several deprecated paths, the most specific one applies.
+    /// 
+    /// If there's a `replacement`, reading the deprecated path reads the replacement path instead, as long as
+    /// it contains a value. This allows a path to be renamed without breaking code that still uses the old one.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("server", Map(CfgMap::new()));
+    /// cmap.add("server/port", Int(8080));
+    /// 
+    /// cmap.on_deprecated(|path, message| println!("{} is deprecated: {}", path, message));
+    /// cmap.deprecate("port", "use `server/port` instead", Some("server/port"));
+    /// 
+    /// assert!(cmap.get("port").check_that(IsExactlyInt(8080)));
+    /// ```
+    pub fn deprecate(&mut self, path: &str, message: &str, replacement: Option<&str>) {
+        let deprecation = deprecation::Deprecation {
+            message: message.to_string(),
+            replacement: replacement.map(str::to_string),
+        };
+        self.state_mut().deprecations.insert(path.to_string(), deprecation);
+    }
+
+    /// Sets the function that deprecated paths are reported to. Nothing is reported until one is set.
+    /// It's called with the deprecated path and its message. See `deprecate` for more details.
+    pub fn on_deprecated<F: Fn(&str, &str) + Send + Sync + 'static>(&mut self, handler: F) {
+        self.state_mut().deprecation_handler = Some(deprecation::Handler(std::sync::Arc::new(handler)));
+    }
+
+    /// Registers a migration, which upgrades the map from the version before `version` to `version`,
+    /// such as by moving renamed keys. Migrations are run in order by `migrate_to`, and registering
+    /// a migration for the same version again replaces it.
+    pub fn register_migration(&mut self, version: _Int, migration: fn(&mut CfgMap)) {
+        self.state_mut().migrations.steps.insert(version, migration);
+    }
+
+    /// Sets the path of the integer holding the version of the map, used by `migrate_to`. This is `version` by default.
+    pub fn set_version_field(&mut self, path: &str) {
+        self.state_mut().migrations.field = path.to_string();
+    }
+
+    /// Runs every migration registered using `register_migration` that's newer than the version of the map,
+    /// up to and including `latest`, in order. The version field is updated after each migration, and is
+    /// set to `latest` at the end. A map without a version field is treated as version 0.
+    /// 
+    /// Returns the versions of the migrations that were run, or `CfgError::InvalidVersion` if the version
+    /// field isn't an integer, or is newer than `latest`.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("version", Int(1));
+    /// cmap.add("timeout", Int(30));
+    /// 
+    /// // Version 2 measures `timeout` in milliseconds, and renames it.
+    /// cmap.register_migration(2, |cmap| {
+    ///     if let Some(Int(secs)) = cmap.remove("timeout") {
+    ///         cmap.add("timeout_ms", Int(secs * 1000));
+    ///     }
+    /// });
+    /// 
+    /// assert_eq!(cmap.migrate_to(2), Ok(vec![2]));
+    /// assert!(cmap.get("timeout_ms").check_that(IsExactlyInt(30000)));
+    /// assert!(cmap.get("version").check_that(IsExactlyInt(2)));
+    /// ```
+    pub fn migrate_to(&mut self, latest: _Int) -> Result<Vec<_Int>, CfgError> {
+        let migrations = self.state().map(|state| state.migrations.clone()).unwrap_or_default();
+        migrations::migrate(self, &migrations, latest)
+    }
+
+    /// Starts tracking which paths are read using `get`, and the methods built on it such as `get_option`,
+    /// clearing any paths that were tracked before. Use `unused_keys` to find the values that were never read.
+    /// 
+    /// Tracking is kept when the map is cloned, and has to be enabled on the root map, as paths are
+    /// tracked relative to the map `get` was called on.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("port", Int(80));
+    /// cmap.add("prot", Str("http".into()));
+    /// cmap.track_access();
+    /// 
+    /// cmap.get("port");
+    /// assert_eq!(cmap.unused_keys(), vec!["prot"]);
+    /// ```
+    pub fn track_access(&mut self) {
+        self.state_mut().accessed = Some(tracking::PathLog::default());
+    }
+
+    /// Gets the full path of every value that wasn't read since `track_access` was called, sorted.
+    /// Only the innermost values are returned, and reading a map or list counts as reading everything within it.
+    /// 
+    /// If `track_access` was never called, every value is returned.
+    pub fn unused_keys(&self) -> Vec<String> {
+        match self.state().and_then(|state| state.accessed.as_ref()) {
+            Some(accessed) => accessed.unused(self),
+            None => self.walk().map(|(path, _)| path).collect(),
+        }
+    }
+
+    /// Freezes the map, so that it can only be read from. This is useful for handing out the configuration
+    /// after it has been validated, guaranteeing that it can't be changed afterwards.
+    /// 
+    /// The frozen map can be cloned cheaply and shared between threads.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("port", Int(80));
+    /// 
+    /// let frozen = cmap.freeze();
+    /// let shared = frozen.clone();
+    /// 
+    /// assert!(shared.get("port").check_that(IsExactlyInt(80)));
+    /// ```
+    pub fn freeze(self) -> FrozenCfgMap {
+        FrozenCfgMap::new(self)
+    }
+
+    /// Wraps the map so that it can only be changed through methods that check its paths, locks and pinned types.
+    /// The wrapped map doesn't deref mutably, so its internal map can only be changed directly through `raw_map_mut`.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*};
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("port", Int(80));
+    /// cmap.lock_path("port");
+    /// 
+    /// let mut checked = cmap.checked();
+    /// assert!(checked.add("port", Int(8080)).is_err());
+    /// ```
+    pub fn checked(self) -> CheckedCfgMap {
+        CheckedCfgMap::new(self)
+    }
+
+    #[cfg(feature = "typed")]
+    /// Gets a typed copy of the value at `path`, which is written back into the map when it's dropped, or
+    /// when calling `Synced::finish`, which also returns any error. Only available if using `typed`.
+    /// 
+    /// Only the values that changed are written back, each using `add`, so keys that `T` doesn't contain are left
+    /// as they were, and so are nested maps along with their own default path and fallbacks. Nothing is written if
+    /// nothing changed, or if any of the changes can't be written, such as when one of them is locked.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// use serde::{Deserialize, Serialize};
+    /// 
+    /// #[derive(Serialize, Deserialize)]
+    /// struct Database {
+    ///     host: String,
+    ///     port: i64,
+    /// }
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("db", Map(CfgMap::new()));
+    /// cmap.add("db/host", Str("localhost".into()));
+    /// cmap.add("db/port", Int(5432));
+    /// 
+    /// let mut db = cmap.sync::<Database>("db").unwrap();
+    /// db.port += 1;
+    /// db.finish().unwrap();
+    /// 
+    /// assert!(cmap.get("db/port").check_that(IsExactlyInt(5433)));
+    /// ```
+    pub fn sync<T>(&mut self, path: &str) -> Result<Synced<'_, T>, CfgError>
+        where T: serde::Serialize + serde::de::DeserializeOwned
+    {
+        let current = self.get(path.trim_end_matches('/')).cloned()
+            .ok_or_else(|| CfgError::InvalidPath(path.trim_end_matches('/').to_string()))?;
+        Synced::new(self, path, current)
+    }
+
+    #[cfg(feature = "typed")]
+    /// Deserializes every option within `category` into a `T`, with defaults applied the same way as `options_in`,
+    /// so that fields missing from `category` fall back to the default path. Only available if using `typed`.
+    /// 
+    /// Returns `CfgError::SyncFailed` with the path of the first option that couldn't be deserialized, along with
+    /// the source it came from, such as the file it was read from using `from_file`, if one was recorded.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*};
+    /// use serde::Deserialize;
+    /// 
+    /// #[derive(Deserialize)]
+    /// struct Server {
+    ///     host: String,
+    ///     port: i64,
+    /// }
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.default = "default/".into();
+    /// cmap.add("default", Map(CfgMap::new()));
+    /// cmap.add("default/host", Str("localhost".into()));
+    /// cmap.add("default/port", Int(80));
+    /// cmap.add("api", Map(CfgMap::new()));
+    /// cmap.add("api/port", Int(8080));
+    /// 
+    /// let api: Server = cmap.options_as("api").unwrap();
+    /// assert_eq!((api.host.as_str(), api.port), ("localhost", 8080));
+    /// ```
+    pub fn options_as<T: serde::de::DeserializeOwned>(&self, category: &str) -> Result<T, CfgError> {
+        typed::deserialize(self, category.trim_end_matches('/'), &CfgValue::Map(self.options_in(category)))
+    }
+
+    #[cfg(feature = "typed")]
+    /// Gets a typed copy of every option within `category`, with defaults applied the same way as `options_as`.
+    /// It's written back the same way as `sync`, so only the options that changed are added to `category`,
+    /// creating it if needed. Only available if using `typed`.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    /// use serde::{Deserialize, Serialize};
+    /// 
+    /// #[derive(Serialize, Deserialize)]
+    /// struct Server {
+    ///     host: String,
+    ///     port: i64,
+    /// }
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.default = "default/".into();
+    /// cmap.add("default", Map(CfgMap::new()));
+    /// cmap.add("default/host", Str("localhost".into()));
+    /// cmap.add("default/port", Int(80));
+    /// 
+    /// let mut api = cmap.sync_options::<Server>("api").unwrap();
+    /// api.port = 8080;
+    /// api.finish().unwrap();
+    /// 
+    /// assert!(cmap.get("api/port").check_that(IsExactlyInt(8080)));
+    /// assert!(cmap.get("api/host").is_none());
+    /// ```
+    pub fn sync_options<T>(&mut self, category: &str) -> Result<Synced<'_, T>, CfgError>
+        where T: serde::Serialize + serde::de::DeserializeOwned
+    {
+        let options = CfgValue::Map(self.options_in(category));
+        Synced::new(self, category, options)
+    }
+}
+
+#[cfg(feature = "from_json")]
+impl TryFrom<JsonValue> for CfgMap {
+    type Error = CfgError;
+
+    fn try_from(value: JsonValue) -> Result<Self, CfgError> {
+        from_json::json_to_cfg(value)
+    }
+}
+
+#[cfg(feature = "from_toml")]
+impl TryFrom<TomlValue> for CfgMap {
+    type Error = CfgError;
+
+    fn try_from(value: TomlValue) -> Result<Self, CfgError> {
+        from_toml::toml_to_cfg(value)
+    }
+}
+
+#[cfg(feature = "from_yaml")]
+impl TryFrom<YamlValue> for CfgMap {
+    type Error = CfgError;
+
+    fn try_from(value: YamlValue) -> Result<Self, CfgError> {
+        from_yaml::yaml_to_cfg(value)
+    }
+}
+
+pub mod prelude {
+    pub use crate::*;
+    pub use crate::{CfgValue::*, Condition::*};
+}
+
+#[cfg(test)]
+#[allow(clippy::single_component_path_imports)]
 mod tests {
     #[cfg(feature = "from_json")]
     use serde_json;
@@ -1012,6 +3896,386 @@ mod tests {
     #[cfg(feature = "from_yaml")]
     use yaml_rust::YamlLoader;
 
+    #[test]
+    fn get_all_wildcards() {
+        let mut cmap = CfgMap::new();
+        cmap.add("a", Map(CfgMap::new())).unwrap();
+        cmap.add("a/b", Map(CfgMap::new())).unwrap();
+        cmap.add("a/b/c", Int(1)).unwrap();
+        cmap.add("a/list", List(vec![Map(CfgMap::new()), Int(3)])).unwrap();
+        cmap.add("a/list/0/c", Int(2)).unwrap();
+        cmap.add("c", Int(0)).unwrap();
+
+        let paths = |pattern| cmap.get_all(pattern).into_iter().map(|(p, _)| p).collect::<Vec<_>>();
+
+        assert_eq!(paths("**/c"), vec!["a/b/c", "a/list/0/c", "c"]);
+        assert_eq!(paths("a/*/c"), vec!["a/b/c"]);
+        assert_eq!(paths("a/list/*"), vec!["a/list/0", "a/list/1"]);
+        assert_eq!(paths("a/**"), vec!["a/b", "a/b/c", "a/list", "a/list/0", "a/list/0/c", "a/list/1"]);
+        assert_eq!(paths("**/**/c"), vec!["a/b/c", "a/list/0/c", "c"]);
+        assert_eq!(paths("c"), vec!["c"]);
+        assert!(paths("d/*").is_empty());
+    }
+
+    #[test]
+    fn map_values_mut_order() {
+        let mut cmap = CfgMap::new();
+        cmap.add("a", Map(CfgMap::new())).unwrap();
+        cmap.add("a/b", Int(1)).unwrap();
+        cmap.add("list", list![1, 2]).unwrap();
+
+        let mut visited = Vec::new();
+        cmap.map_values_mut(|path, value| {
+            visited.push(path.to_string());
+
+            // Replacing a list means its replacement is what gets visited.
+            if path == "list" {
+                *value = list![10];
+            } else if let Int(i) = value {
+                *i += 1;
+            }
+        });
+
+        assert_eq!(visited, vec!["a", "a/b", "list", "list/0"]);
+        assert!(cmap.get("a/b").check_that(IsExactlyInt(2)));
+        assert!(cmap.get("list").check_that(IsExactlyList(vec![Int(11)])));
+    }
+
+    #[test]
+    fn retain_paths_pruning() {
+        let mut cmap = CfgMap::new();
+        cmap.add("list", list![1, 2, 3, 4]).unwrap();
+        cmap.add("empty", Map(CfgMap::new())).unwrap();
+        cmap.add("nested", List(vec![Map(CfgMap::new())])).unwrap();
+        cmap.add("nested/0/drop", Int(0)).unwrap();
+
+        cmap.retain_paths(|path, _| !matches!(path, "list/1" | "list/2" | "nested/0/drop"));
+
+        // Already empty maps are kept, only emptied ones are pruned.
+        assert!(cmap.get("list").check_that(IsExactlyList(vec![Int(1), Int(4)])));
+        assert!(cmap.get("empty").check_that(IsMap));
+        assert!(cmap.get("nested").is_none());
+    }
+
+    #[test]
+    fn pinned_types() {
+        let mut cmap = CfgMap::new();
+        cmap.add("servers", List(vec![Map(CfgMap::new())])).unwrap();
+        cmap.pin_type("servers/0/port", CfgType::Int).unwrap();
+
+        // Pins apply to nested values within replaced maps and lists.
+        let mut server = CfgMap::new();
+        server.add("port", Str("80".into())).unwrap();
+        let err = cmap.add("servers", List(vec![Map(server)])).unwrap_err();
+        assert_eq!(err, CfgError::TypeMismatch { path: "servers/0/port".into(), expected: CfgType::Int, found: CfgType::Str });
+
+        assert!(cmap.add("servers/0/port", Float(80.0)).is_err());
+        assert!(cmap.add("servers/0/port", Int(80)).is_ok());
+        assert!(cmap.get("servers/0/port").check_that(IsExactlyInt(80)));
+
+        // Pins don't affect equality.
+        let mut other = CfgMap::new();
+        other.add("servers", List(vec![Map(CfgMap::new())])).unwrap();
+        other.add("servers/0/port", Int(80)).unwrap();
+        assert_eq!(cmap, other);
+    }
+
+    #[test]
+    fn pinned_updates() {
+        let mut cmap = CfgMap::new();
+        cmap.add_force("server/port", Int(80)).unwrap();
+        cmap.add("name", Str("app".into())).unwrap();
+        cmap.pin_type("server/port", CfgType::Int).unwrap();
+
+        assert!(matches!(cmap.update_option("server", "port", Str("x".into())), Err(CfgError::TypeMismatch { .. })));
+        assert!(cmap.get("server/port").check_that(IsExactlyInt(80)));
+        assert_eq!(cmap.update_option("server", "port", Int(443)), Ok(Some(Int(80))));
+
+        // Changes that would break a pin are undone, while the rest are kept.
+        cmap.map_values_mut(|_, value| if !value.is_map() { *value = Str("x".into()) });
+        assert!(cmap.get("server/port").check_that(IsExactlyInt(443)));
+        assert!(cmap.get("name").check_that(IsExactlyStr("x".into())));
+
+        let mut server = CfgMap::new();
+        server.add("port", Str("80".into())).unwrap();
+        cmap.map_values_mut(|path, value| if path == "server" { *value = Map(server.clone()) });
+        assert!(cmap.get("server/port").check_that(IsExactlyInt(443)));
+    }
+
+    #[test]
+    fn move_path_edge_cases() {
+        let mut cmap = CfgMap::new();
+        cmap.add("a", Map(CfgMap::new())).unwrap();
+        cmap.add("a/b", Int(1)).unwrap();
+        cmap.add("list", list![1]).unwrap();
+        let original = cmap.clone();
+
+        // Into itself, out of a list, or through a scalar.
+        assert!(cmap.move_path("a", "a/b/c").is_err());
+        assert!(cmap.move_path("list/0", "x").is_err());
+        assert!(cmap.move_path("a", "a/b/x").is_err());
+        assert_eq!(cmap, original);
+
+        // Onto an ancestor, and onto the end of a list.
+        cmap.move_path("a/b", "a").unwrap();
+        assert!(cmap.get("a").check_that(IsExactlyInt(1)));
+        cmap.move_path("a", "list/-").unwrap();
+        assert!(cmap.get("list").check_that(IsExactlyList(vec![Int(1), Int(1)])));
+        assert!(cmap.get("a").is_none());
+    }
+
+    #[test]
+    fn locked_paths() {
+        let mut cmap = CfgMap::new();
+        cmap.add("security", Map(CfgMap::new())).unwrap();
+        cmap.add("security/tls", Map(CfgMap::new())).unwrap();
+        cmap.add("security/tls/enabled", Bool(true)).unwrap();
+        cmap.add("security/level", Int(1)).unwrap();
+        cmap.lock_path("security/tls");
+        let original = cmap.clone();
+
+        // Siblings can change, but containing maps can't be replaced.
+        assert!(cmap.add("security/level", Int(2)).is_ok());
+        assert_eq!(cmap.add("security", Int(0)), Err(CfgError::Locked("security".into())));
+        assert!(cmap.get_mut("security").is_none());
+        assert!(cmap.get_pointer_mut("/security/tls/enabled").is_none());
+        assert!(cmap.move_path("security/tls", "tls").is_err());
+
+        cmap.map_values_mut(|_, value| *value = Int(0));
+        cmap.retain_paths(|_, _| false);
+        assert_eq!(cmap.get("security/tls"), original.get("security/tls"));
+        assert!(cmap.get("security/level").is_none());
+
+        let mut remove = CfgMap::new();
+        remove.add("op", Str("remove".into())).unwrap();
+        remove.add("path", Str("/security/tls/enabled".into())).unwrap();
+        assert_eq!(cmap.apply_patch(&List(vec![Map(remove)])), Err(PatchError::Locked("security/tls".into())));
+        assert!(cmap.get("security/tls/enabled").check_that(IsTrue));
+    }
+
+    #[test]
+    fn locked_options() {
+        let mut cmap = CfgMap::new();
+        cmap.add_force("server/port", Int(1)).unwrap();
+        cmap.add_force("default/port", Int(2)).unwrap();
+        cmap.set_default_path("default").unwrap();
+        cmap.lock_path("server/port");
+
+        // The locked option is found, rather than falling back to the default.
+        assert_eq!(cmap.update_option("server", "port", Int(9)), Err(CfgError::Locked("server/port".into())));
+        assert!(cmap.get("server/port").check_that(IsExactlyInt(1)));
+        assert!(cmap.get("default/port").check_that(IsExactlyInt(2)));
+
+        // Locks within nested maps are found as well.
+        cmap.add_force("client/port", Int(3)).unwrap();
+        cmap.get_mut("client").unwrap().as_map_mut().unwrap().lock_path("port");
+        assert_eq!(cmap.update_option("client", "port", Int(9)), Err(CfgError::Locked("client/port".into())));
+        assert_eq!(cmap.update_option("other", "port", Int(9)), Ok(Some(Int(2))));
+    }
+
+    #[test]
+    fn locked_runtime_info() {
+        let mut cmap = CfgMap::new();
+        cmap.attach_runtime_info::<&str>(&[]).unwrap();
+        cmap.lock_path(META_KEY);
+        let original = cmap.get(META_KEY).cloned();
+
+        assert_eq!(cmap.attach_runtime_info::<&str>(&[]), Err(CfgError::Locked(META_KEY.into())));
+        assert_eq!(cmap.get(META_KEY).cloned(), original);
+
+        let mut cmap = CfgMap::new();
+        cmap.pin_type(META_KEY, CfgType::Str).unwrap();
+        assert!(matches!(cmap.attach_runtime_info::<&str>(&[]), Err(CfgError::TypeMismatch { .. })));
+        assert!(matches!(cmap.attach_runtime_info(&["missing.toml"]), Err(CfgError::LoadFailed(_))));
+        assert!(cmap.get(META_KEY).is_none());
+    }
+
+    #[test]
+    fn add_list_append() {
+        let mut cmap = CfgMap::new();
+        cmap.add("list", list![1, 2]).unwrap();
+        cmap.add("sub", Map(CfgMap::new())).unwrap();
+        cmap.add("sub/list", List(vec![])).unwrap();
+
+        assert_eq!(Ok(None), cmap.add("list/-", Int(3)));
+        assert_eq!(Ok(None), cmap.add("sub/list/-", Int(1)));
+
+        assert!(cmap.get("list").check_that(IsExactlyList(vec![Int(1), Int(2), Int(3)])));
+        assert!(cmap.get("sub/list/0").check_that(IsExactlyInt(1)));
+
+        // `-` on a map is just a normal key.
+        assert_eq!(Ok(None), cmap.add("sub/-", Int(4)));
+        assert!(cmap.get("sub/-").check_that(IsExactlyInt(4)));
+
+        // `-` on anything else fails.
+        cmap.add("int", Int(0)).unwrap();
+        assert!(cmap.add("int/-", Int(1)).is_err());
+    }
+
+    #[test]
+    fn clones_share_contents() {
+        use std::sync::Arc;
+
+        let mut cmap = CfgMap::new();
+        cmap.add("a", Map(CfgMap::new())).unwrap();
+        cmap.add("a/x", Int(1)).unwrap();
+        cmap.add("b", Map(CfgMap::new())).unwrap();
+        cmap.add("b/y", Int(2)).unwrap();
+
+        let mut clone = cmap.clone();
+        assert!(Arc::ptr_eq(&cmap.internal_map, &clone.internal_map));
+
+        // Only the maps along the changed path are copied.
+        *clone.get_mut("a/x").unwrap() = Int(10);
+        assert!(cmap.get("a/x").check_that(IsExactlyInt(1)));
+        assert!(clone.get("a/x").check_that(IsExactlyInt(10)));
+        assert!(!Arc::ptr_eq(&cmap.internal_map, &clone.internal_map));
+
+        let shared = |map: &CfgMap, key| map.get(key).and_then(CfgValue::as_map).unwrap().internal_map.clone();
+        assert!(!Arc::ptr_eq(&shared(&cmap, "a"), &shared(&clone, "a")));
+        assert!(Arc::ptr_eq(&shared(&cmap, "b"), &shared(&clone, "b")));
+    }
+
+    #[test]
+    fn fallback_chain() {
+        let mut cmap = CfgMap::new();
+        cmap.default = "user/".into();
+        cmap.fallbacks = vec!["workspace".into(), "system/".into()];
+        cmap.add_force("user/theme", Str("dark".into())).unwrap();
+        cmap.add_force("workspace/theme", Str("light".into())).unwrap();
+        cmap.add_force("workspace/tabs", Int(2)).unwrap();
+        cmap.add_force("system/tabs", Int(8)).unwrap();
+        cmap.add_force("system/font", Str("mono".into())).unwrap();
+        cmap.add_force("editor/font", Str("serif".into())).unwrap();
+
+        assert!(cmap.get_option("editor", "theme").check_that(IsExactlyStr("dark".into())));
+        assert!(cmap.get_option("editor", "tabs").check_that(IsExactlyInt(2)));
+        assert!(cmap.get_option("editor", "font").check_that(IsExactlyStr("serif".into())));
+        assert!(cmap.get_option("other", "font").check_that(IsExactlyStr("mono".into())));
+        assert_eq!(cmap.explain_option("other", "tabs").read_from.as_deref(), Some("workspace/tabs"));
+
+        let options = cmap.options_in("editor");
+        assert!(options.get("theme").check_that(IsExactlyStr("dark".into())));
+        assert!(options.get("tabs").check_that(IsExactlyInt(2)));
+        assert!(options.get("font").check_that(IsExactlyStr("serif".into())));
+
+        assert_eq!(cmap.update_option("other", "tabs", Int(4)), Ok(Some(Int(2))));
+        assert!(cmap.get("workspace/tabs").check_that(IsExactlyInt(4)));
+
+        // Paths are joined the same way when tracking access.
+        cmap.track_access();
+        assert!(cmap.get_option("other", "font").check_that(IsExactlyStr("mono".into())));
+    }
+
+    #[test]
+    fn set_defaults_checks() {
+        let mut cmap = CfgMap::new();
+        cmap.add_force("sec/tls/cert", Str("cert.pem".into())).unwrap();
+        cmap.add_force("server/port", Int(8080)).unwrap();
+        cmap.lock_path("sec/tls");
+        cmap.pin_type("server/host", CfgType::Str).unwrap();
+
+        let mut defaults = CfgMap::new();
+        defaults.add_force("sec/tls/cert", Str("default.pem".into())).unwrap();
+        defaults.add_force("server/port", Int(80)).unwrap();
+        defaults.add_force("server/workers", Int(4)).unwrap();
+
+        // Existing values within a locked path are kept as they are, without any error.
+        assert_eq!(cmap.set_defaults(defaults.clone()), Ok(()));
+        assert!(cmap.get("server/port").check_that(IsExactlyInt(8080)));
+        assert!(cmap.get("server/workers").check_that(IsExactlyInt(4)));
+
+        let original = cmap.clone();
+        let mut locked = defaults.clone();
+        locked.add_force("sec/tls/level", Int(1)).unwrap();
+        assert_eq!(cmap.set_defaults(locked), Err(CfgError::Locked("sec/tls/level".into())));
+
+        let mut pinned = defaults;
+        pinned.add_force("server/host", Int(1)).unwrap();
+        assert!(matches!(cmap.set_defaults(pinned), Err(CfgError::TypeMismatch { .. })));
+        assert_eq!(cmap, original);
+        assert!(cmap.get("sec/tls/level").is_none());
+    }
+
+    #[test]
+    fn separate_defaults() {
+        let mut cmap = CfgMap::new();
+        cmap.default = "default/".into();
+        cmap.add_default("tabs", Int(8)).unwrap();
+        cmap.add_default("theme", Str("light".into())).unwrap();
+        cmap.add_force("default/theme", Str("dark".into())).unwrap();
+        cmap.add_force("editor/font", Str("mono".into())).unwrap();
+
+        assert!(cmap.get_option("editor", "tabs").check_that(IsExactlyInt(8)));
+        assert!(cmap.get_option("editor", "theme").check_that(IsExactlyStr("dark".into())));
+        assert!(cmap.get_option("", "tabs").check_that(IsExactlyInt(8)));
+        assert_eq!(cmap.keys().count(), 2);
+
+        let options = cmap.options_in("editor");
+        assert_eq!(options.len(), 3);
+        assert!(options.get("theme").check_that(IsExactlyStr("dark".into())));
+
+        assert_eq!(cmap.update_option("editor", "tabs", Int(4)), Ok(Some(Int(8))));
+        assert!(cmap.get_default("tabs").check_that(IsExactlyInt(4)));
+        *cmap.get_default_mut("tabs").unwrap() = Int(2);
+        assert!(cmap.get_option("editor", "tabs").check_that(IsExactlyInt(2)));
+        assert!(CfgMap::new().get_default("tabs").is_none());
+    }
+
+    #[test]
+    fn check_path_errors() {
+        use crate::CheckError;
+
+        let mut cmap = CfgMap::new();
+        cmap.add("db", Map(CfgMap::new())).unwrap();
+        cmap.add("db/port", Str("5432".into())).unwrap();
+
+        let error = cmap.check_path("db/port", &(IsInt | IsFloat)).unwrap_err();
+        assert_eq!(error.path(), "db/port");
+        assert_eq!(error.to_string(), "`db/port` doesn't satisfy `IsInt | IsFloat`");
+        assert_eq!(cmap.check_path("db/host", &IsStr).unwrap_err().to_string(), "`db/host` is missing");
+        assert!(matches!(cmap.check_path("db/port/x", &IsStr), Err(CheckError::Missing { .. })));
+        assert_eq!(cmap.check_path("db", &HasKey("port".into())), Ok(()));
+
+        let report = cmap.validate_all(&[("db", IsMap), ("db/port", IsInt), ("db/port", IsParsableAsInt), ("db/user", IsStr)]);
+        assert_eq!(report.failures.iter().map(CheckError::path).collect::<Vec<_>>(), vec!["db/port", "db/user"]);
+        assert!(report.into_result().is_err());
+        assert_eq!(cmap.validate_all(&[]).into_result(), Ok(()));
+    }
+
+    #[test]
+    fn get_option_paths() {
+        let mut cmap = CfgMap::new();
+        cmap.add("defaults", Map(CfgMap::new())).unwrap();
+        cmap.add("defaults/db", Map(CfgMap::new())).unwrap();
+        cmap.add("defaults/db/port", Int(5432)).unwrap();
+        cmap.add("services", List(vec![Map(CfgMap::new())])).unwrap();
+        cmap.add("services/0/db", Map(CfgMap::new())).unwrap();
+        cmap.add("services/0/db/host", Str("db.local".into())).unwrap();
+
+        // Without a default, the root is used instead.
+        assert!(cmap.get_option("services/0", "defaults/db/port").check_that(IsExactlyInt(5432)));
+
+        cmap.default = "defaults/".into();
+        assert!(cmap.get_option("services/0", "db/host").check_that(IsExactlyStr("db.local".into())));
+        assert!(cmap.get_option("services/0", "db/port").check_that(IsExactlyInt(5432)));
+        assert!(cmap.get_option("services/1", "db/port").check_that(IsExactlyInt(5432)));
+        assert!(cmap.get_option("services/0", "db/").is_none());
+
+        // Tracking access gives the same results.
+        cmap.track_access();
+        assert!(cmap.get_option("services/0", "db/port").check_that(IsExactlyInt(5432)));
+        assert!(cmap.unused_keys().iter().all(|key| key != "defaults/db/port"));
+
+        // The default path is joined the same way with or without a trailing `/`.
+        cmap.default = "defaults".into();
+        assert!(cmap.get_option("services/1", "db/port").check_that(IsExactlyInt(5432)));
+        assert_eq!(cmap.explain_option("services/1", "db/port").read_from.as_deref(), Some("defaults/db/port"));
+        assert_eq!(cmap.set_default_path("services/0/db/host"), Err(CfgError::InvalidPath("services/0/db/host".into())));
+        assert_eq!(cmap.set_default_path("services/0/"), Ok(()));
+        assert_eq!(cmap.default, "services/0");
+    }
+
     #[test]
     #[cfg(feature = "from_json")]
     fn from_json_test() {
@@ -1036,6 +4300,20 @@ mod tests {
         assert!(cmap.get("array").check_that(IsListWith(Box::new(IsInt)) & IsListWithLength(2)));
     }
 
+    #[test]
+    #[cfg(feature = "from_json")]
+    fn try_from_json_test() {
+        use std::convert::TryFrom;
+
+        let cmap = CfgMap::try_from(serde_json::json!({ "a": [1, { "b": null }] })).unwrap();
+        assert!(cmap.get("a/1/b").check_that(IsNull));
+
+        assert_eq!(CfgMap::try_from(serde_json::json!([1, 2])), Err(CfgError::NotAMap));
+        let big = CfgMap::try_from(serde_json::json!({ "big": u64::MAX, "small": 5u64 })).unwrap();
+        assert!(big.get("big").check_that(IsExactlyUInt(u64::MAX)));
+        assert!(big.get("small").check_that(IsExactlyInt(5)));
+    }
+
     #[test]
     #[cfg(feature = "from_toml")]
     fn from_toml_test() {
@@ -0,0 +1,129 @@
+use std::fmt;
+
+/// Why a value doesn't satisfy a condition, as returned by `CfgMap::check_path`.
+///
+/// The value itself isn't included, so that checking a secret value doesn't reveal it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CheckError {
+    /// There's no value at `path`.
+    Missing { path: String },
+
+    /// The value at `path` doesn't satisfy `condition`, which is displayed the same way it's written.
+    /// `violation` is the full path of the offending value, which is `path` itself unless the condition
+    /// checks the values within it, such as `IsListWith`.
+    Failed { path: String, condition: String, violation: String },
+}
+
+impl CheckError {
+    /// The path that was checked.
+    pub fn path(&self) -> &str {
+        match self {
+            CheckError::Missing { path } | CheckError::Failed { path, .. } => path,
+        }
+    }
+}
+
+impl fmt::Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckError::Missing { path } => write!(f, "`{}` is missing", path),
+            CheckError::Failed { path, condition, violation } if path == violation => {
+                write!(f, "`{}` doesn't satisfy `{}`", path, condition)
+            },
+            CheckError::Failed { path, condition, violation } => {
+                write!(f, "`{}` doesn't satisfy `{}`, because of `{}`", path, condition, violation)
+            },
+        }
+    }
+}
+
+impl std::error::Error for CheckError {}
+
+/// How serious it is for a rule to fail. Only errors make a `ValidationReport` invalid, while warnings and
+/// infos are still reported, such as for deprecated values or suspicious combinations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum Severity {
+    Info,
+    Warning,
+    #[default]
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// Every failure found by `CfgMap::validate_all` or `Schema::validate`, grouped by severity, in the order of
+/// the rules that failed.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ValidationReport {
+    /// Why each failing rule with `Severity::Error` failed.
+    pub failures: Vec<CheckError>,
+
+    /// Why each failing rule with `Severity::Warning` failed.
+    pub warnings: Vec<CheckError>,
+
+    /// Why each failing rule with `Severity::Info` failed.
+    pub infos: Vec<CheckError>,
+
+    /// The paths that were repaired by `Schema::validate_and_fix`, in the order of their rules.
+    pub fixed: Vec<String>,
+}
+
+impl ValidationReport {
+    /// Adds the failure of a rule with the given `severity`.
+    pub fn push(&mut self, severity: Severity, failure: CheckError) {
+        match severity {
+            Severity::Error => self.failures.push(failure),
+            Severity::Warning => self.warnings.push(failure),
+            Severity::Info => self.infos.push(failure),
+        }
+    }
+
+    /// Gets every failure along with its severity, starting with errors, then warnings, then infos.
+    pub fn entries(&self) -> impl Iterator<Item = (Severity, &CheckError)> {
+        let failures = self.failures.iter().map(|failure| (Severity::Error, failure));
+        let warnings = self.warnings.iter().map(|failure| (Severity::Warning, failure));
+        let infos = self.infos.iter().map(|failure| (Severity::Info, failure));
+        failures.chain(warnings).chain(infos)
+    }
+
+    /// Whether every rule with `Severity::Error` was satisfied. Warnings and infos don't make a report invalid.
+    pub fn is_valid(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Converts the report into a result, which is an error if any rule with `Severity::Error` failed.
+    pub fn into_result(self) -> Result<(), ValidationReport> {
+        if self.is_valid() { Ok(()) } else { Err(self) }
+    }
+}
+
+/// Displays every failure on its own line, where warnings and infos are prefixed with their severity,
+/// followed by every fixed path.
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (severity, failure)) in self.entries().enumerate() {
+            if i > 0 { f.write_str("\n")?; }
+            match severity {
+                Severity::Error => write!(f, "{}", failure)?,
+                severity => write!(f, "{}: {}", severity, failure)?,
+            }
+        }
+        for (i, path) in self.fixed.iter().enumerate() {
+            if i > 0 || self.entries().next().is_some() {
+                f.write_str("\n")?;
+            }
+            write!(f, "fixed `{}`", path)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationReport {}
@@ -0,0 +1,129 @@
+use super::{CfgError, CfgHashMap, CfgMap, CfgType, CfgValue, PatchError};
+use std::ops::Deref;
+
+/// A `CfgMap` that can only be changed through methods that check its paths, locks and pinned types,
+/// created by `CfgMap::checked`.
+///
+/// The map can be read like a normal `CfgMap`, but it doesn't deref mutably or hand out mutable references to its
+/// values, so neither its internal map nor those of nested maps can be changed by accident. `raw_map_mut` is the only way to change the internal map directly, which makes it
+/// easy to find every place that bypasses these checks.
+///
+/// ## Examples
+/// ```
+/// use cfgmap::{CfgMap, CfgType, CfgValue::*, Condition::*, Checkable};
+///
+/// let mut cmap = CfgMap::new().checked();
+/// cmap.add("port", Int(80)).unwrap();
+/// cmap.pin_type("port", CfgType::Int).unwrap();
+///
+/// assert!(cmap.add("port", Str("80".into())).is_err());
+/// assert!(cmap.get("port").check_that(IsExactlyInt(80)));
+/// ```
+///
+/// Changing the internal map through `Deref` doesn't compile:
+/// ```compile_fail
+/// use cfgmap::{CfgMap, CfgValue::*};
+///
+/// let mut cmap = CfgMap::new().checked();
+/// cmap.insert("port".into(), Int(80));
+/// ```
+///
+/// Neither does getting a mutable reference to a value, which could replace a pinned value with one of
+/// another type:
+/// ```compile_fail
+/// use cfgmap::{CfgMap, CfgType, CfgValue::*};
+///
+/// let mut cmap = CfgMap::new().checked();
+/// cmap.add("port", Int(80)).unwrap();
+/// cmap.pin_type("port", CfgType::Int).unwrap();
+/// *cmap.get_mut("port").unwrap() = Str("80".into());
+/// ```
+///
+/// Or change the internal map of a nested map:
+/// ```compile_fail
+/// use cfgmap::{CfgMap, CfgType, CfgValue::*};
+///
+/// let mut cmap = CfgMap::new().checked();
+/// cmap.add_force("db/port", Int(5432)).unwrap();
+/// cmap.pin_type("db/port", CfgType::Int).unwrap();
+/// cmap.get_mut("db").unwrap().as_map_mut().unwrap().insert("port".into(), Bool(true));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CheckedCfgMap {
+    map: CfgMap,
+}
+
+impl CheckedCfgMap {
+    pub(crate) fn new(map: CfgMap) -> CheckedCfgMap {
+        CheckedCfgMap { map }
+    }
+
+    /// Gets the map back, which can be changed directly again.
+    pub fn into_inner(self) -> CfgMap {
+        self.map
+    }
+
+    /// Gets the internal map for changing it directly, the same as `CfgMap::raw_map_mut`.
+    pub fn raw_map_mut(&mut self) -> &mut CfgHashMap {
+        self.map.raw_map_mut()
+    }
+
+    /// Adds a value at `key`, the same as `CfgMap::add`.
+    pub fn add(&mut self, key: &str, value: CfgValue) -> Result<Option<CfgValue>, CfgError> {
+        self.map.add(key, value)
+    }
+
+    /// Adds a value at `key`, creating any missing maps, the same as `CfgMap::add_force`.
+    pub fn add_force(&mut self, key: &str, value: CfgValue) -> Result<Option<CfgValue>, CfgError> {
+        self.map.add_force(key, value)
+    }
+
+    /// Updates an existing option, the same as `CfgMap::update_option`.
+    pub fn update_option(&mut self, category: &str, option: &str, to: CfgValue) -> Result<Option<CfgValue>, CfgError> {
+        self.map.update_option(category, option, to)
+    }
+
+    /// Removes the value at `key`, the same as `CfgMap::remove`.
+    pub fn remove(&mut self, key: &str) -> Option<CfgValue> {
+        self.map.remove(key)
+    }
+
+    /// Moves a value to another path, the same as `CfgMap::move_path`.
+    pub fn move_path(&mut self, from: &str, to: &str) -> Result<(), CfgError> {
+        self.map.move_path(from, to)
+    }
+
+    /// Copies a value to another path, the same as `CfgMap::copy_path`.
+    pub fn copy_path(&mut self, from: &str, to: &str) -> Result<(), CfgError> {
+        self.map.copy_path(from, to)
+    }
+
+    /// Applies a JSON Patch, the same as `CfgMap::apply_patch`.
+    pub fn apply_patch(&mut self, patch: &CfgValue) -> Result<(), PatchError> {
+        self.map.apply_patch(patch)
+    }
+
+    /// Pins the type of a path, the same as `CfgMap::pin_type`.
+    pub fn pin_type(&mut self, path: &str, expected: CfgType) -> Result<(), CfgError> {
+        self.map.pin_type(path, expected)
+    }
+
+    /// Locks a path, the same as `CfgMap::lock_path`.
+    pub fn lock_path(&mut self, path: &str) {
+        self.map.lock_path(path)
+    }
+}
+
+impl Deref for CheckedCfgMap {
+    type Target = CfgMap;
+
+    fn deref(&self) -> &CfgMap {
+        &self.map
+    }
+}
+
+impl From<CfgMap> for CheckedCfgMap {
+    fn from(map: CfgMap) -> CheckedCfgMap {
+        CheckedCfgMap::new(map)
+    }
+}
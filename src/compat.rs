@@ -0,0 +1,79 @@
+use super::Condition::{self, *};
+use std::fmt;
+
+/// A change between two versions of a schema that can break configurations or their consumers,
+/// as returned by `Schema::is_compatible_with`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Incompatibility {
+    /// The old schema had a rule at `path`, but the new one doesn't, so it's no longer guaranteed to be present.
+    Removed { path: String },
+
+    /// The new schema requires `path`, which existing configurations might not have.
+    Added { path: String },
+
+    /// The rule at `path` might reject values the old rule accepted, such as by narrowing its type or range.
+    /// Both conditions are displayed the same way they're written.
+    Narrowed { path: String, from: String, to: String },
+}
+
+impl Incompatibility {
+    /// The path whose rule changed.
+    pub fn path(&self) -> &str {
+        match self {
+            Incompatibility::Removed { path } | Incompatibility::Added { path } | Incompatibility::Narrowed { path, .. } => path,
+        }
+    }
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Incompatibility::Removed { path } => write!(f, "`{}` was removed", path),
+            Incompatibility::Added { path } => write!(f, "`{}` is now required", path),
+            Incompatibility::Narrowed { path, from, to } => write!(f, "`{}` was narrowed from `{}` to `{}`", path, from, to),
+        }
+    }
+}
+
+impl std::error::Error for Incompatibility {}
+
+/// Gets the type check that `condition` implies, if it only accepts values of a single type.
+fn implied_type(condition: &Condition) -> Option<Condition> {
+    Some(match condition {
+        IsInt | IsExactlyInt(_) | IsIntInRange(..) => IsInt,
+        IsUInt | IsExactlyUInt(_) => IsUInt,
+        IsFloat | IsExactlyFloat(_) | IsFloatInRange(..) => IsFloat,
+        IsBool | IsExactlyBool(_) | IsTrue => IsBool,
+        IsList | IsExactlyList(_) | IsListWith(_) | IsListWithLength(_) | IsListWithAny(_) | IsListWithUniqueElements
+            | IsSortedList | IsListWithLengthAtLeast(_) | IsNonEmptyList => IsList,
+        IsMap | IsExactlyMap(_) | IsMapWith(_) | IsMapWithKeyValue(..) | HasKey(_) | HasKeys(_) | HasOnlyKeys(_) => IsMap,
+        IsStr | IsExactlyStr(_) | IsOneOfStr(_) | IsStrStartingWith(_) | IsStrEndingWith(_) | IsStrContaining(_)
+            | IsStrWithLength(_) | IsNonEmptyStr | IsDurationStr | IsByteSizeStr | IsParsableAsInt | IsParsableAsFloat
+            | IsParsableAsBool => IsStr,
+        _ => return None,
+    })
+}
+
+/// Checks whether every value accepted by `old` is provably accepted by `new`. Conditions that can't be
+/// compared, other than by being written the same way, are assumed to be narrower.
+pub(crate) fn accepts_all(new: &Condition, old: &Condition) -> bool {
+    match (new, old) {
+        (TRUE, _) | (_, FALSE) => true,
+        (_, Or(a, b)) => accepts_all(new, a) && accepts_all(new, b),
+        (And(a, b), _) => accepts_all(a, old) && accepts_all(b, old),
+        (Or(a, b), _) if accepts_all(a, old) || accepts_all(b, old) => true,
+        (_, And(a, b)) if accepts_all(new, a) || accepts_all(new, b) => true,
+
+        (IsIntInRange(min, max), IsIntInRange(old_min, old_max)) => min <= old_min && old_max <= max,
+        (IsIntInRange(min, max), IsExactlyInt(i)) => min <= i && i <= max,
+        (IsFloatInRange(min, max), IsFloatInRange(old_min, old_max)) => min <= old_min && old_max <= max,
+        (IsFloatInRange(min, max), IsExactlyFloat(f)) => min <= f && f <= max,
+        (IsOneOfStr(new), IsOneOfStr(old)) => old.iter().all(|s| new.contains(s)),
+        (IsOneOfStr(new), IsExactlyStr(s)) => new.contains(s),
+        (IsBool, _) | (IsInt, _) | (IsUInt, _) | (IsFloat, _) | (IsStr, _) | (IsList, _) | (IsMap, _)
+            if implied_type(old).is_some_and(|implied| implied.to_string() == new.to_string()) => true,
+
+        // Schemas are parsed from text, so conditions written the same way are the same.
+        _ => new.to_string() == old.to_string(),
+    }
+}
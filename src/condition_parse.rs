@@ -0,0 +1,465 @@
+use super::Condition;
+use std::fmt;
+
+/// The maximum number of parentheses, negations and conditions such as `list_with` that can be nested within each other.
+const MAX_DEPTH: usize = 64;
+
+/// An error produced when a condition can't be parsed using `Condition::parse`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConditionParseError {
+    /// The byte offset within the parsed text where the error was found.
+    pub position: usize,
+
+    /// What was wrong at that position.
+    pub message: String,
+}
+
+impl fmt::Display for ConditionParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid condition at {}: {}", self.position, self.message)
+    }
+}
+
+impl std::error::Error for ConditionParseError {}
+
+/// Parses conditions, where `depth` is how deeply the condition being parsed is nested.
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, ConditionParseError> {
+        Err(ConditionParseError { position: self.position, message: message.into() })
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    /// Skips over `token` if it's next, returning whether it was.
+    fn eat(&mut self, token: char) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.position += token.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: char) -> Result<(), ConditionParseError> {
+        if self.eat(token) { Ok(()) } else { self.error(format!("expected `{}`", token)) }
+    }
+
+    /// Takes the longest prefix of the rest of the text whose characters satisfy `valid`.
+    fn take_while(&mut self, valid: impl Fn(char) -> bool) -> &'a str {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest.find(|c: char| !valid(c)).unwrap_or(rest.len());
+        self.position += len;
+        &rest[..len]
+    }
+
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T, ConditionParseError>) -> Result<T, ConditionParseError> {
+        if self.depth >= MAX_DEPTH {
+            return self.error("the condition is nested too deeply");
+        }
+        self.depth += 1;
+        let parsed = parse(self);
+        self.depth -= 1;
+        parsed
+    }
+
+    /// Parses conditions separated by `|`, which binds less tightly than `&`.
+    fn or(&mut self) -> Result<Condition, ConditionParseError> {
+        let mut condition = self.and()?;
+        while self.eat('|') {
+            condition = condition.or(self.and()?);
+        }
+        Ok(condition)
+    }
+
+    fn and(&mut self) -> Result<Condition, ConditionParseError> {
+        let mut condition = self.not()?;
+        while self.eat('&') {
+            condition = condition.and(self.not()?);
+        }
+        Ok(condition)
+    }
+
+    fn not(&mut self) -> Result<Condition, ConditionParseError> {
+        if self.eat('!') {
+            self.nested(|p| p.not()).map(Condition::not)
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<Condition, ConditionParseError> {
+        if self.eat('(') {
+            let condition = self.nested(|p| p.or())?;
+            self.expect(')')?;
+            return Ok(condition);
+        }
+
+        let start = self.position;
+        let name = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+
+        Ok(match name {
+            "int" if self.keyword("in") => {
+                let (min, max) = self.range(Parser::integer)?;
+                Condition::IsIntInRange(min, max)
+            },
+            "float" if self.keyword("in") => {
+                let (min, max) = self.range(Parser::float)?;
+                Condition::IsFloatInRange(min, max)
+            },
+            "int" | "IsInt" => Condition::IsInt,
+            "uint" | "IsUInt" => Condition::IsUInt,
+            "float" | "IsFloat" => Condition::IsFloat,
+            "str" | "IsStr" => Condition::IsStr,
+            "list" | "IsList" => Condition::IsList,
+            "map" | "IsMap" => Condition::IsMap,
+            "bool" | "IsBool" => Condition::IsBool,
+            "opaque" | "IsOpaque" => Condition::IsOpaque,
+            "is_true" | "IsTrue" => Condition::IsTrue,
+            "always" | "TRUE" => Condition::TRUE,
+            "never" | "FALSE" => Condition::FALSE,
+            #[cfg(feature = "from_json")]
+            "null" | "IsNull" => Condition::IsNull,
+            #[cfg(feature = "from_toml")]
+            "datetime" | "IsDatetime" => Condition::IsDatetime,
+            "IsIntInRange" => {
+                let (min, max) = self.argument(|p| p.bounds(Parser::integer))?;
+                Condition::IsIntInRange(min, max)
+            },
+            "IsFloatInRange" => {
+                let (min, max) = self.argument(|p| p.bounds(Parser::float))?;
+                Condition::IsFloatInRange(min, max)
+            },
+            "exactly_int" | "IsExactlyInt" => Condition::IsExactlyInt(self.argument(Parser::integer)?),
+            "exactly_uint" | "IsExactlyUInt" => Condition::IsExactlyUInt(self.argument(Parser::unsigned)?),
+            "exactly_float" | "IsExactlyFloat" => Condition::IsExactlyFloat(self.argument(Parser::float)?),
+            "exactly_str" | "IsExactlyStr" => Condition::IsExactlyStr(self.argument(Parser::string)?),
+            "exactly_bool" | "IsExactlyBool" => Condition::IsExactlyBool(self.argument(Parser::boolean)?),
+            #[cfg(feature = "from_toml")]
+            "exactly_datetime" | "IsExactlyDatetime" => Condition::IsExactlyDatetime(self.argument(Parser::datetime)?),
+            #[cfg(feature = "net-validate")]
+            "url" | "IsUrl" => Condition::IsUrl,
+            #[cfg(feature = "net-validate")]
+            "email" | "IsEmail" => Condition::IsEmail,
+            #[cfg(feature = "net-validate")]
+            "ip_addr" | "IsIpAddr" => Condition::IsIpAddr,
+            #[cfg(feature = "net-validate")]
+            "port" | "IsPort" => Condition::IsPort,
+            #[cfg(feature = "net-validate")]
+            "cidr" | "IsCidr" => Condition::IsCidr,
+            #[cfg(feature = "regex")]
+            "matches" | "IsStrMatching" => Condition::IsStrMatching(self.argument(Parser::regex)?),
+            "starts_with" | "IsStrStartingWith" => Condition::IsStrStartingWith(self.argument(Parser::string)?),
+            "ends_with" | "IsStrEndingWith" => Condition::IsStrEndingWith(self.argument(Parser::string)?),
+            "contains" | "IsStrContaining" => Condition::IsStrContaining(self.argument(Parser::string)?),
+            "str_len" | "IsStrWithLength" => Condition::IsStrWithLength(self.argument(Parser::length)?),
+            "non_empty_str" | "IsNonEmptyStr" => Condition::IsNonEmptyStr,
+            "duration_str" | "IsDurationStr" => Condition::IsDurationStr,
+            "byte_size_str" | "IsByteSizeStr" => Condition::IsByteSizeStr,
+            "existing_path" | "IsExistingPath" => Condition::IsExistingPath,
+            "readable_file" | "IsReadableFile" => Condition::IsReadableFile,
+            "directory" | "IsDirectory" => Condition::IsDirectory,
+            "parsable_int" | "IsParsableAsInt" => Condition::IsParsableAsInt,
+            "parsable_float" | "IsParsableAsFloat" => Condition::IsParsableAsFloat,
+            "parsable_bool" | "IsParsableAsBool" => Condition::IsParsableAsBool,
+            "one_of" => Condition::IsOneOfStr(self.argument(|p| p.strings(')'))?),
+            "IsOneOfStr" => Condition::IsOneOfStr(self.argument(Parser::string_list)?),
+            "xor" | "Xor" => {
+                let (x, y) = self.nested(|p| p.argument(Parser::pair))?;
+                x.xor(y)
+            },
+            "implies" | "Implies" => {
+                let (x, y) = self.nested(|p| p.argument(Parser::pair))?;
+                x.implies(y)
+            },
+            "at_least" => {
+                let (n, conditions) = self.nested(|p| p.argument(|p| {
+                    let n = p.length()?;
+                    let conditions = if p.eat(',') { p.conditions(')')? } else { Vec::new() };
+                    Ok((n, conditions))
+                }))?;
+                Condition::AtLeastN(n, conditions)
+            },
+            "AtLeastN" => {
+                let (n, conditions) = self.nested(|p| p.argument(|p| {
+                    let n = p.length()?;
+                    p.expect(',')?;
+                    p.expect('[')?;
+                    let conditions = p.conditions(']')?;
+                    p.expect(']')?;
+                    Ok((n, conditions))
+                }))?;
+                Condition::AtLeastN(n, conditions)
+            },
+            "list_with_any" | "IsListWithAny" => {
+                Condition::IsListWithAny(Box::new(self.nested(|p| p.argument(Parser::or))?))
+            },
+            "unique_list" | "IsListWithUniqueElements" => Condition::IsListWithUniqueElements,
+            "sorted_list" | "IsSortedList" => Condition::IsSortedList,
+            "list_len_at_least" | "IsListWithLengthAtLeast" => Condition::IsListWithLengthAtLeast(self.argument(Parser::length)?),
+            "non_empty_list" | "IsNonEmptyList" => Condition::IsNonEmptyList,
+            "map_with" | "IsMapWith" => {
+                Condition::IsMapWith(Box::new(self.nested(|p| p.argument(Parser::or))?))
+            },
+            "map_with_key" | "IsMapWithKeyValue" => {
+                let (key, condition) = self.nested(|p| p.argument(|p| {
+                    let key = p.string()?;
+                    p.expect(',')?;
+                    Ok((key, p.or()?))
+                }))?;
+                Condition::IsMapWithKeyValue(key, Box::new(condition))
+            },
+            "has_key" | "HasKey" => Condition::HasKey(self.argument(Parser::string)?),
+            "has_keys" => Condition::HasKeys(self.argument(|p| p.strings(')'))?),
+            "HasKeys" => Condition::HasKeys(self.argument(Parser::string_list)?),
+            "has_only_keys" => Condition::HasOnlyKeys(self.argument(|p| p.strings(')'))?),
+            "HasOnlyKeys" => Condition::HasOnlyKeys(self.argument(Parser::string_list)?),
+            "list_len" | "IsListWithLength" => Condition::IsListWithLength(self.argument(Parser::length)?),
+            "list_with" | "IsListWith" => {
+                Condition::IsListWith(Box::new(self.nested(|p| p.argument(Parser::or))?))
+            },
+            "" => return self.error("expected a condition"),
+            name => {
+                self.position = start;
+                return self.error(format!("unknown condition `{}`", name));
+            },
+        })
+    }
+
+    /// Skips over `keyword` if it's the next word, returning whether it was.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let is_keyword = rest.starts_with(keyword)
+            && !rest[keyword.len()..].starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_');
+
+        if is_keyword {
+            self.position += keyword.len();
+        }
+        is_keyword
+    }
+
+    /// Parses a range such as `1..10`, where both ends are included, using `parse` for each end.
+    /// `..=` is accepted too, and means the same.
+    fn range<T>(&mut self, parse: fn(&mut Self) -> Result<T, ConditionParseError>) -> Result<(T, T), ConditionParseError> {
+        let min = self.bound(parse)?;
+        self.skip_whitespace();
+        if !self.rest().starts_with("..") {
+            return self.error("expected `..`");
+        }
+        self.position += 2;
+        self.eat('=');
+        Ok((min, self.bound(parse)?))
+    }
+
+    /// Parses two ends of a range separated by a comma, using `parse` for each end.
+    fn bounds<T>(&mut self, parse: fn(&mut Self) -> Result<T, ConditionParseError>) -> Result<(T, T), ConditionParseError> {
+        let min = parse(self)?;
+        self.expect(',')?;
+        Ok((min, parse(self)?))
+    }
+
+    /// Parses one end of a range using `parse`, which stops before any `..` that follows it.
+    fn bound<T>(&mut self, parse: fn(&mut Self) -> Result<T, ConditionParseError>) -> Result<T, ConditionParseError> {
+        self.skip_whitespace();
+        let end = self.rest().find("..").map_or(self.text.len(), |i| self.position + i);
+        let text = self.text;
+
+        // The rest of the text is hidden from `parse`, so that the number doesn't include the dots.
+        self.text = &text[..end];
+        let parsed = parse(self);
+        self.text = text;
+        parsed
+    }
+
+    /// Parses an argument within parentheses using `parse`.
+    fn argument<T>(&mut self, parse: fn(&mut Self) -> Result<T, ConditionParseError>) -> Result<T, ConditionParseError> {
+        self.expect('(')?;
+        let argument = parse(self)?;
+        self.expect(')')?;
+        Ok(argument)
+    }
+
+    /// Parses two conditions separated by a comma.
+    fn pair(&mut self) -> Result<(Condition, Condition), ConditionParseError> {
+        let first = self.or()?;
+        self.expect(',')?;
+        Ok((first, self.or()?))
+    }
+
+    /// Parses conditions separated by commas, until reaching `close`.
+    fn conditions(&mut self, close: char) -> Result<Vec<Condition>, ConditionParseError> {
+        let mut conditions = Vec::new();
+        self.skip_whitespace();
+        while !self.rest().starts_with(close) {
+            conditions.push(self.or()?);
+            if !self.eat(',') {
+                break;
+            }
+            self.skip_whitespace();
+        }
+        Ok(conditions)
+    }
+
+    fn number(&mut self) -> &'a str {
+        self.take_while(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+    }
+
+    fn integer(&mut self) -> Result<i64, ConditionParseError> {
+        let start = self.position;
+        let number = self.number();
+        number.parse().or_else(|_| { self.position = start; self.error(format!("`{}` isn't an integer", number)) })
+    }
+
+    fn unsigned(&mut self) -> Result<u64, ConditionParseError> {
+        let start = self.position;
+        let number = self.number();
+        number.parse().or_else(|_| { self.position = start; self.error(format!("`{}` isn't an unsigned integer", number)) })
+    }
+
+    fn float(&mut self) -> Result<f64, ConditionParseError> {
+        let start = self.position;
+        let number = self.number();
+        number.parse().or_else(|_| { self.position = start; self.error(format!("`{}` isn't a float", number)) })
+    }
+
+    fn length(&mut self) -> Result<usize, ConditionParseError> {
+        let start = self.position;
+        let number = self.number();
+        number.parse().or_else(|_| { self.position = start; self.error(format!("`{}` isn't a length", number)) })
+    }
+
+    fn boolean(&mut self) -> Result<bool, ConditionParseError> {
+        match self.take_while(|c| c.is_ascii_alphabetic()) {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => self.error("expected `true` or `false`"),
+        }
+    }
+
+    /// Parses a string within double quotes, where `\"`, `\\`, `\n`, `\t` and `\r` are escaped.
+    fn string(&mut self) -> Result<String, ConditionParseError> {
+        self.expect('"')?;
+
+        let mut string = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((i, c)) = chars.next() {
+            let unescaped = match c {
+                '"' => {
+                    self.position += i + 1;
+                    return Ok(string);
+                },
+                '\\' => match chars.next() {
+                    Some((_, '"')) => '"',
+                    Some((_, '\\')) => '\\',
+                    Some((_, 'n')) => '\n',
+                    Some((_, 't')) => '\t',
+                    Some((_, 'r')) => '\r',
+                    _ => {
+                        self.position += i;
+                        return self.error("invalid escape");
+                    },
+                },
+                c => c,
+            };
+            string.push(unescaped);
+        }
+
+        self.position = self.text.len();
+        self.error("the string is never closed")
+    }
+
+    /// Parses strings separated by commas, until reaching `close`.
+    fn strings(&mut self, close: char) -> Result<Vec<String>, ConditionParseError> {
+        let mut strings = Vec::new();
+        self.skip_whitespace();
+        while !self.rest().starts_with(close) {
+            strings.push(self.string()?);
+            if !self.eat(',') {
+                break;
+            }
+            self.skip_whitespace();
+        }
+        Ok(strings)
+    }
+
+    /// Parses strings separated by commas within square brackets.
+    fn string_list(&mut self) -> Result<Vec<String>, ConditionParseError> {
+        self.expect('[')?;
+        let strings = self.strings(']')?;
+        self.expect(']')?;
+        Ok(strings)
+    }
+
+    #[cfg(feature = "regex")]
+    fn regex(&mut self) -> Result<regex::Regex, ConditionParseError> {
+        let start = self.position;
+        let pattern = self.string()?;
+        regex::Regex::new(&pattern).or_else(|e| { self.position = start; self.error(e.to_string()) })
+    }
+
+    #[cfg(feature = "from_toml")]
+    fn datetime(&mut self) -> Result<toml::value::Datetime, ConditionParseError> {
+        let start = self.position;
+        let string = self.string()?;
+        string.parse().or_else(|_| { self.position = start; self.error(format!("`{}` isn't a datetime", string)) })
+    }
+}
+
+/// Parses `text` into a condition, as described by `Condition::parse`.
+pub(crate) fn parse(text: &str) -> Result<Condition, ConditionParseError> {
+    let mut parser = Parser { text, position: 0, depth: 0 };
+    let condition = parser.or()?;
+
+    parser.skip_whitespace();
+    if parser.position < text.len() {
+        return parser.error("expected `&`, `|` or the end of the condition");
+    }
+    Ok(condition)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CfgValue::*, Checkable, Condition, ConditionParseError};
+
+    #[test]
+    fn precedence_and_arguments() {
+        let condition = Condition::parse(r#"!str & list_with(int | exactly_str("a \"b\"")) | exactly_bool(true)"#).unwrap();
+        assert_eq!(condition.to_string(), r#"!IsStr & IsListWith(IsInt | IsExactlyStr("a \"b\"")) | IsExactlyBool(true)"#);
+
+        assert!(List(vec![Int(1), Str("a \"b\"".into())]).check_that(condition.clone()));
+        assert!(Bool(true).check_that(condition.clone()));
+        assert!(!List(vec![Float(1.0)]).check_that(condition));
+
+        // The displayed form of a condition parses back into the same condition.
+        let condition = Condition::parse("(IsInt | IsExactlyFloat(-2.5)) & !IsListWithLength(3)").unwrap();
+        assert_eq!(Condition::parse(&condition.to_string()).unwrap().to_string(), condition.to_string());
+    }
+
+    #[test]
+    fn errors() {
+        let error = |text: &str| Condition::parse(text).unwrap_err();
+
+        assert_eq!(error("int & nope"), ConditionParseError { position: 6, message: "unknown condition `nope`".into() });
+        assert_eq!(error("exactly_int(1.5)").position, 12);
+        assert_eq!(error("(int | float").message, "expected `)`");
+        assert_eq!(error("int float").position, 4);
+        assert_eq!(error("exactly_str(\"open").message, "the string is never closed");
+        assert_eq!(error("").message, "expected a condition");
+        assert_eq!(error(&"(".repeat(100)).message, "the condition is nested too deeply");
+    }
+}
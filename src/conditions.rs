@@ -1,4 +1,10 @@
-use std::ops::{BitAnd, BitOr, Not};
+use std::fmt;
+use std::fs::File;
+use std::ops::{BitAnd, BitOr, BitXor, Not};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 /// Trait for the `check_that` function, that allows it to run a condition on a struct.
 pub trait Checkable {
@@ -45,15 +51,21 @@ pub trait Checkable {
 /// 
 /// These exist for all `CfgValue`s. There also exist other miscellaneous conditions, such as
 /// `IsListWithLength(usize)` or `IsListWith(Box<Condition>)`, which serve other purposes.
+/// 
+/// Conditions are displayed the same way they're written, such as `IsInt | IsFloat`, which is useful
+/// for reporting which condition a value failed.
 #[derive(Clone)]
 pub enum Condition {
     IsInt,
+    IsUInt,
     IsFloat,
     IsStr,
     IsList,
     IsBool,
 
     IsMap,
+    /// Verifies the value to be a `CfgValue::Opaque`, which holds a value that no other `CfgValue` can represent.
+    IsOpaque,
     /// A combination of two conditions.
     /// 
     /// If both evaluate to `TRUE`, the result is `TRUE`, otherwise it is `FALSE`.
@@ -67,9 +79,27 @@ pub enum Condition {
     /// Represents a negation.
     Not(Box<Condition>),
 
+    /// A combination of two conditions.
+    /// 
+    /// If exactly one evaluates to `TRUE`, the result is `TRUE`, otherwise it is `FALSE`.
+    Xor(Box<Condition>, Box<Condition>),
+
+    /// A combination of two conditions.
+    /// 
+    /// If the first evaluates to `TRUE` and the second doesn't, the result is `FALSE`, otherwise it is `TRUE`.
+    Implies(Box<Condition>, Box<Condition>),
+
+    /// A combination of any number of conditions.
+    /// 
+    /// If at least a specific number of them evaluate to `TRUE`, the result is `TRUE`, otherwise it is `FALSE`.
+    AtLeastN(usize, Vec<Condition>),
+
     /// Does an exact comparison with an integer.
     IsExactlyInt(super::_Int),
 
+    /// Does an exact comparison with an unsigned integer, which is only satisfied by a `UInt`.
+    IsExactlyUInt(super::_UInt),
+
     /// Does an exact comparison with an float.
     IsExactlyFloat(super::_Float),
 
@@ -82,6 +112,15 @@ pub enum Condition {
     /// Does an exact comparison with a `CfgMap`
     IsExactlyMap(super::CfgMap),
 
+    /// Does an exact comparison with a boolean.
+    IsExactlyBool(super::_Bool),
+
+    /// Verifies it to be an `Int` from the first integer to the second, inclusive.
+    IsIntInRange(super::_Int, super::_Int),
+
+    /// Verifies it to be a `Float` from the first float to the second, inclusive.
+    IsFloatInRange(super::_Float, super::_Float),
+
     /// Verifies it to be a `Bool`, and checks whether it is true.
     IsTrue,
 
@@ -91,6 +130,93 @@ pub enum Condition {
     /// Verifies it to be a `List`, while also having a specific length.
     IsListWithLength(usize),
 
+    /// Verifies it to be a `List` with at least one element satisfying the condition.
+    IsListWithAny(Box<Condition>),
+
+    /// Verifies it to be a `List` without any equal elements.
+    IsListWithUniqueElements,
+
+    /// Verifies it to be a `List` of numbers or of strings, in ascending order. Numbers are compared by value,
+    /// so `Int`s and `Float`s can be mixed, while strings are compared lexicographically.
+    IsSortedList,
+
+    /// Verifies it to be a `List` with at least a specific length.
+    IsListWithLengthAtLeast(usize),
+
+    /// Verifies it to be a `List` that isn't empty.
+    IsNonEmptyList,
+
+    /// Verifies it to be a `Str` starting with a specific string.
+    IsStrStartingWith(super::_Str),
+
+    /// Verifies it to be a `Str` ending with a specific string.
+    IsStrEndingWith(super::_Str),
+
+    /// Verifies it to be a `Str` containing a specific string.
+    IsStrContaining(super::_Str),
+
+    /// Verifies it to be a `Str` with a specific number of characters.
+    IsStrWithLength(usize),
+
+    /// Verifies it to be a `Str` that isn't empty.
+    IsNonEmptyStr,
+
+    /// Verifies it to be a `Str` containing a duration, such as `30s` or `1h 30m`, as parsed by `CfgValue::to_duration`.
+    IsDurationStr,
+
+    /// Verifies it to be a `Str` containing a size in bytes, such as `10MB` or `512KiB`, as parsed by `CfgValue::to_byte_size`.
+    IsByteSizeStr,
+
+    /// Verifies it to be a `Str` containing a path that exists on the filesystem.
+    /// 
+    /// This accesses the filesystem each time the condition is executed, and the result can change afterwards.
+    IsExistingPath,
+
+    /// Verifies it to be a `Str` containing the path of a file that can be opened for reading.
+    /// 
+    /// This opens the file each time the condition is executed, and the result can change afterwards.
+    IsReadableFile,
+
+    /// Verifies it to be a `Str` containing the path of a directory.
+    /// 
+    /// This accesses the filesystem each time the condition is executed, and the result can change afterwards.
+    IsDirectory,
+
+    /// Verifies it to be a `Str` that parses into an integer using `str::parse`, such as `"-42"`.
+    IsParsableAsInt,
+
+    /// Verifies it to be a `Str` that parses into a float using `str::parse`, such as `"2.5"` or `"1e3"`.
+    IsParsableAsFloat,
+
+    /// Verifies it to be a `Str` that parses into a boolean using `str::parse`, meaning `"true"` or `"false"`.
+    IsParsableAsBool,
+
+    /// Verifies it to be a `Str` equal to one of the strings, such as for settings with a fixed set of options.
+    IsOneOfStr(Vec<super::_Str>),
+
+    /// Verifies it to be equal to one of the values.
+    IsIn(Vec<super::CfgValue>),
+
+    /// Verifies it to be a `Map` and applies the condition to each of its values.
+    IsMapWith(Box<Condition>),
+
+    /// Verifies it to be a `Map` containing a specific key, and applies the condition to its value.
+    IsMapWithKeyValue(super::_Str, Box<Condition>),
+
+    /// Verifies it to be a `Map` containing a specific key.
+    HasKey(super::_Str),
+
+    /// Verifies it to be a `Map` containing every one of the keys.
+    HasKeys(Vec<super::_Str>),
+
+    /// Verifies it to be a `Map` without any keys other than these, which catches unknown keys.
+    /// The keys don't all need to be present, which can be checked using `HasKeys`.
+    HasOnlyKeys(Vec<super::_Str>),
+
+    /// Applies a custom predicate to the value, so that checks specific to an application can be combined
+    /// with other conditions. Usually created using `Condition::custom`.
+    Satisfies(Arc<dyn Fn(&super::CfgValue) -> bool + Send + Sync>),
+
     #[cfg(feature = "from_json")]
     /// Verifies the value to be `null`. Only availiable while using `from_json`.
     IsNull,
@@ -99,6 +225,38 @@ pub enum Condition {
     /// Verifies the value to be a `Datetime`. Only available while using `from_toml`.
     IsDatetime,
 
+    #[cfg(feature = "from_toml")]
+    /// Does an exact comparison with a `Datetime`. Only available while using `from_toml`.
+    IsExactlyDatetime(toml::value::Datetime),
+
+    #[cfg(feature = "net-validate")]
+    /// Verifies it to be a `Str` containing an absolute url with a host, such as `https://example.com:8080/path`.
+    /// Only available while using `net-validate`.
+    IsUrl,
+
+    #[cfg(feature = "net-validate")]
+    /// Verifies it to be a `Str` containing an email address, such as `admin@example.com`. This checks
+    /// its shape rather than fully validating it. Only available while using `net-validate`.
+    IsEmail,
+
+    #[cfg(feature = "net-validate")]
+    /// Verifies it to be a `Str` containing an IPv4 or IPv6 address. Only available while using `net-validate`.
+    IsIpAddr,
+
+    #[cfg(feature = "net-validate")]
+    /// Verifies it to be an `Int` from 1 to 65535. Only available while using `net-validate`.
+    IsPort,
+
+    #[cfg(feature = "net-validate")]
+    /// Verifies it to be a `Str` containing an IPv4 or IPv6 address followed by a prefix length, such as `10.0.0.0/8`.
+    /// Only available while using `net-validate`.
+    IsCidr,
+
+    #[cfg(feature = "regex")]
+    /// Verifies it to be a `Str` that the regex matches somewhere within. Use `^` and `$` to match
+    /// the whole string. Only available while using `regex`.
+    IsStrMatching(regex::Regex),
+
     /// A result condition. When executed this will always return `true`.
     TRUE,
 
@@ -106,6 +264,36 @@ pub enum Condition {
     FALSE
 }
 
+/// Options for executing a condition using `Condition::execute_with`.
+/// 
+/// By default, conditions can be nested without limit, and aren't counted.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExecuteOptions<'a> {
+    max_depth: Option<usize>,
+    counter: Option<&'a AtomicUsize>,
+}
+
+impl<'a> ExecuteOptions<'a> {
+    /// Creates the default options.
+    pub fn new() -> ExecuteOptions<'a> {
+        ExecuteOptions::default()
+    }
+
+    /// Limits how deeply conditions can be nested within `And`, `Or`, `Not` and `IsListWith`.
+    /// The condition being executed is at depth 0.
+    pub fn max_depth(mut self, max_depth: usize) -> ExecuteOptions<'a> {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Adds the number of conditions evaluated to `counter`, including every nested condition,
+    /// and every time a condition within `IsListWith` is evaluated on an element.
+    pub fn counter(mut self, counter: &'a AtomicUsize) -> ExecuteOptions<'a> {
+        self.counter = Some(counter);
+        self
+    }
+}
+
 impl Condition {
 
     /// Helper function to generate an `AND` condition.
@@ -119,10 +307,104 @@ impl Condition {
     }
 
     /// Helper function to generate a `NOT` condition.
+    #[allow(clippy::should_implement_trait)]
     pub fn not(self) -> Condition {
         Condition::Not(Box::new(self))
     }
 
+    /// Helper function to generate an `XOR` condition.
+    pub fn xor(self, other: Condition) -> Condition {
+        Condition::Xor(Box::new(self), Box::new(other))
+    }
+
+    /// Helper function to generate an `IMPLIES` condition.
+    pub fn implies(self, other: Condition) -> Condition {
+        Condition::Implies(Box::new(self), Box::new(other))
+    }
+
+    /// Helper function to generate an `AtLeastN` condition.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{Condition::{self, *}, CfgMap, CfgValue::*, Checkable};
+    /// 
+    /// // Exactly one of `file` and `url` may be set.
+    /// let source = HasKey("file".into()).xor(HasKey("url".into()));
+    /// // Retries are only allowed over the network.
+    /// let retries = HasKey("retries".into()).implies(HasKey("url".into()));
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("file", Str("app.toml".into()));
+    /// assert!(Map(cmap.clone()).check_that(source.clone() & retries.clone()));
+    /// 
+    /// cmap.add("retries", Int(3));
+    /// assert!(!Map(cmap.clone()).check_that(retries));
+    /// cmap.add("url", Str("https://example.com".into()));
+    /// assert!(!Map(cmap.clone()).check_that(source));
+    /// 
+    /// let two_sources = Condition::at_least(2, vec![HasKey("file".into()), HasKey("url".into()), HasKey("env".into())]);
+    /// assert!(Map(cmap).check_that(two_sources));
+    /// ```
+    pub fn at_least(n: usize, conditions: Vec<Condition>) -> Condition {
+        Condition::AtLeastN(n, conditions)
+    }
+
+    /// Helper function to generate a `Satisfies` condition from a predicate.
+    ///
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{Condition::{self, *}, CfgValue::*, Checkable};
+    ///
+    /// let even = Condition::custom(|value| value.as_int().is_some_and(|i| i % 2 == 0));
+    /// assert!(Int(4).check_that(even.clone()));
+    /// assert!(!Int(3).check_that(even.clone() | IsFloat));
+    /// assert!(Float(3.0).check_that(even | IsFloat));
+    /// ```
+    pub fn custom<F>(predicate: F) -> Condition
+    where F: Fn(&super::CfgValue) -> bool + Send + Sync + 'static {
+        Condition::Satisfies(Arc::new(predicate))
+    }
+
+    /// Parses a condition from text, so that conditions can be stored alongside the configuration.
+    ///
+    /// Conditions are combined using `&`, `|` and `!`, where `!` binds the most tightly and `|` the least,
+    /// and can be grouped using parentheses. Each condition is written either the way it's displayed, such as
+    /// `IsInt` and `IsListWithLength(2)`, or using a shorter name:
+    ///
+    /// - Types: `int`, `uint`, `float`, `str`, `list`, `map`, `bool`, `opaque`, `null` and `datetime`.
+    /// - Values: `exactly_int(1)`, `exactly_uint(1)`, `exactly_float(2.5)`, `exactly_str("a")`, `exactly_bool(true)`,
+    ///   `exactly_datetime("1979-05-27")`, `is_true` and `one_of("a", "b")`.
+    /// - Ranges: `int in 1..65535` and `float in 0.0..1.0`, where both ends are included.
+    /// - Strings: `starts_with("a")`, `ends_with("a")`, `contains("a")`, `str_len(2)`, `non_empty_str`,
+    ///   `parsable_int`, `parsable_float`, `parsable_bool`, `duration_str`, `byte_size_str` and, while using `regex`,
+    ///   `matches("^[a-z]+$")`.
+    /// - Paths: `existing_path`, `readable_file` and `directory`.
+    /// - Lists: `list_with(int)`, `list_with_any(int)`, `list_len(2)`, `list_len_at_least(2)`, `non_empty_list`,
+    ///   `unique_list` and `sorted_list`.
+    /// - Maps: `map_with(int)`, `map_with_key("a", int)`, `has_key("a")`, `has_keys("a", "b")` and
+    ///   `has_only_keys("a", "b")`.
+    /// - Networking, while using `net-validate`: `url`, `email`, `ip_addr`, `port` and `cidr`.
+    /// - Combinations: `xor(int, str)`, `implies(int, exactly_int(1))` and `at_least(2, int, str, float)`.
+    /// - Results: `always` and `never`, which are `TRUE` and `FALSE`.
+    ///
+    /// `IsExactlyList`, `IsExactlyMap`, `IsIn` and `Satisfies` can't be parsed.
+    ///
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{Condition, CfgValue::*, Checkable};
+    ///
+    /// let condition = Condition::parse("!str & (int | list_with(exactly_float(2.5)))").unwrap();
+    /// assert_eq!(condition.to_string(), "!IsStr & (IsInt | IsListWith(IsExactlyFloat(2.5)))");
+    /// assert!(Int(5).check_that(condition.clone()));
+    ///
+    /// let error = Condition::parse("int | flaot").unwrap_err();
+    /// assert_eq!(error.position, 6);
+    /// assert_eq!(error.message, "unknown condition `flaot`");
+    /// ```
+    pub fn parse(text: &str) -> Result<Condition, super::ConditionParseError> {
+        super::condition_parse::parse(text)
+    }
+
     /// Executes the condition. For all conditions, this function
     /// will return one of the result conditions - `TRUE` or `FALSE`.
     /// All conditions are executed on the input that is passed - including 
@@ -137,71 +419,206 @@ impl Condition {
     /// assert!((IsInt | IsFloat).execute(&Float(1.0)).to_bool());
     /// ```
     pub fn execute(&self, input: &super::CfgValue) -> Condition {
+        self.execute_with(input, &ExecuteOptions::new())
+    }
+
+    /// Executes the condition the same way as `execute`, but using `options`.
+    /// 
+    /// If the condition is nested deeper than the maximum depth of `options`,
+    /// the nested conditions that are too deep are treated as `FALSE`.
+    /// 
+    /// ## Examples
+    /// 
+    /// ```
+    /// use cfgmap::{Condition::*, CfgValue::*, ExecuteOptions};
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// 
+    /// let evaluated = AtomicUsize::new(0);
+    /// let options = ExecuteOptions::new().max_depth(1).counter(&evaluated);
+    /// 
+    /// let value = List(vec![List(vec![Int(5)])]);
+    /// assert!(IsListWith(Box::new(IsList)).execute_with(&value, &options).to_bool());
+    /// assert!(!IsListWith(Box::new(IsListWith(Box::new(IsInt)))).execute_with(&value, &options).to_bool());
+    /// 
+    /// assert_eq!(evaluated.load(Ordering::Relaxed), 4);
+    /// ```
+    pub fn execute_with(&self, input: &super::CfgValue, options: &ExecuteOptions) -> Condition {
+        self.run(input, options, 0).into()
+    }
+
+    /// Finds the first value within `input` that doesn't satisfy the condition, returning its path relative to `input`.
+    /// Returns `None` if the condition is satisfied.
+    /// 
+    /// For `IsListWith`, this is the index of the first offending element, followed by the path of the offending
+    /// value within it if the element is checked using `IsListWith` too. `IsMapWith` and `IsMapWithKeyValue` work
+    /// the same way using keys, where `IsMapWith` checks keys in sorted order, and `IsMapWithKeyValue` reports a
+    /// missing key as the offending value. For `And`, this is the violation of whichever condition fails first. Otherwise, the path is empty, meaning that `input` itself is the offending value.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{Condition::*, CfgValue::*, list, value};
+    /// 
+    /// let value = List(vec![list![1, 2], list![3, "four"]]);
+    /// let condition = IsListWith(Box::new(IsListWith(Box::new(IsInt))));
+    /// 
+    /// assert_eq!(condition.first_violation(&value), Some("1/1".into()));
+    /// assert_eq!(IsList.first_violation(&value), None);
+    /// assert_eq!(IsInt.first_violation(&value), Some("".into()));
+    /// ```
+    pub fn first_violation(&self, input: &super::CfgValue) -> Option<String> {
+        // Prefixes the path of a violation with the key of the value it was found in.
+        fn within(key: &str, path: String) -> String {
+            if path.is_empty() { key.to_string() } else { format!("{}/{}", key, path) }
+        }
+
+        match self {
+            Condition::IsListWith(s) => match input.as_list() {
+                Some(list) => list.iter().enumerate().find_map(|(i, elem)| {
+                    s.first_violation(elem).map(|path| within(&i.to_string(), path))
+                }),
+                None => Some(String::new()),
+            },
+            Condition::IsMapWith(s) => match input.as_map() {
+                Some(map) => {
+                    let mut entries: Vec<(&String, &super::CfgValue)> = map.iter().collect();
+                    entries.sort_by(|a, b| a.0.cmp(b.0));
+                    entries.into_iter().find_map(|(key, value)| s.first_violation(value).map(|path| within(key, path)))
+                },
+                None => Some(String::new()),
+            },
+            Condition::IsMapWithKeyValue(key, s) => match input.as_map() {
+                Some(map) => match map.get(key) {
+                    Some(value) => s.first_violation(value).map(|path| within(key, path)),
+                    None => Some(key.clone()),
+                },
+                None => Some(String::new()),
+            },
+            Condition::And(x, y) => x.first_violation(input).or_else(|| y.first_violation(input)),
+            _ if self.execute(input).to_bool() => None,
+            _ => Some(String::new()),
+        }
+    }
+
+    /// Executes the condition, which is nested `depth` levels deep within the condition being executed.
+    fn run(&self, input: &super::CfgValue, options: &ExecuteOptions, depth: usize) -> bool {
         use Condition::*;
 
+        if options.max_depth.is_some_and(|max| depth > max) {
+            return false;
+        }
+        if let Some(counter) = options.counter {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+
         match self {
             // Basic conditions.
-            IsInt => input.is_int().into(),
-            IsFloat => input.is_float().into(),
-            IsStr => input.is_str().into(),
-            IsList => input.is_list().into(),
-            IsMap => input.is_map().into(),
-            IsBool => input.is_bool().into(),
-            TRUE => TRUE,
-            FALSE => FALSE,
+            IsInt => input.is_int(),
+            IsUInt => input.is_uint(),
+            IsFloat => input.is_float(),
+            IsStr => input.is_str(),
+            IsList => input.is_list(),
+            IsMap => input.is_map(),
+            IsBool => input.is_bool(),
+            IsOpaque => input.is_opaque(),
+            TRUE => true,
+            FALSE => false,
 
             // Combined conditions.
-            And(x,y) => {
-                let res1 = x.execute(input);
-                let res2 = y.execute(input);
+            And(x,y) => x.run(input, options, depth + 1) && y.run(input, options, depth + 1),
+            Or(x,y) => x.run(input, options, depth + 1) || y.run(input, options, depth + 1),
+            Not(x) => !x.run(input, options, depth + 1),
+            Xor(x,y) => x.run(input, options, depth + 1) != y.run(input, options, depth + 1),
+            Implies(x,y) => !x.run(input, options, depth + 1) || y.run(input, options, depth + 1),
+            AtLeastN(n, s) => s.iter().filter(|c| c.run(input, options, depth + 1)).take(*n).count() == *n,
 
-                if res1.to_bool() && res2.to_bool() {
-                    TRUE
-                } else {FALSE}
-            },
-            Or(x,y) => {
-                let res1 = x.execute(input);
-                let res2 = y.execute(input);
+            // Exact condition.
+            IsExactlyInt(s) => input.as_int() == Some(s),
+            IsExactlyUInt(s) => input.as_uint() == Some(s),
+            IsExactlyFloat(s) => input.as_float() == Some(s),
+            IsExactlyStr(s) => input.as_str() == Some(s),
+            IsExactlyList(s) => input.as_list() == Some(s),
+            IsExactlyMap(s) => input.as_map() == Some(s),
+            IsExactlyBool(s) => input.as_bool() == Some(s),
+            IsTrue => input.as_bool() == Some(&true),
+            IsIntInRange(min, max) => input.as_int().is_some_and(|i| min <= i && i <= max),
+            IsFloatInRange(min, max) => input.as_float().is_some_and(|f| min <= f && f <= max),
 
-                if res1.to_bool() || res2.to_bool() {
-                    TRUE
-                } else {FALSE}
-            },
-            Not(x) => {
-                let res = x.execute(input);
+            // Miscellaneous.
+            IsListWith(s) => input.as_list()
+                .is_some_and(|list| list.iter().all(|elem| s.run(elem, options, depth + 1))),
 
-                if res.to_bool() { FALSE } else { TRUE }
-            },
+            IsListWithLength(l) => input.as_list().is_some_and(|li| *l == li.len()),
 
-            // Exact condition.
-            IsExactlyInt(s) => input.as_int().map_or(false, |i| *i == *s).into(),
-            IsExactlyFloat(s) => input.as_float().map_or(false, |f| *f == *s).into(),
-            IsExactlyStr(s) => input.as_str().map_or(false, |st| *st == *s).into(),
-            IsExactlyList(s) => input.as_list().map_or(false, |l| *l == *s).into(),
-            IsExactlyMap(s) => input.as_map().map_or(false, |l| *l == *s).into(),
-            IsTrue => input.as_bool().map_or(false, |b| *b).into(),
+            IsListWithAny(s) => input.as_list()
+                .is_some_and(|list| list.iter().any(|elem| s.run(elem, options, depth + 1))),
 
-            // Miscellaneous.
-            IsListWith(s) => {
-                input.as_list().map(|list| {
-                    for elem in list.iter() {
-                        if !elem.check_that((**s).clone()) {
-                            return FALSE;
-                        }
-                    }
-                    TRUE
-                }).map_or(FALSE, |o| o.into())
-            },
+            IsListWithUniqueElements => input.as_list()
+                .is_some_and(|list| list.iter().enumerate().all(|(i, elem)| !list[..i].contains(elem))),
+
+            IsSortedList => input.as_list()
+                .is_some_and(|list| list.windows(2).all(|pair| in_order(&pair[0], &pair[1]))),
+
+            IsListWithLengthAtLeast(l) => input.as_list().is_some_and(|li| li.len() >= *l),
+            IsNonEmptyList => input.as_list().is_some_and(|li| !li.is_empty()),
+
+            IsStrStartingWith(s) => input.as_str().is_some_and(|st| st.starts_with(s.as_str())),
+            IsStrEndingWith(s) => input.as_str().is_some_and(|st| st.ends_with(s.as_str())),
+            IsStrContaining(s) => input.as_str().is_some_and(|st| st.contains(s.as_str())),
+            IsStrWithLength(l) => input.as_str().is_some_and(|st| *l == st.chars().count()),
+            IsNonEmptyStr => input.as_str().is_some_and(|st| !st.is_empty()),
+            IsDurationStr => input.to_duration().is_some(),
+            IsByteSizeStr => input.to_byte_size().is_some(),
+            IsExistingPath => input.as_str().is_some_and(|st| Path::new(st).exists()),
+            IsReadableFile => input.as_str().is_some_and(|st| Path::new(st).is_file() && File::open(st).is_ok()),
+            IsDirectory => input.as_str().is_some_and(|st| Path::new(st).is_dir()),
+            IsParsableAsInt => input.as_str().is_some_and(|st| st.parse::<super::_Int>().is_ok()),
+            IsParsableAsFloat => input.as_str().is_some_and(|st| st.parse::<super::_Float>().is_ok()),
+            IsParsableAsBool => input.as_str().is_some_and(|st| st.parse::<super::_Bool>().is_ok()),
+
+            IsOneOfStr(s) => input.as_str().is_some_and(|st| s.contains(st)),
+            IsIn(s) => s.contains(input),
+
+            IsMapWith(s) => input.as_map()
+                .is_some_and(|map| map.values().all(|value| s.run(value, options, depth + 1))),
 
-            IsListWithLength(l) => input.as_list().map_or(false, |li| *l == li.len()).into(),
+            IsMapWithKeyValue(k, s) => input.as_map()
+                .and_then(|map| map.get(k))
+                .is_some_and(|value| s.run(value, options, depth + 1)),
+
+            HasKey(k) => input.as_map().is_some_and(|map| map.contains_key(k)),
+            HasKeys(k) => input.as_map().is_some_and(|map| k.iter().all(|key| map.contains_key(key))),
+            HasOnlyKeys(k) => input.as_map().is_some_and(|map| map.keys().all(|key| k.contains(key))),
+
+            Satisfies(predicate) => predicate(input),
 
             // Feature-dependent.
 
             #[cfg(feature = "from_json")]
-            IsNull => input.is_null().into(),
+            IsNull => input.is_null(),
+
+            #[cfg(feature = "from_toml")]
+            IsDatetime => input.is_datetime(),
 
             #[cfg(feature = "from_toml")]
-            IsDatetime => input.is_datetime().into(),
+            IsExactlyDatetime(s) => input.as_datetime() == Some(s),
+
+            #[cfg(feature = "net-validate")]
+            IsUrl => input.as_str().is_some_and(|s| super::net::is_url(s)),
+
+            #[cfg(feature = "net-validate")]
+            IsEmail => input.as_str().is_some_and(|s| super::net::is_email(s)),
+
+            #[cfg(feature = "net-validate")]
+            IsIpAddr => input.as_str().is_some_and(|s| s.parse::<std::net::IpAddr>().is_ok()),
+
+            #[cfg(feature = "net-validate")]
+            IsPort => input.as_int().is_some_and(|i| (1..=65535).contains(i)),
+
+            #[cfg(feature = "net-validate")]
+            IsCidr => input.as_str().is_some_and(|s| super::net::is_cidr(s)),
+
+            #[cfg(feature = "regex")]
+            IsStrMatching(r) => input.as_str().is_some_and(|s| r.is_match(s)),
         }
     }
 
@@ -216,7 +633,184 @@ impl Condition {
     /// Reasoning behind this is that all other values are either incomplete conditions,
     /// or FALSE.
     pub fn to_bool(&self) -> bool {
-        if let Condition::TRUE = self { true } else { false }
+        matches!(self, Condition::TRUE)
+    }
+}
+
+/// Checks whether `a` comes no later than `b` for `IsSortedList`, where both are numbers or both are strings.
+fn in_order(a: &super::CfgValue, b: &super::CfgValue) -> bool {
+    use super::CfgValue::*;
+
+    let number = |value: &super::CfgValue| match value {
+        Int(i) => Some(*i as f64),
+        UInt(u) => Some(*u as f64),
+        Float(f) => Some(*f),
+        _ => None,
+    };
+
+    match (a, b) {
+        (Str(a), Str(b)) => a <= b,
+        (Int(a), Int(b)) => a <= b,
+        (UInt(a), UInt(b)) => a <= b,
+        (Int(a), UInt(b)) => *a < 0 || (*a as u64) <= *b,
+        (UInt(a), Int(b)) => *b >= 0 && *a <= (*b as u64),
+        _ => match (number(a), number(b)) {
+            (Some(a), Some(b)) => a <= b,
+            _ => false,
+        },
+    }
+}
+
+impl Condition {
+    /// How tightly the condition binds when displayed, so that conditions binding less tightly than
+    /// the condition they're within are displayed in parentheses.
+    fn precedence(&self) -> u8 {
+        match self {
+            Condition::Or(..) => 0,
+            Condition::And(..) => 1,
+            _ => 2,
+        }
+    }
+
+    /// Displays `inner`, which is within this condition, in parentheses if it binds less tightly.
+    fn fmt_inner(&self, inner: &Condition, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if inner.precedence() < self.precedence() {
+            write!(f, "({})", inner)
+        } else {
+            write!(f, "{}", inner)
+        }
+    }
+}
+
+/// Debugs the condition the same way it's written, except that `Satisfies` is debugged as `Satisfies(..)`,
+/// since its predicate can't be.
+impl fmt::Debug for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Condition::*;
+
+        match self {
+            IsInt => f.write_str("IsInt"),
+            IsUInt => f.write_str("IsUInt"),
+            IsFloat => f.write_str("IsFloat"),
+            IsStr => f.write_str("IsStr"),
+            IsList => f.write_str("IsList"),
+            IsBool => f.write_str("IsBool"),
+            IsMap => f.write_str("IsMap"),
+            IsOpaque => f.write_str("IsOpaque"),
+            And(x, y) => f.debug_tuple("And").field(x).field(y).finish(),
+            Or(x, y) => f.debug_tuple("Or").field(x).field(y).finish(),
+            Not(x) => f.debug_tuple("Not").field(x).finish(),
+            Xor(x, y) => f.debug_tuple("Xor").field(x).field(y).finish(),
+            Implies(x, y) => f.debug_tuple("Implies").field(x).field(y).finish(),
+            AtLeastN(n, s) => f.debug_tuple("AtLeastN").field(n).field(s).finish(),
+            IsExactlyInt(s) => f.debug_tuple("IsExactlyInt").field(s).finish(),
+            IsExactlyUInt(s) => f.debug_tuple("IsExactlyUInt").field(s).finish(),
+            IsExactlyFloat(s) => f.debug_tuple("IsExactlyFloat").field(s).finish(),
+            IsExactlyStr(s) => f.debug_tuple("IsExactlyStr").field(s).finish(),
+            IsExactlyList(s) => f.debug_tuple("IsExactlyList").field(s).finish(),
+            IsExactlyMap(s) => f.debug_tuple("IsExactlyMap").field(s).finish(),
+            IsExactlyBool(s) => f.debug_tuple("IsExactlyBool").field(s).finish(),
+            IsTrue => f.write_str("IsTrue"),
+            IsIntInRange(min, max) => f.debug_tuple("IsIntInRange").field(min).field(max).finish(),
+            IsFloatInRange(min, max) => f.debug_tuple("IsFloatInRange").field(min).field(max).finish(),
+            IsListWith(s) => f.debug_tuple("IsListWith").field(s).finish(),
+            IsListWithLength(l) => f.debug_tuple("IsListWithLength").field(l).finish(),
+            IsListWithAny(s) => f.debug_tuple("IsListWithAny").field(s).finish(),
+            IsListWithUniqueElements => f.write_str("IsListWithUniqueElements"),
+            IsSortedList => f.write_str("IsSortedList"),
+            IsListWithLengthAtLeast(l) => f.debug_tuple("IsListWithLengthAtLeast").field(l).finish(),
+            IsNonEmptyList => f.write_str("IsNonEmptyList"),
+            IsStrStartingWith(s) => f.debug_tuple("IsStrStartingWith").field(s).finish(),
+            IsStrEndingWith(s) => f.debug_tuple("IsStrEndingWith").field(s).finish(),
+            IsStrContaining(s) => f.debug_tuple("IsStrContaining").field(s).finish(),
+            IsStrWithLength(l) => f.debug_tuple("IsStrWithLength").field(l).finish(),
+            IsNonEmptyStr => f.write_str("IsNonEmptyStr"),
+            IsDurationStr => f.write_str("IsDurationStr"),
+            IsByteSizeStr => f.write_str("IsByteSizeStr"),
+            IsExistingPath => f.write_str("IsExistingPath"),
+            IsReadableFile => f.write_str("IsReadableFile"),
+            IsDirectory => f.write_str("IsDirectory"),
+            IsParsableAsInt => f.write_str("IsParsableAsInt"),
+            IsParsableAsFloat => f.write_str("IsParsableAsFloat"),
+            IsParsableAsBool => f.write_str("IsParsableAsBool"),
+            IsOneOfStr(s) => f.debug_tuple("IsOneOfStr").field(s).finish(),
+            IsIn(s) => f.debug_tuple("IsIn").field(s).finish(),
+            IsMapWith(s) => f.debug_tuple("IsMapWith").field(s).finish(),
+            IsMapWithKeyValue(k, s) => f.debug_tuple("IsMapWithKeyValue").field(k).field(s).finish(),
+            HasKey(k) => f.debug_tuple("HasKey").field(k).finish(),
+            HasKeys(k) => f.debug_tuple("HasKeys").field(k).finish(),
+            HasOnlyKeys(k) => f.debug_tuple("HasOnlyKeys").field(k).finish(),
+            Satisfies(_) => f.write_str("Satisfies(..)"),
+            #[cfg(feature = "from_json")]
+            IsNull => f.write_str("IsNull"),
+            #[cfg(feature = "from_toml")]
+            IsDatetime => f.write_str("IsDatetime"),
+            #[cfg(feature = "from_toml")]
+            IsExactlyDatetime(s) => f.debug_tuple("IsExactlyDatetime").field(s).finish(),
+            #[cfg(feature = "net-validate")]
+            IsUrl => f.write_str("IsUrl"),
+            #[cfg(feature = "net-validate")]
+            IsEmail => f.write_str("IsEmail"),
+            #[cfg(feature = "net-validate")]
+            IsIpAddr => f.write_str("IsIpAddr"),
+            #[cfg(feature = "net-validate")]
+            IsPort => f.write_str("IsPort"),
+            #[cfg(feature = "net-validate")]
+            IsCidr => f.write_str("IsCidr"),
+            // Debugged using the pattern, so that it can be parsed back.
+            #[cfg(feature = "regex")]
+            IsStrMatching(r) => f.debug_tuple("IsStrMatching").field(&r.as_str()).finish(),
+            TRUE => f.write_str("TRUE"),
+            FALSE => f.write_str("FALSE"),
+        }
+    }
+}
+
+/// Displays the condition the same way it would be written using `&`, `|` and `!`.
+/// 
+/// ## Examples
+/// ```
+/// use cfgmap::Condition::*;
+/// 
+/// assert_eq!((IsInt | IsFloat).to_string(), "IsInt | IsFloat");
+/// assert_eq!((IsListWithLength(2) & IsListWith(Box::new(IsInt))).to_string(), "IsListWithLength(2) & IsListWith(IsInt)");
+/// assert_eq!(IsListWith(Box::new(IsInt | IsFloat)).to_string(), "IsListWith(IsInt | IsFloat)");
+/// assert_eq!((!(IsInt | IsExactlyStr("a".into())) & IsTrue).to_string(), "!(IsInt | IsExactlyStr(\"a\")) & IsTrue");
+/// ```
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Condition::*;
+
+        match self {
+            And(x, y) => { self.fmt_inner(x, f)?; f.write_str(" & ")?; self.fmt_inner(y, f) },
+            Or(x, y) => { self.fmt_inner(x, f)?; f.write_str(" | ")?; self.fmt_inner(y, f) },
+            Not(x) => { f.write_str("!")?; self.fmt_inner(x, f) },
+            Xor(x, y) => write!(f, "Xor({}, {})", x, y),
+            Implies(x, y) => write!(f, "Implies({}, {})", x, y),
+            AtLeastN(n, s) => {
+                write!(f, "AtLeastN({}, [", n)?;
+                for (i, condition) in s.iter().enumerate() {
+                    if i > 0 { f.write_str(", ")?; }
+                    write!(f, "{}", condition)?;
+                }
+                f.write_str("])")
+            },
+            IsListWith(x) => write!(f, "IsListWith({})", x),
+            IsListWithAny(x) => write!(f, "IsListWithAny({})", x),
+            IsMapWith(x) => write!(f, "IsMapWith({})", x),
+            IsMapWithKeyValue(k, x) => write!(f, "IsMapWithKeyValue({:?}, {})", k, x),
+            // Everything else is displayed the same way it's debugged.
+            condition => fmt::Debug::fmt(condition, f),
+        }
+    }
+}
+
+/// Parses the condition using `Condition::parse`.
+impl FromStr for Condition {
+    type Err = super::ConditionParseError;
+
+    fn from_str(text: &str) -> Result<Condition, Self::Err> {
+        Condition::parse(text)
     }
 }
 
@@ -238,6 +832,15 @@ impl BitOr for Condition {
     }
 }
 
+/// Syntactical sugar for `a.xor(b)`.
+impl BitXor for Condition {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        self.xor(rhs)
+    }
+}
+
 /// Syntactical sugar for `a.not()`
 impl Not for Condition {
     type Output = Self;
@@ -260,8 +863,9 @@ impl From<Condition> for bool {
 }
 
 #[cfg(test)]
+#[allow(clippy::useless_vec)]
 mod test {
-    use crate::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    use crate::{CfgMap, CfgValue::*, Condition::{self, *}, Checkable, list, value};
 
     #[test]
     fn basic_and_exact() {
@@ -328,4 +932,251 @@ mod test {
         assert!(!listexample.check_that(IsListWithLength(3)));
     }
 
+    #[test]
+    fn list_violations() {
+        let list = List(vec![Int(1), Str("two".into()), List(vec![Int(3), Float(4.0)]), Str("five".into())]);
+
+        assert_eq!(list.find_violations(&IsInt), vec![1, 2, 3]);
+        assert_eq!(list.find_violations(&(IsInt | IsList)), vec![1, 3]);
+        assert!(Int(1).find_violations(&IsInt).is_empty());
+
+        let nested = IsListWith(Box::new(IsInt | IsListWith(Box::new(IsInt))));
+        assert_eq!(nested.first_violation(&list), Some("1".into()));
+        assert_eq!(IsListWith(Box::new(IsList & IsListWith(Box::new(IsInt)))).first_violation(&list), Some("0".into()));
+        assert_eq!(IsListWith(Box::new(IsInt)).first_violation(&Int(1)), Some("".into()));
+        assert_eq!((IsList & IsListWithLength(4)).first_violation(&list), None);
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn regex_conditions() {
+        let version = IsStrMatching(regex::Regex::new(r"^\d+\.\d+\.\d+$").unwrap());
+
+        assert!(Str("1.12.0".into()).check_that(version.clone()));
+        assert!(!Str("v1.12".into()).check_that(version.clone()));
+        assert!(!Int(1).check_that(version.clone()));
+
+        let parsed = Condition::parse(&version.to_string()).unwrap();
+        assert_eq!(parsed.to_string(), r#"IsStrMatching("^\\d+\\.\\d+\\.\\d+$")"#);
+        assert!(Str("0.4.0".into()).check_that(parsed));
+        assert_eq!(Condition::parse(r#"matches("(")"#).unwrap_err().position, 8);
+    }
+
+    #[test]
+    fn string_contents() {
+        let host = Str("db.example.com".into());
+
+        assert!(host.check_that(IsStrStartingWith("db.".into()) & IsStrEndingWith(".com".into())));
+        assert!(host.check_that(IsStrContaining("example".into()) & IsNonEmptyStr));
+        assert!(!host.check_that(IsStrStartingWith("example".into())));
+        assert!(Str("é!".into()).check_that(IsStrWithLength(2)));
+        assert!(!Str("".into()).check_that(IsNonEmptyStr));
+        assert!(!Int(1).check_that(IsNonEmptyStr | IsStrWithLength(1)));
+
+        let parsed = Condition::parse(r#"starts_with("db.") & str_len(14) & non_empty_str"#).unwrap();
+        assert_eq!(parsed.to_string(), r#"IsStrStartingWith("db.") & IsStrWithLength(14) & IsNonEmptyStr"#);
+        assert!(host.check_that(parsed));
+    }
+
+    #[test]
+    fn ranges() {
+        assert!(Int(1).check_that(IsIntInRange(1, 65535)) && Int(65535).check_that(IsIntInRange(1, 65535)));
+        assert!(!Int(0).check_that(IsIntInRange(1, 65535)) && !Float(2.0).check_that(IsIntInRange(1, 65535)));
+        assert!(Float(0.5).check_that(IsFloatInRange(0.0, 1.0)) && !Float(f64::NAN).check_that(IsFloatInRange(0.0, 1.0)));
+
+        let parsed = Condition::parse("int in 1..65535 | float in -0.5..0.5").unwrap();
+        assert_eq!(parsed.to_string(), "IsIntInRange(1, 65535) | IsFloatInRange(-0.5, 0.5)");
+        assert_eq!(Condition::parse(&parsed.to_string()).unwrap().to_string(), parsed.to_string());
+        assert!(Float(0.0).check_that(parsed));
+        assert_eq!(Condition::parse("int in 1").unwrap_err().message, "expected `..`");
+    }
+
+    #[test]
+    fn parsable_strings() {
+        assert!(Str("-42".into()).check_that(IsParsableAsInt & IsParsableAsFloat));
+        assert!(Str("1e3".into()).check_that(IsParsableAsFloat & !IsParsableAsInt));
+        assert!(Str("false".into()).check_that(IsParsableAsBool));
+        assert!(!Str(" 42".into()).check_that(IsParsableAsInt));
+        assert!(!Str("yes".into()).check_that(IsParsableAsBool));
+        assert!(!Int(42).check_that(IsParsableAsInt));
+
+        let parsed = Condition::parse("parsable_int | parsable_bool").unwrap();
+        assert_eq!(parsed.to_string(), "IsParsableAsInt | IsParsableAsBool");
+        assert!(Str("true".into()).check_that(parsed));
+    }
+
+    #[test]
+    #[cfg(feature = "net-validate")]
+    fn networking() {
+        let valid = |condition: Condition, values: &[&str]| values.iter().all(|v| Str(v.to_string()).check_that(condition.clone()));
+        let invalid = |condition: Condition, values: &[&str]| values.iter().all(|v| !Str(v.to_string()).check_that(condition.clone()));
+
+        assert!(valid(IsUrl, &["https://example.com", "http://localhost:8080/a?b#c", "postgres://user:pw@[::1]:5432/db", "s3://10.0.0.1"]));
+        assert!(invalid(IsUrl, &["example.com", "https://", "https://exa mple.com", "http://a:99999", "http://[::1]8080", "1http://a"]));
+        assert!(valid(IsEmail, &["admin@example.com", "first.last+tag@mail.example.org"]));
+        assert!(invalid(IsEmail, &["admin", "admin@localhost", "@example.com", "a..b@example.com", "a@-example.com"]));
+        assert!(valid(IsIpAddr, &["127.0.0.1", "::1", "fd00::abcd"]));
+        assert!(invalid(IsIpAddr, &["256.0.0.1", "localhost", "1.2.3"]));
+        assert!(valid(IsCidr, &["10.0.0.0/8", "0.0.0.0/0", "fd00::/128"]));
+        assert!(invalid(IsCidr, &["10.0.0.0", "10.0.0.0/33", "fd00::/129", "10.0.0.0/+8"]));
+
+        assert!(Int(443).check_that(IsPort));
+        assert!(!Int(0).check_that(IsPort) && !Int(65536).check_that(IsPort) && !Str("443".into()).check_that(IsPort));
+
+        let parsed = Condition::parse("url | email | ip_addr | cidr | port").unwrap();
+        assert_eq!(parsed.to_string(), "IsUrl | IsEmail | IsIpAddr | IsCidr | IsPort");
+        assert!(Int(80).check_that(parsed));
+    }
+
+    #[test]
+    fn paths() {
+        let manifest = format!("{}/Cargo.toml", env!("CARGO_MANIFEST_DIR"));
+        let dir = Str(env!("CARGO_MANIFEST_DIR").into());
+        let missing = Str(format!("{}/missing.toml", env!("CARGO_MANIFEST_DIR")));
+
+        assert!(Str(manifest.clone()).check_that(IsExistingPath & IsReadableFile & !IsDirectory));
+        assert!(dir.check_that(IsExistingPath & IsDirectory & !IsReadableFile));
+        assert!(missing.check_that(!IsExistingPath & !IsReadableFile & !IsDirectory));
+        assert!(!Int(1).check_that(IsExistingPath));
+
+        let parsed = Condition::parse("readable_file | directory & existing_path").unwrap();
+        assert_eq!(parsed.to_string(), "IsReadableFile | IsDirectory & IsExistingPath");
+        assert!(Str(manifest).check_that(parsed));
+    }
+
+    #[test]
+    fn units() {
+        assert!(Str("1h 30m".into()).check_that(IsDurationStr & !IsByteSizeStr));
+        assert!(Str("512KiB".into()).check_that(IsByteSizeStr & !IsDurationStr));
+        assert!(!Str("30".into()).check_that(IsDurationStr | IsByteSizeStr));
+        assert!(!Int(30).check_that(IsDurationStr | IsByteSizeStr));
+
+        let parsed = Condition::parse("duration_str | byte_size_str").unwrap();
+        assert_eq!(parsed.to_string(), "IsDurationStr | IsByteSizeStr");
+        assert!(Str("10MB".into()).check_that(parsed));
+    }
+
+    #[test]
+    fn membership() {
+        let level = IsOneOfStr(vec!["debug".into(), "info".into(), "warn".into(), "error".into()]);
+
+        assert!(Str("warn".into()).check_that(level.clone()));
+        assert!(!Str("trace".into()).check_that(level.clone()));
+        assert!(!Int(1).check_that(level.clone()));
+        assert!(Float(0.5).check_that(IsIn(vec![Int(1), Float(0.5), Str("half".into())])));
+        assert!(!Int(2).check_that(IsIn(vec![Int(1), Float(2.0)])));
+
+        let parsed = Condition::parse(r#"one_of("debug", "info", "warn", "error",)"#).unwrap();
+        assert_eq!(parsed.to_string(), r#"IsOneOfStr(["debug", "info", "warn", "error"])"#);
+        assert_eq!(Condition::parse(&parsed.to_string()).unwrap().to_string(), parsed.to_string());
+        assert!(!Str("info".into()).check_that(Condition::parse("one_of()").unwrap()));
+    }
+
+    #[test]
+    fn map_keys() {
+        let mut cmap = CfgMap::new();
+        cmap.add("host", Str("localhost".into())).unwrap();
+        cmap.add("port", Int(5432)).unwrap();
+        let db = Map(cmap);
+
+        assert!(db.check_that(HasKey("port".into()) & HasKeys(vec!["host".into(), "port".into()])));
+        assert!(!db.check_that(HasKeys(vec!["host".into(), "user".into()])));
+        assert!(db.check_that(HasOnlyKeys(vec!["host".into(), "port".into(), "user".into()])));
+        assert!(!db.check_that(HasOnlyKeys(vec!["host".into()])));
+        assert!(!List(vec![]).check_that(HasOnlyKeys(vec![])));
+
+        let parsed = Condition::parse(r#"has_keys("host") & has_only_keys("host", "port")"#).unwrap();
+        assert_eq!(parsed.to_string(), r#"HasKeys(["host"]) & HasOnlyKeys(["host", "port"])"#);
+        assert_eq!(Condition::parse(&parsed.to_string()).unwrap().to_string(), parsed.to_string());
+        assert!(db.check_that(parsed));
+    }
+
+    #[test]
+    fn map_values() {
+        let mut ports = CfgMap::new();
+        ports.add("http", Int(80)).unwrap();
+        ports.add("https", Int(443)).unwrap();
+        ports.add("admin", Str("8080".into())).unwrap();
+        let ports = Map(ports);
+
+        assert!(!ports.check_that(IsMapWith(Box::new(IsInt))));
+        assert!(ports.check_that(IsMapWith(Box::new(IsInt | IsStr))));
+        assert!(ports.check_that(IsMapWithKeyValue("http".into(), Box::new(IsExactlyInt(80)))));
+        assert!(!ports.check_that(IsMapWithKeyValue("ftp".into(), Box::new(IsInt))));
+        assert!(Map(CfgMap::new()).check_that(IsMapWith(Box::new(FALSE))));
+
+        assert_eq!(IsMapWith(Box::new(IsInt)).first_violation(&ports), Some("admin".into()));
+        assert_eq!(IsMapWithKeyValue("ftp".into(), Box::new(IsInt)).first_violation(&ports), Some("ftp".into()));
+        assert_eq!(IsMapWith(Box::new(IsInt)).first_violation(&Int(1)), Some("".into()));
+
+        let parsed = Condition::parse(r#"map_with(int | str) & map_with_key("http", exactly_int(80))"#).unwrap();
+        assert_eq!(parsed.to_string(), r#"IsMapWith(IsInt | IsStr) & IsMapWithKeyValue("http", IsExactlyInt(80))"#);
+        assert_eq!(Condition::parse(&parsed.to_string()).unwrap().to_string(), parsed.to_string());
+        assert!(ports.check_that(parsed));
+    }
+
+    #[test]
+    fn list_contents() {
+        let ports = list![80, 443.0, 8080];
+
+        assert!(ports.check_that(IsListWithAny(Box::new(IsFloat)) & IsListWithUniqueElements & IsSortedList));
+        assert!(!ports.check_that(IsListWithAny(Box::new(IsStr))));
+        assert!(!list![1, 2, 1].check_that(IsListWithUniqueElements));
+        assert!(!list![2, 1].check_that(IsSortedList));
+        assert!(!list![1, "a"].check_that(IsSortedList));
+        assert!(list!["a", "a", "b"].check_that(IsSortedList));
+        assert!(List(vec![]).check_that(IsSortedList & !IsNonEmptyList & IsListWithLengthAtLeast(0)));
+        assert!(ports.check_that(IsListWithLengthAtLeast(3) & !IsListWithLengthAtLeast(4) & IsNonEmptyList));
+
+        let parsed = Condition::parse("list_with_any(float) & unique_list & sorted_list & list_len_at_least(1)").unwrap();
+        assert_eq!(parsed.to_string(), "IsListWithAny(IsFloat) & IsListWithUniqueElements & IsSortedList & IsListWithLengthAtLeast(1)");
+        assert!(ports.check_that(parsed));
+    }
+
+    #[test]
+    fn combinators() {
+        assert!(Int(1).check_that(IsInt ^ IsStr));
+        assert!(!Int(1).check_that(IsInt ^ IsExactlyInt(1)));
+        assert!(!Int(1).check_that(IsInt.xor(IsExactlyInt(1))));
+        assert!(Str("a".into()).check_that(IsInt.implies(FALSE)));
+        assert!(!Int(1).check_that(IsInt.implies(IsExactlyInt(2))));
+        assert!(Int(1).check_that(Condition::at_least(2, vec![IsInt, IsStr, IsExactlyInt(1)])));
+        assert!(!Int(1).check_that(Condition::at_least(3, vec![IsInt, IsStr, IsExactlyInt(1)])));
+        assert!(Int(1).check_that(Condition::at_least(0, vec![])));
+
+        let parsed = Condition::parse("xor(int, str) & implies(int, exactly_int(1)) & at_least(1, float, int)").unwrap();
+        assert_eq!(parsed.to_string(), "Xor(IsInt, IsStr) & Implies(IsInt, IsExactlyInt(1)) & AtLeastN(1, [IsFloat, IsInt])");
+        assert_eq!(Condition::parse(&parsed.to_string()).unwrap().to_string(), parsed.to_string());
+        assert!(Int(1).check_that(parsed));
+    }
+
+    #[test]
+    fn custom_predicates() {
+        let short = Condition::custom(|value| value.as_str().is_some_and(|s| s.len() <= 3));
+        let list = List(vec![Str("abc".into()), Str("abcd".into())]);
+
+        assert!(!list.check_that(IsListWith(Box::new(short.clone()))));
+        assert_eq!(IsListWith(Box::new(short.clone())).first_violation(&list), Some("1".into()));
+        assert!(list.check_that(IsListWith(Box::new(short.clone() | IsExactlyStr("abcd".into())))));
+        assert_eq!(format!("{:?}", !short.clone()), "Not(Satisfies(..))");
+        assert_eq!((short & IsStr).to_string(), "Satisfies(..) & IsStr");
+    }
+
+    #[test]
+    fn exact_bools_and_datetimes() {
+        assert!(Bool(true).check_that(IsExactlyBool(true)));
+        assert!(!Bool(true).check_that(IsExactlyBool(false)));
+        assert!(!Str("true".into()).check_that(IsExactlyBool(true)));
+
+        #[cfg(feature = "from_toml")]
+        {
+            let date: toml::value::Datetime = "1979-05-27T07:32:00Z".parse().unwrap();
+            let other: toml::value::Datetime = "1979-05-28T07:32:00Z".parse().unwrap();
+
+            assert!(Datetime(date.clone()).check_that(IsExactlyDatetime(date.clone())));
+            assert!(!Datetime(date.clone()).check_that(IsExactlyDatetime(other)));
+            assert!(!Str("1979-05-27T07:32:00Z".into()).check_that(IsExactlyDatetime(date)));
+        }
+    }
+
 }
\ No newline at end of file
@@ -0,0 +1,135 @@
+use super::{CfgError, CfgHashMap, CfgMap, CfgValue};
+use std::vec::IntoIter;
+
+/// A value of a document, split into either a converted leaf, or the children that still need converting.
+pub(crate) enum Node<V> {
+    Leaf(CfgValue),
+    List(Vec<V>),
+    Map(Vec<(String, V)>),
+}
+
+/// A list or map that's being converted, along with its children that weren't converted yet.
+enum Frame<V> {
+    List { pending: IntoIter<V>, done: Vec<CfgValue> },
+    Map { pending: IntoIter<(String, V)>, done: CfgHashMap, key: String },
+}
+
+impl<V> Frame<V> {
+    fn new(node: Node<V>) -> Result<Frame<V>, CfgValue> {
+        match node {
+            Node::Leaf(value) => Err(value),
+            Node::List(items) => Ok(Frame::List { done: Vec::with_capacity(items.len()), pending: items.into_iter() }),
+            Node::Map(entries) => Ok(Frame::Map {
+                done: CfgHashMap::with_capacity_and_hasher(entries.len(), Default::default()),
+                pending: entries.into_iter(),
+                key: String::new(),
+            }),
+        }
+    }
+
+    /// Adds a converted child, for the key returned by the last call to `next`.
+    fn push(&mut self, value: CfgValue) {
+        match self {
+            Frame::List { done, .. } => done.push(value),
+            Frame::Map { done, key, .. } => { done.insert(std::mem::take(key), value); },
+        }
+    }
+
+    /// Gets the next child that needs converting.
+    fn next(&mut self) -> Option<V> {
+        match self {
+            Frame::List { pending, .. } => pending.next(),
+            Frame::Map { pending, key, .. } => pending.next().map(|(k, v)| { *key = k; v }),
+        }
+    }
+
+    fn finish(self) -> CfgValue {
+        match self {
+            Frame::List { done, .. } => CfgValue::List(done),
+            Frame::Map { done, .. } => CfgValue::Map(CfgMap::with_internal_map(done)),
+        }
+    }
+}
+
+/// The most maps and lists that can be nested within each other in a converted document, including the root,
+/// the same as the limit of `serde_json`. Dropping, comparing and writing values all recurse once per level,
+/// so values nested any deeper could overflow the call stack.
+pub(crate) const MAX_DEPTH: usize = 128;
+
+/// Converts `root` and everything within it into a `CfgValue`, using `split` to convert each value into a `Node`.
+///
+/// The conversion uses its own stack rather than recursing, so deeply nested documents can't overflow the call stack.
+/// Returns `CfgError::Unrepresentable` if maps and lists are nested more than `MAX_DEPTH` levels deep.
+pub(crate) fn convert<V, F>(root: V, mut split: F) -> Result<CfgValue, CfgError>
+    where F: FnMut(V) -> Result<Node<V>, CfgError>
+{
+    let mut stack: Vec<Frame<V>> = Vec::new();
+    let mut next = root;
+
+    loop {
+        let mut converted = match Frame::new(split(next)?) {
+            Ok(_) if stack.len() == MAX_DEPTH => {
+                return Err(CfgError::Unrepresentable(format!("the document is nested more than {} levels deep", MAX_DEPTH)));
+            },
+            Ok(frame) => { stack.push(frame); None },
+            Err(leaf) => Some(leaf),
+        };
+
+        // Pass the converted value up to its parent, finishing every parent with nothing left to convert.
+        loop {
+            let top = match stack.last_mut() {
+                Some(top) => top,
+                None => return Ok(converted.expect("the root was converted")),
+            };
+
+            if let Some(value) = converted.take() {
+                top.push(value);
+            }
+
+            match top.next() {
+                Some(child) => { next = child; break; },
+                None => converted = stack.pop().map(Frame::finish),
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "from_json"))]
+mod test {
+    use super::MAX_DEPTH;
+    use crate::{CfgError, CfgMap, Checkable, Condition::*};
+    use serde_json::{Map, Value};
+    use std::convert::TryFrom;
+
+    /// Builds a document with `depth` maps and lists nested within each other, including the root.
+    fn nested(depth: usize) -> Value {
+        let mut value = Value::from(5);
+        for level in (1..depth).rev() {
+            if level % 2 == 0 {
+                let mut map = Map::new();
+                map.insert("a".into(), value);
+                value = Value::Object(map);
+            } else {
+                value = Value::Array(vec![value]);
+            }
+        }
+        let mut root = Map::new();
+        root.insert("root".into(), value);
+        Value::Object(root)
+    }
+
+    #[test]
+    fn deeply_nested() {
+        // Run on a small stack, so that recursing too deep would overflow it.
+        std::thread::Builder::new().stack_size(256 * 1024).spawn(|| {
+            let cmap = CfgMap::try_from(nested(MAX_DEPTH)).unwrap();
+            // The innermost list is at an odd level, right below a map.
+            let path = format!("root{}/0", "/0/a".repeat((MAX_DEPTH - 2) / 2));
+            assert!(cmap.get(&path).check_that(IsExactlyInt(5)));
+            assert_eq!(cmap.clone(), cmap);
+            drop(cmap);
+
+            assert!(matches!(CfgMap::try_from(nested(MAX_DEPTH + 1)), Err(CfgError::Unrepresentable(_))));
+        }).unwrap().join().unwrap();
+    }
+}
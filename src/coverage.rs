@@ -0,0 +1,52 @@
+use super::{CfgMap, Schema};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// How well a `Schema` covers a set of sample configurations, as returned by `Schema::coverage`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Coverage {
+    /// The paths of rules whose value is missing from every sample, in the order of the schema.
+    pub unexercised: Vec<String>,
+
+    /// The full paths of values within the samples that no rule checks, in sorted order. A value is checked by
+    /// a rule at its own path or at any path containing it.
+    pub uncovered: Vec<String>,
+}
+
+impl Coverage {
+    /// Checks whether every rule was exercised and every value was checked.
+    pub fn is_complete(&self) -> bool {
+        self.unexercised.is_empty() && self.uncovered.is_empty()
+    }
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines = self.unexercised.iter().map(|path| format!("`{}` is never exercised", path))
+            .chain(self.uncovered.iter().map(|path| format!("`{}` has no rule", path)));
+        for (i, line) in lines.enumerate() {
+            if i > 0 { f.write_str("\n")?; }
+            f.write_str(&line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Computes the coverage of `schema` over `samples`.
+pub(crate) fn coverage<'a, I: IntoIterator<Item = &'a CfgMap>>(schema: &Schema, samples: I) -> Coverage {
+    let samples: Vec<&CfgMap> = samples.into_iter().collect();
+    let rules = schema.rules();
+
+    let unexercised = rules.iter()
+        .filter(|(path, _)| samples.iter().all(|sample| sample.get_untracked(path).is_none()))
+        .map(|(path, _)| path.clone())
+        .collect();
+
+    let uncovered: BTreeSet<String> = samples.iter()
+        .flat_map(|sample| sample.walk())
+        .map(|(path, _)| path)
+        .filter(|path| !rules.iter().any(|(rule, _)| rule == path || super::is_within(path, rule)))
+        .collect();
+
+    Coverage { unexercised, uncovered: uncovered.into_iter().collect() }
+}
@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// A path deprecated using `CfgMap::deprecate`.
+#[derive(Debug, Clone)]
+pub(crate) struct Deprecation {
+    pub(crate) message: String,
+    pub(crate) replacement: Option<String>,
+}
+
+type HandlerFn = dyn Fn(&str, &str) + Send + Sync;
+
+/// The function called the first time a deprecated path is read.
+#[derive(Clone)]
+pub(crate) struct Handler(pub(crate) Arc<HandlerFn>);
+
+impl fmt::Debug for Handler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Handler")
+    }
+}
+
+/// Finds the deprecation that applies to `path`, which is either deprecated itself or within a deprecated path.
+/// If several apply, the most specific one is used.
+pub(crate) fn find<'a>(deprecations: &'a HashMap<String, Deprecation>, path: &str) -> Option<(&'a str, &'a Deprecation)> {
+    deprecations.iter()
+        .filter(|(deprecated, _)| *deprecated == path || super::is_within(path, deprecated))
+        .max_by_key(|(deprecated, _)| deprecated.len())
+        .map(|(deprecated, deprecation)| (deprecated.as_str(), deprecation))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CfgMap, CfgValue::*, Checkable, Condition::*};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn warns_once_and_aliases() {
+        let mut cmap = CfgMap::new();
+        cmap.add("old", Map(CfgMap::new())).unwrap();
+        cmap.add("old/port", Int(80)).unwrap();
+        cmap.add("old/host", Str("a".into())).unwrap();
+        cmap.add("new", Map(CfgMap::new())).unwrap();
+        cmap.add("new/port", Int(8080)).unwrap();
+
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let recorded = warnings.clone();
+        cmap.on_deprecated(move |path, message| recorded.lock().unwrap().push(format!("{}: {}", path, message)));
+        cmap.deprecate("old", "use `new` instead", Some("new"));
+
+        // Values missing from the replacement fall back to the deprecated path.
+        assert!(cmap.get("old/port").check_that(IsExactlyInt(8080)));
+        assert!(cmap.get("old/host").check_that(IsExactlyStr("a".into())));
+        assert!(cmap.get("new/port").check_that(IsExactlyInt(8080)));
+
+        assert_eq!(*warnings.lock().unwrap(), vec!["old: use `new` instead"]);
+    }
+
+    #[test]
+    fn most_specific_deprecation() {
+        let mut cmap = CfgMap::new();
+        cmap.add_force("db/port", Int(5432)).unwrap();
+        cmap.add_force("db/host", Str("a".into())).unwrap();
+        cmap.add_force("database/host", Str("b".into())).unwrap();
+        cmap.add("port", Int(6543)).unwrap();
+
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let recorded = warnings.clone();
+        cmap.deprecate("db", "use `database` instead", Some("database"));
+        cmap.deprecate("db/port", "use `port` instead", Some("port"));
+
+        // Nothing is reported without a handler, but reads are still aliased.
+        assert!(cmap.get("db/port").check_that(IsExactlyInt(6543)));
+        cmap.on_deprecated(move |path, message| recorded.lock().unwrap().push(format!("{}: {}", path, message)));
+
+        assert!(cmap.get("db/port").check_that(IsExactlyInt(6543)));
+        assert!(cmap.get("db/host").check_that(IsExactlyStr("b".into())));
+        assert_eq!(*warnings.lock().unwrap(), vec!["db/port: use `port` instead", "db: use `database` instead"]);
+    }
+}
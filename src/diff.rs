@@ -0,0 +1,119 @@
+use super::{CfgMap, CfgValue};
+use std::collections::BTreeMap;
+
+/// The differences between two `CfgMap`s, keyed by full path.
+///
+/// Nested maps and lists are recursed into, so only the innermost differing paths are recorded.
+/// For example, changing `server/port` will record `server/port` as changed, rather than `server`.
+/// List elements are compared by index.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CfgDiff {
+    /// Paths that only exist in the new map, along with their value.
+    pub added: BTreeMap<String, CfgValue>,
+
+    /// Paths that only exist in the old map, along with their value.
+    pub removed: BTreeMap<String, CfgValue>,
+
+    /// Paths that exist in both maps with differing values, along with the old and new value.
+    pub changed: BTreeMap<String, (CfgValue, CfgValue)>,
+}
+
+impl CfgDiff {
+    /// Checks whether there are no differences.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Gets every path that differs, regardless of how, sorted.
+    pub fn paths(&self) -> Vec<&String> {
+        let mut paths: Vec<&String> = self.added.keys()
+            .chain(self.removed.keys())
+            .chain(self.changed.keys())
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    fn diff_values(&mut self, path: String, old: &CfgValue, new: &CfgValue) {
+        match (old, new) {
+            (CfgValue::Map(old), CfgValue::Map(new)) => self.diff_maps(&path, old, new),
+            (CfgValue::List(old), CfgValue::List(new)) => {
+                for (i, o) in old.iter().enumerate() {
+                    let full = super::join_path(&path, &i.to_string());
+                    match new.get(i) {
+                        Some(n) => self.diff_values(full, o, n),
+                        None => { self.removed.insert(full, o.clone()); },
+                    }
+                }
+
+                for (i, n) in new.iter().enumerate().skip(old.len()) {
+                    self.added.insert(super::join_path(&path, &i.to_string()), n.clone());
+                }
+            },
+            _ => if old != new {
+                self.changed.insert(path, (old.clone(), new.clone()));
+            }
+        }
+    }
+
+    fn diff_maps(&mut self, path: &str, old: &CfgMap, new: &CfgMap) {
+        // Runtime information differs between every process, so it's never part of the diff.
+        let compared = |key: &&String| !super::is_meta_path(&super::join_path(path, key));
+
+        for (key, o) in old.iter().filter(|(key, _)| compared(key)) {
+            let full = super::join_path(path, key);
+            match new.internal_map.get(key) {
+                Some(n) => self.diff_values(full, o, n),
+                None => { self.removed.insert(full, o.clone()); },
+            }
+        }
+
+        for (key, n) in new.iter().filter(|(key, _)| compared(key) && !old.internal_map.contains_key(*key)) {
+            self.added.insert(super::join_path(path, key), n.clone());
+        }
+    }
+}
+
+/// Computes the differences between `old` and `new`.
+pub(crate) fn diff(old: &CfgMap, new: &CfgMap) -> CfgDiff {
+    let mut diff = CfgDiff::default();
+    diff.diff_maps("", old, new);
+    diff
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CfgMap, CfgValue::*};
+
+    #[test]
+    fn nested() {
+        let mut old = CfgMap::new();
+        old.add("server", Map(CfgMap::new())).unwrap();
+        old.add("server/port", Int(80)).unwrap();
+        old.add("server/host", Str("localhost".into())).unwrap();
+        old.add("list", list![1, 2, 3]).unwrap();
+        old.add("kind", Str("map".into())).unwrap();
+
+        let mut new = old.clone();
+        new.add("server/port", Int(8080)).unwrap();
+        new.remove("server/host");
+        new.add("server/tls", Bool(true)).unwrap();
+        new.add("list", list![1, 5]).unwrap();
+        new.add("kind", Map(CfgMap::new())).unwrap();
+
+        let diff = old.diff(&new);
+
+        assert_eq!(diff.paths(), vec!["kind", "list/1", "list/2", "server/host", "server/port", "server/tls"]);
+        assert_eq!(diff.changed["server/port"], (Int(80), Int(8080)));
+        assert_eq!(diff.changed["list/1"], (Int(2), Int(5)));
+        assert_eq!(diff.changed["kind"], (Str("map".into()), Map(CfgMap::new())));
+        assert_eq!(diff.removed["server/host"], Str("localhost".into()));
+        assert_eq!(diff.removed["list/2"], Int(3));
+        assert_eq!(diff.added["server/tls"], Bool(true));
+
+        assert!(old.diff(&old).is_empty());
+
+        new.attach_runtime_info::<&str>(&[]).unwrap();
+        assert_eq!(old.diff(&new).paths().len(), 6);
+    }
+}
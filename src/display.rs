@@ -0,0 +1,194 @@
+use super::{CfgMap, CfgValue};
+use std::collections::HashSet;
+use std::fmt;
+
+/// Renders values as an indented tree, one entry per line, with the keys of each map in sorted order.
+struct Tree<'a> {
+    secrets: &'a HashSet<String>,
+
+    /// How many levels of lists and maps are rendered, if limited.
+    depth: Option<usize>,
+
+    lines: Vec<String>,
+}
+
+impl Tree<'_> {
+    /// Checks whether `path` is secret, or within a secret map or list.
+    fn is_secret(&self, path: &str) -> bool {
+        self.secrets.iter().any(|secret| secret == path || super::is_within(path, secret))
+    }
+
+    /// Renders `value` on a single line, if it's a scalar, an empty list or map, or too deeply nested to render.
+    fn line(&self, value: &CfgValue, path: &str, depth: usize) -> Option<String> {
+        if self.is_secret(path) {
+            return Some("***".to_string());
+        }
+
+        match value {
+            CfgValue::Map(map) if map.is_empty() => Some("{}".to_string()),
+            CfgValue::List(list) if list.is_empty() => Some("[]".to_string()),
+            CfgValue::Map(_) if self.depth.is_some_and(|max| depth >= max) => Some("{...}".to_string()),
+            CfgValue::List(_) if self.depth.is_some_and(|max| depth >= max) => Some("[...]".to_string()),
+            CfgValue::Map(_) | CfgValue::List(_) => None,
+            value => Some(scalar(value)),
+        }
+    }
+
+    /// Renders every entry of `value`, which is a list or map at `path`, nested `depth` levels deep.
+    fn entries(&mut self, value: &CfgValue, path: &str, depth: usize) {
+        match value {
+            CfgValue::Map(map) => {
+                let mut entries: Vec<(&String, &CfgValue)> = map.iter().collect();
+                entries.sort_by(|a, b| a.0.cmp(b.0));
+
+                for (key, value) in entries {
+                    let path = super::join_path(path, key);
+                    match self.line(value, &path, depth + 1) {
+                        Some(line) => self.lines.push(format!("{}: {}", key, line)),
+                        None => {
+                            self.lines.push(format!("{}:", key));
+                            self.nested(value, &path, depth + 1, "  ");
+                        },
+                    }
+                }
+            },
+            CfgValue::List(list) => for (i, value) in list.iter().enumerate() {
+                let path = super::join_path(path, &i.to_string());
+                match self.line(value, &path, depth + 1) {
+                    Some(line) => self.lines.push(format!("- {}", line)),
+                    None => self.nested(value, &path, depth + 1, "- "),
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Renders the entries of `value` indented by two spaces, with the first one starting with `first`.
+    fn nested(&mut self, value: &CfgValue, path: &str, depth: usize, first: &str) {
+        let start = self.lines.len();
+        self.entries(value, path, depth);
+
+        for (i, line) in self.lines[start..].iter_mut().enumerate() {
+            line.insert_str(0, if i == 0 { first } else { "  " });
+        }
+    }
+}
+
+/// Renders a value that isn't a list or map.
+fn scalar(value: &CfgValue) -> String {
+    match value {
+        CfgValue::Int(i) => i.to_string(),
+        CfgValue::UInt(u) => u.to_string(),
+        CfgValue::Float(f) => format!("{:?}", f),
+        CfgValue::Str(s) => format!("{:?}", s),
+        CfgValue::Bool(b) => b.to_string(),
+        CfgValue::Opaque(format, _) => format!("<opaque {}>", format),
+        #[cfg(feature = "from_toml")]
+        CfgValue::Datetime(datetime) => datetime.to_string(),
+        #[cfg(any(feature = "from_json", feature = "from_yaml"))]
+        CfgValue::Null => "null".to_string(),
+        #[cfg(feature = "from_yaml")]
+        CfgValue::BadValue => "<bad value>".to_string(),
+        #[cfg(feature = "from_yaml")]
+        CfgValue::Alias(alias) => format!("*{}", alias),
+        CfgValue::Map(_) | CfgValue::List(_) => unreachable!("lists and maps aren't scalars"),
+    }
+}
+
+/// Renders `value`, whose values at or within `secrets` are redacted, using the precision of `f` as the depth limit.
+fn render(value: &CfgValue, secrets: &HashSet<String>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut tree = Tree { secrets, depth: f.precision(), lines: Vec::new() };
+
+    match tree.line(value, "", 0) {
+        Some(line) => f.write_str(&line),
+        None => {
+            tree.entries(value, "", 0);
+            f.write_str(&tree.lines.join("\n"))
+        },
+    }
+}
+
+/// Renders the map as an indented tree, with the keys of each map in sorted order, which is useful for
+/// printing the configuration while debugging. Values marked with `mark_secret` are printed as `***`.
+///
+/// The precision limits how many levels of lists and maps are rendered, so that `{:.1}` only renders the
+/// top-level entries, and renders any lists and maps within them as `[...]` and `{...}`.
+///
+/// ## Examples
+/// ```
+/// use cfgmap::{CfgMap, CfgValue::*, list, value};
+///
+/// let mut cmap = CfgMap::new();
+/// cmap.add("name", Str("app".into()));
+/// cmap.add("db", Map(CfgMap::new()));
+/// cmap.add("db/ports", list![5432, 5433]);
+/// cmap.add("db/password", Str("hunter2".into()));
+/// cmap.mark_secret("db/password");
+///
+/// assert_eq!(cmap.to_string(), "db:\n  password: ***\n  ports:\n    - 5432\n    - 5433\nname: \"app\"");
+/// assert_eq!(format!("{:.1}", cmap), "db: {...}\nname: \"app\"");
+/// ```
+impl fmt::Display for CfgMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        render(&CfgValue::Map(self.contents()), &self.secret_paths(), f)
+    }
+}
+
+/// Renders the value the same way as `CfgMap`. Scalars are rendered on a single line, with strings in quotes.
+impl fmt::Display for CfgValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        render(self, &HashSet::new(), f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Tree;
+    use crate::{CfgMap, CfgValue::*};
+
+    #[test]
+    fn nested_lists_and_depth() {
+        let mut cmap = CfgMap::new();
+        cmap.add("servers", List(vec![Map(CfgMap::new()), list![1, 2], List(vec![])])).unwrap();
+        cmap.add("servers/0/port", Int(80)).unwrap();
+        cmap.add("servers/0/host", Str("a".into())).unwrap();
+        cmap.add("ratio", Float(2.0)).unwrap();
+
+        assert_eq!(cmap.to_string(), [
+            "ratio: 2.0",
+            "servers:",
+            "  - host: \"a\"",
+            "    port: 80",
+            "  - - 1",
+            "    - 2",
+            "  - []",
+        ].join("\n"));
+
+        assert_eq!(format!("{:.2}", cmap), "ratio: 2.0\nservers:\n  - {...}\n  - [...]\n  - []");
+        assert_eq!(format!("{:.0}", cmap), "{...}");
+        assert_eq!(CfgMap::new().to_string(), "{}");
+        assert_eq!(Str("a\nb".into()).to_string(), "\"a\\nb\"");
+    }
+
+    #[test]
+    fn nested_secrets() {
+        let mut cmap = CfgMap::new();
+        cmap.add("db", Map(CfgMap::new())).unwrap();
+        cmap.add("db/password", Str("hunter2".into())).unwrap();
+        cmap.add("db/replicas", List(vec![Map(CfgMap::new())])).unwrap();
+        cmap.add("db/replicas/0/password", Str("swordfish".into())).unwrap();
+        cmap.add("port", Int(5432)).unwrap();
+        cmap.mark_secret("db");
+
+        let tree = Tree { secrets: &cmap.secret_paths(), depth: None, lines: Vec::new() };
+        assert!(tree.is_secret("db/password"));
+        assert!(tree.is_secret("db/replicas/0/password"));
+        assert!(!tree.is_secret("dbx"));
+
+        for output in &[cmap.to_string(), format!("{:.1}", cmap), format!("{:.2}", cmap)] {
+            assert!(!output.contains("hunter2"));
+            assert!(!output.contains("swordfish"));
+            assert_eq!(output, "db: ***\nport: 5432");
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use super::{CfgType, ValidationReport};
+use std::fmt;
+
+/// An error produced by a fallible `CfgMap` operation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CfgError {
+    /// The root of a document wasn't a map, such as a JSON object or a TOML table.
+    NotAMap,
+
+    /// A document couldn't be read, or isn't valid for its format.
+    LoadFailed(String),
+
+    /// A document couldn't be written.
+    SaveFailed(String),
+
+    /// A value within a document can't be represented by a `CfgValue`, or a value can't be represented
+    /// by the format a document is being written in.
+    Unrepresentable(String),
+
+    /// A path couldn't be resolved, either because part of it doesn't exist,
+    /// or because it goes through a value that isn't a map or list.
+    InvalidPath(String),
+
+    /// A value would change the type of a path pinned using `CfgMap::pin_type`.
+    TypeMismatch {
+        /// The pinned path.
+        path: String,
+
+        /// The type pinned to the path.
+        expected: CfgType,
+
+        /// The type of the rejected value.
+        found: CfgType,
+    },
+
+    /// A path, or a value within it, was locked using `CfgMap::lock_path`.
+    Locked(String),
+
+    /// The reference at a path refers to a path that doesn't exist, or has other keys
+    /// alongside it while referring to something that isn't a map. For interpolated strings,
+    /// the string refers to a map or list, or has a `${` that's never closed.
+    UnresolvedRef(String),
+
+    /// The reference at a path refers back to a path that's already being resolved.
+    RefCycle(String),
+
+    /// The encrypted string at a path couldn't be decrypted.
+    DecryptFailed {
+        /// The path of the encrypted string.
+        path: String,
+
+        /// The reason given by the decryptor.
+        reason: String,
+    },
+
+    /// The file included at a path couldn't be loaded.
+    IncludeFailed {
+        /// The path of the include.
+        path: String,
+
+        /// The reason given by the loader.
+        reason: String,
+    },
+
+    /// The include at a path includes a file that's already being included.
+    IncludeCycle(String),
+
+    /// The include at a path is nested within too many other includes.
+    IncludeTooDeep(String),
+
+    /// The string at a path refers to an environment variable that isn't set, and has no default.
+    MissingEnvVar {
+        /// The path of the string.
+        path: String,
+
+        /// The name of the environment variable.
+        name: String,
+    },
+
+    /// The value at a path couldn't be converted to or from a typed struct, such as when using `CfgMap::sync`.
+    SyncFailed {
+        /// The path of the value that couldn't be converted.
+        path: String,
+
+        /// The reason given by `serde`, followed by the source of the value, if one was recorded using
+        /// `CfgMap::add_from` or `CfgMap::from_file`.
+        reason: String,
+    },
+
+    /// The version field read by `CfgMap::migrate_to` isn't an integer, or is newer than the latest version.
+    InvalidVersion {
+        /// The path of the version field.
+        path: String,
+
+        /// Why the version is invalid.
+        reason: String,
+    },
+
+    /// A document loaded using `CfgMap::from_file_validated` or `CfgMap::from_reader_validated` doesn't satisfy
+    /// its schema. Warnings and infos alone don't make a document invalid.
+    Invalid(ValidationReport),
+}
+
+impl fmt::Display for CfgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CfgError::NotAMap => write!(f, "the root of the document isn't a map"),
+            CfgError::LoadFailed(reason) => write!(f, "couldn't load the document: {}", reason),
+            CfgError::SaveFailed(reason) => write!(f, "couldn't save the document: {}", reason),
+            CfgError::Unrepresentable(reason) => write!(f, "unrepresentable value: {}", reason),
+            CfgError::InvalidPath(path) => write!(f, "`{}` is an invalid path", path),
+            CfgError::TypeMismatch { path, expected, found } => {
+                write!(f, "`{}` is pinned to {}, but got {}", path, expected, found)
+            },
+            CfgError::Locked(path) => write!(f, "`{}` is locked", path),
+            CfgError::UnresolvedRef(path) => write!(f, "the reference at `{}` can't be resolved", path),
+            CfgError::RefCycle(path) => write!(f, "the reference at `{}` is part of a cycle", path),
+            CfgError::DecryptFailed { path, reason } => write!(f, "couldn't decrypt `{}`: {}", path, reason),
+            CfgError::IncludeFailed { path, reason } => write!(f, "couldn't load the include at `{}`: {}", path, reason),
+            CfgError::IncludeCycle(path) => write!(f, "the include at `{}` is part of a cycle", path),
+            CfgError::IncludeTooDeep(path) => write!(f, "the include at `{}` is nested too deeply", path),
+            CfgError::MissingEnvVar { path, name } => {
+                write!(f, "`{}` refers to the environment variable `{}`, which isn't set", path, name)
+            },
+            CfgError::SyncFailed { path, reason } => write!(f, "couldn't sync `{}`: {}", path, reason),
+            CfgError::InvalidVersion { path, reason } => write!(f, "the version at `{}` is invalid: {}", path, reason),
+            CfgError::Invalid(report) => write!(f, "the document doesn't satisfy its schema:\n{}", report),
+        }
+    }
+}
+
+impl std::error::Error for CfgError {}
@@ -0,0 +1,32 @@
+use super::CfgType;
+
+/// How the value at a path is read, as returned by `CfgMap::explain`.
+///
+/// The value itself isn't included, so that explaining a secret value doesn't reveal it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    /// The path that was explained.
+    pub path: String,
+
+    /// The path the value is actually read from, if it's different, such as the replacement
+    /// of a deprecated path, or a default option.
+    pub read_from: Option<String>,
+
+    /// The type of the value, or `None` if there's no value.
+    pub value_type: Option<CfgType>,
+
+    /// The source that last wrote the value using `CfgMap::add_from`.
+    pub source: Option<String>,
+
+    /// The message the path was deprecated with using `CfgMap::deprecate`.
+    pub deprecation: Option<String>,
+
+    /// Whether the value is locked using `CfgMap::lock_path`.
+    pub locked: bool,
+
+    /// The type the value is pinned to using `CfgMap::pin_type`.
+    pub pinned: Option<CfgType>,
+
+    /// Whether the value is marked as secret using `CfgMap::mark_secret`.
+    pub secret: bool,
+}
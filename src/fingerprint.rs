@@ -0,0 +1,125 @@
+use super::{CfgMap, CfgValue};
+use std::collections::HashSet;
+
+/// A 64-bit FNV-1a hasher. Unlike `DefaultHasher`, its output is guaranteed to be
+/// the same across platforms, Rust versions and runs.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Fnv1a {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_len(&mut self, len: usize) {
+        self.write(&(len as u64).to_le_bytes());
+    }
+
+    fn write_str(&mut self, s: &str) {
+        self.write_len(s.len());
+        self.write(s.as_bytes());
+    }
+}
+
+/// Feeds the canonical encoding of a map into the hasher. Keys are sorted, and any
+/// path within `excluded` is skipped as though it didn't exist.
+fn hash_map(hasher: &mut Fnv1a, map: &CfgMap, path: &str, excluded: &HashSet<String>) {
+    let mut entries: Vec<(&String, String, &CfgValue)> = map.iter()
+        .map(|(k, v)| (k, super::join_path(path, k), v))
+        .filter(|(_, full, _)| !excluded.contains(full))
+        .collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    hasher.write(b"m");
+    hasher.write_len(entries.len());
+    for (key, full, value) in entries {
+        hasher.write_str(key);
+        hash_value(hasher, value, &full, excluded);
+    }
+}
+
+fn hash_value(hasher: &mut Fnv1a, value: &CfgValue, path: &str, excluded: &HashSet<String>) {
+    match value {
+        CfgValue::Int(i) => { hasher.write(b"i"); hasher.write(&i.to_le_bytes()); },
+        CfgValue::UInt(u) => { hasher.write(b"u"); hasher.write(&u.to_le_bytes()); },
+        CfgValue::Float(f) => { hasher.write(b"f"); hasher.write(&f.to_bits().to_le_bytes()); },
+        CfgValue::Str(s) => { hasher.write(b"s"); hasher.write_str(s); },
+        CfgValue::Bool(b) => { hasher.write(b"b"); hasher.write(&[*b as u8]); },
+        CfgValue::Map(map) => hash_map(hasher, map, path, excluded),
+        CfgValue::List(list) => {
+            let elements: Vec<(String, &CfgValue)> = list.iter().enumerate()
+                .map(|(i, v)| (super::join_path(path, &i.to_string()), v))
+                .filter(|(p, _)| !excluded.contains(p))
+                .collect();
+
+            hasher.write(b"l");
+            hasher.write_len(elements.len());
+            for (full, value) in elements {
+                hash_value(hasher, value, &full, excluded);
+            }
+        },
+
+        CfgValue::Opaque(format, source) => {
+            hasher.write(b"o");
+            hasher.write_str(format);
+            hasher.write_len(source.len());
+            hasher.write(source);
+        },
+
+        #[cfg(feature = "from_toml")]
+        CfgValue::Datetime(d) => { hasher.write(b"d"); hasher.write_str(&d.to_string()); },
+
+        #[cfg(any(feature = "from_json", feature = "from_yaml"))]
+        CfgValue::Null => hasher.write(b"n"),
+
+        #[cfg(feature = "from_yaml")]
+        CfgValue::BadValue => hasher.write(b"x"),
+
+        #[cfg(feature = "from_yaml")]
+        CfgValue::Alias(a) => { hasher.write(b"a"); hasher.write_len(*a); },
+    }
+}
+
+/// Fingerprints a whole map, skipping the paths in `excluded`, as well as the runtime information.
+pub(crate) fn of_map(map: &CfgMap, mut excluded: HashSet<String>) -> u64 {
+    excluded.insert(super::META_KEY.to_string());
+
+    let mut hasher = Fnv1a::new();
+    hash_map(&mut hasher, map, "", &excluded);
+    hasher.0
+}
+
+/// Fingerprints a single value.
+pub(crate) fn of_value(value: &CfgValue) -> u64 {
+    let mut hasher = Fnv1a::new();
+    hash_value(&mut hasher, value, "", &HashSet::new());
+    hasher.0
+}
+
+/// Hashes raw bytes.
+pub(crate) fn of_bytes(bytes: &[u8]) -> u64 {
+    let mut hasher = Fnv1a::new();
+    hasher.write(bytes);
+    hasher.0
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CfgMap, CfgValue::*};
+
+    #[test]
+    fn stable() {
+        let mut cmap = CfgMap::new();
+        cmap.add("port", Int(80)).unwrap();
+        cmap.add("hosts", list!["a", "b"]).unwrap();
+
+        // This must never change, as fingerprints are meant to be compared across versions.
+        assert_eq!(cmap.fingerprint(), 14541491032625442673);
+    }
+}
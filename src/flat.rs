@@ -0,0 +1,131 @@
+use super::{CfgMap, CfgValue};
+use std::collections::{BTreeMap, HashMap};
+
+fn flatten_value(prefix: String, value: &CfgValue, sep: &str, out: &mut HashMap<String, CfgValue>) {
+    let children: Vec<(String, &CfgValue)> = match value {
+        CfgValue::Map(map) => map.iter().map(|(k, v)| (k.clone(), v)).collect(),
+        CfgValue::List(list) => list.iter().enumerate().map(|(i, v)| (i.to_string(), v)).collect(),
+        _ => Vec::new(),
+    };
+
+    // Scalars, as well as empty maps and lists, are kept as they are.
+    if children.is_empty() {
+        out.insert(prefix, value.clone());
+        return;
+    }
+
+    for (key, child) in children {
+        flatten_value(format!("{}{}{}", prefix, sep, key), child, sep, out);
+    }
+}
+
+pub(crate) fn flatten(map: &CfgMap, sep: &str) -> HashMap<String, CfgValue> {
+    let mut out = HashMap::new();
+    for (key, value) in map.iter() {
+        flatten_value(key.clone(), value, sep, &mut out);
+    }
+    out
+}
+
+/// An intermediate tree used while unflattening.
+enum Node {
+    Leaf(CfgValue),
+    Branch(BTreeMap<String, Node>),
+}
+
+impl Node {
+    fn insert(&mut self, segments: &[&str], value: CfgValue) -> Option<()> {
+        let branch = match self {
+            Node::Branch(branch) => branch,
+            Node::Leaf(_) => return None,
+        };
+
+        match segments {
+            [] => None,
+            [last] => match branch.insert(last.to_string(), Node::Leaf(value)) {
+                None => Some(()),
+                Some(_) => None,
+            },
+            [first, rest @ ..] => branch.entry(first.to_string())
+                .or_insert_with(|| Node::Branch(BTreeMap::new()))
+                .insert(rest, value),
+        }
+    }
+
+    fn into_map(branch: BTreeMap<String, Node>) -> CfgMap {
+        CfgMap::with_internal_map(branch.into_iter().map(|(k, node)| (k, node.into_value())).collect())
+    }
+
+    fn into_value(self) -> CfgValue {
+        match self {
+            Node::Leaf(value) => value,
+            Node::Branch(branch) => {
+                let mut indexes: Vec<usize> = branch.keys().filter_map(|k| k.parse().ok()).collect();
+                indexes.sort_unstable();
+
+                // A branch is a list only if its keys are exactly `0..n`.
+                if indexes.len() == branch.len() && indexes.iter().enumerate().all(|(i, index)| i == *index) {
+                    let mut elements: Vec<(usize, Node)> = branch.into_iter()
+                        .map(|(k, node)| (k.parse().unwrap(), node))
+                        .collect();
+                    elements.sort_by_key(|(i, _)| *i);
+                    CfgValue::List(elements.into_iter().map(|(_, node)| node.into_value()).collect())
+                } else {
+                    CfgValue::Map(Node::into_map(branch))
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn unflatten(map: HashMap<String, CfgValue>, sep: &str) -> Option<CfgMap> {
+    let mut root = Node::Branch(BTreeMap::new());
+
+    for (key, value) in map {
+        let segments: Vec<&str> = key.split(sep).collect();
+        root.insert(&segments, value)?;
+    }
+
+    match root {
+        Node::Branch(branch) => Some(Node::into_map(branch)),
+        Node::Leaf(_) => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CfgMap, CfgValue::*};
+    use std::collections::HashMap;
+
+    #[test]
+    fn round_trip() {
+        let mut cmap = CfgMap::new();
+        cmap.add("a", Map(CfgMap::new())).unwrap();
+        cmap.add("a/list", List(vec![Map(CfgMap::new()), Int(2)])).unwrap();
+        cmap.add("a/list/0/b", Int(1)).unwrap();
+        cmap.add("a/empty", List(vec![])).unwrap();
+        cmap.add("a/10", Str("not an index".into())).unwrap();
+        cmap.add("c", Bool(true)).unwrap();
+
+        let flat = cmap.flatten(".");
+        let mut keys: Vec<&String> = flat.keys().collect();
+        keys.sort();
+
+        assert_eq!(keys, vec!["a.10", "a.empty", "a.list.0.b", "a.list.1", "c"]);
+        assert_eq!(flat["a.list.0.b"], Int(1));
+        assert_eq!(CfgMap::unflatten(flat, "."), Some(cmap));
+    }
+
+    #[test]
+    fn conflicts() {
+        let mut flat = HashMap::new();
+        flat.insert("a".to_string(), Int(1));
+        flat.insert("a__b".to_string(), Int(2));
+        assert_eq!(CfgMap::unflatten(flat, "__"), None);
+
+        let mut flat = HashMap::new();
+        flat.insert("a__1".to_string(), Int(1));
+        let unflat = CfgMap::unflatten(flat, "__").unwrap();
+        assert!(unflat.get("a").unwrap().is_map());
+    }
+}
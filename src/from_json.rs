@@ -1,37 +1,70 @@
+use super::CfgError;
 use super::CfgMap;
 use super::CfgValue;
-use serde_json::{Value, Map};
+use super::convert::{convert, Node};
+use serde_json::{Map, Number, Value};
 
-fn jsonval_to_cfgval(value: Value) -> CfgValue {
-    match value {
-        Value::Null => CfgValue::Null,
-        Value::Bool(x) => CfgValue::Bool(x),
+fn split_jsonval(value: Value) -> Result<Node<Value>, CfgError> {
+    Ok(match value {
+        Value::Null => Node::Leaf(CfgValue::Null),
+        Value::Bool(x) => Node::Leaf(CfgValue::Bool(x)),
         Value::Number(x) => {
-            if x.is_i64() | x.is_u64() {
-                CfgValue::Int(x.as_i64().unwrap())
+            if let Some(i) = x.as_i64() {
+                Node::Leaf(CfgValue::Int(i))
+            } else if let Some(u) = x.as_u64() {
+                Node::Leaf(CfgValue::UInt(u))
             } else {
-                CfgValue::Float(x.as_f64().unwrap())
+                Node::Leaf(CfgValue::Float(x.as_f64().unwrap()))
             }
         },
-        Value::String(x) => CfgValue::Str(x),
-        Value::Array(x) => {
-            CfgValue::List(x.into_iter().map(|v| jsonval_to_cfgval(v)).collect())
-        },
-        Value::Object(x) => jsonmap_to_cfgval(x)
-    }
+        Value::String(x) => Node::Leaf(CfgValue::Str(x)),
+        Value::Array(x) => Node::List(x),
+        Value::Object(x) => Node::Map(x.into_iter().collect()),
+    })
 }
 
-fn jsonmap_to_cfgval(map: Map<String, Value>) -> CfgValue {
-    CfgValue::Map(CfgMap::with_hashmap(map.into_iter().map(|(k,v)| {
-        (k, jsonval_to_cfgval(v))
-    }).collect()))
+/// Converts any json value into a `CfgValue`.
+pub(crate) fn json_to_value(value: Value) -> Result<CfgValue, CfgError> {
+    convert(value, split_jsonval)
 }
 
 /// Only works if the value is a json `Map`.
-pub(crate) fn json_to_cfg(value: Value) -> CfgMap {
-    if let CfgValue::Map(x) = jsonval_to_cfgval(value) {
-        x
-    } else {
-        panic!("Json value passed wasn't Object.")
+pub(crate) fn json_to_cfg(value: Value) -> Result<CfgMap, CfgError> {
+    if !value.is_object() {
+        return Err(CfgError::NotAMap);
     }
-}
\ No newline at end of file
+
+    match json_to_value(value)? {
+        CfgValue::Map(map) => Ok(map),
+        _ => unreachable!("objects are converted into maps"),
+    }
+}
+
+/// Converts `value` into a json value, where `path` is its path, which is reported if it can't be converted.
+pub(crate) fn cfg_to_json(path: &str, value: &CfgValue) -> Result<Value, CfgError> {
+    Ok(match value {
+        CfgValue::Int(i) => Value::from(*i),
+        CfgValue::UInt(u) => Value::from(*u),
+        CfgValue::Float(f) => Value::Number(Number::from_f64(*f).ok_or_else(|| {
+            CfgError::Unrepresentable(format!("`{}` is {}, which json can't represent", path, f))
+        })?),
+        CfgValue::Str(s) => Value::String(s.clone()),
+        CfgValue::Bool(b) => Value::Bool(*b),
+        CfgValue::Map(map) => Value::Object(map.iter()
+            .map(|(key, value)| Ok((key.clone(), cfg_to_json(&super::join_path(path, key), value)?)))
+            .collect::<Result<Map<_, _>, _>>()?),
+        CfgValue::List(list) => Value::Array(list.iter().enumerate()
+            .map(|(i, value)| cfg_to_json(&super::join_path(path, &i.to_string()), value))
+            .collect::<Result<_, _>>()?),
+        CfgValue::Opaque(format, _) => {
+            return Err(CfgError::Unrepresentable(format!("`{}` is an opaque {} value, which json can't represent", path, format)));
+        },
+        #[cfg(feature = "from_toml")]
+        CfgValue::Datetime(datetime) => Value::String(datetime.to_string()),
+        CfgValue::Null => Value::Null,
+        #[cfg(feature = "from_yaml")]
+        CfgValue::BadValue | CfgValue::Alias(_) => {
+            return Err(CfgError::Unrepresentable(format!("`{}` is a yaml alias or bad value", path)));
+        },
+    })
+}
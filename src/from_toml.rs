@@ -1,32 +1,56 @@
+use super::CfgError;
 use super::CfgMap;
 use super::CfgValue;
-use toml::{value::Value, value::Table};
+use super::convert::{convert, Node};
+use toml::value::Value;
 
-fn tomlval_to_cfgval(value: Value) -> CfgValue {
-    match value {
-        Value::String(x) => CfgValue::Str(x),
-        Value::Integer(x) => CfgValue::Int(x),
-        Value::Float(x) => CfgValue::Float(x),
-        Value::Boolean(x) => CfgValue::Bool(x),
-        Value::Array(x) => {
-            CfgValue::List(x.into_iter().map(|v| tomlval_to_cfgval(v)).collect())
-        },
-        Value::Table(x) => tomlmap_to_cfgval(x),
-        Value::Datetime(x) => CfgValue::Datetime(x),
-    }
-}
-
-fn tomlmap_to_cfgval(map: Table) -> CfgValue {
-    CfgValue::Map(CfgMap::with_hashmap(map.into_iter().map(|(k,v)| {
-        (k, tomlval_to_cfgval(v))
-    }).collect()))
+fn split_tomlval(value: Value) -> Result<Node<Value>, CfgError> {
+    Ok(match value {
+        Value::String(x) => Node::Leaf(CfgValue::Str(x)),
+        Value::Integer(x) => Node::Leaf(CfgValue::Int(x)),
+        Value::Float(x) => Node::Leaf(CfgValue::Float(x)),
+        Value::Boolean(x) => Node::Leaf(CfgValue::Bool(x)),
+        Value::Array(x) => Node::List(x),
+        Value::Table(x) => Node::Map(x.into_iter().collect()),
+        Value::Datetime(x) => Node::Leaf(CfgValue::Datetime(x)),
+    })
 }
 
 /// Only works if the value is a toml `Map`.
-pub(crate) fn toml_to_cfg(value: Value) -> CfgMap {
-    if let CfgValue::Map(x) = tomlval_to_cfgval(value) {
-        x
-    } else {
-        panic!("Toml value passed wasn't a Table.")
+pub(crate) fn toml_to_cfg(value: Value) -> Result<CfgMap, CfgError> {
+    if !value.is_table() {
+        return Err(CfgError::NotAMap);
+    }
+
+    match convert(value, split_tomlval)? {
+        CfgValue::Map(map) => Ok(map),
+        _ => unreachable!("tables are converted into maps"),
     }
-}
\ No newline at end of file
+}
+
+/// Converts `value` into a toml value, where `path` is its path, which is reported if it can't be converted.
+pub(crate) fn cfg_to_toml(path: &str, value: &CfgValue) -> Result<Value, CfgError> {
+    Ok(match value {
+        CfgValue::Int(i) => Value::Integer(*i),
+        CfgValue::UInt(u) => return Err(CfgError::Unrepresentable(format!("`{}` is {}, which toml can't represent", path, u))),
+        CfgValue::Float(f) => Value::Float(*f),
+        CfgValue::Str(s) => Value::String(s.clone()),
+        CfgValue::Bool(b) => Value::Boolean(*b),
+        CfgValue::Map(map) => Value::Table(map.iter()
+            .map(|(key, value)| Ok((key.clone(), cfg_to_toml(&super::join_path(path, key), value)?)))
+            .collect::<Result<_, CfgError>>()?),
+        CfgValue::List(list) => Value::Array(list.iter().enumerate()
+            .map(|(i, value)| cfg_to_toml(&super::join_path(path, &i.to_string()), value))
+            .collect::<Result<_, _>>()?),
+        CfgValue::Opaque(format, _) => {
+            return Err(CfgError::Unrepresentable(format!("`{}` is an opaque {} value, which toml can't represent", path, format)));
+        },
+        CfgValue::Datetime(datetime) => Value::Datetime(datetime.clone()),
+        #[cfg(any(feature = "from_json", feature = "from_yaml"))]
+        CfgValue::Null => return Err(CfgError::Unrepresentable(format!("`{}` is null, which toml can't represent", path))),
+        #[cfg(feature = "from_yaml")]
+        CfgValue::BadValue | CfgValue::Alias(_) => {
+            return Err(CfgError::Unrepresentable(format!("`{}` is a yaml alias or bad value", path)));
+        },
+    })
+}
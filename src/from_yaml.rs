@@ -1,35 +1,96 @@
+use super::CfgError;
 use super::CfgMap;
 use super::CfgValue;
-use yaml_rust::Yaml as Value;
-use yaml_rust::yaml::Hash;
+use super::convert::{convert, Node};
+use yaml_rust::{Yaml as Value, YamlEmitter, YamlLoader};
 
-fn yamlval_to_cfgval(value: Value) -> CfgValue {
-    match value {
-        Value::String(x) => CfgValue::Str(x),
-        Value::Integer(x) => CfgValue::Int(x),
-        Value::Real(x) => CfgValue::Float(x.parse().unwrap()),
-        Value::Boolean(x) => CfgValue::Bool(x),
-        Value::Array(x) => {
-            CfgValue::List(x.into_iter().map(|v| yamlval_to_cfgval(v)).collect())
-        },
-        Value::Hash(x) => yamlmap_to_cfgval(x),
-        Value::Null => CfgValue::Null,
-        Value::BadValue => CfgValue::BadValue,
-        Value::Alias(x) => CfgValue::Alias(x)
-    }
+/// Writes `value` as a yaml document, without its `---` header.
+fn yaml_source(value: &Value) -> Result<Vec<u8>, CfgError> {
+    let mut source = String::new();
+    YamlEmitter::new(&mut source).dump(value)
+        .map_err(|e| CfgError::Unrepresentable(format!("yaml value couldn't be written: {:?}", e)))?;
+
+    Ok(source.trim_start_matches("---").trim_start().as_bytes().to_vec())
 }
 
-fn yamlmap_to_cfgval(map: Hash) -> CfgValue {
-    CfgValue::Map(CfgMap::with_hashmap(map.into_iter().map(|(k,v)| {
-        (k.into_string().unwrap(), yamlval_to_cfgval(v))
-    }).collect()))
+fn split_yamlval(value: Value) -> Result<Node<Value>, CfgError> {
+    Ok(match value {
+        Value::String(x) => Node::Leaf(CfgValue::Str(x)),
+        Value::Integer(x) => Node::Leaf(CfgValue::Int(x)),
+        // Integers too large for an `i64` are read as reals, so they're kept as unsigned integers where possible.
+        Value::Real(x) => match x.parse() {
+            Ok(u) => Node::Leaf(CfgValue::UInt(u)),
+            Err(_) => Node::Leaf(CfgValue::Float(x.parse().map_err(|_| {
+                CfgError::Unrepresentable(format!("real `{}` isn't a valid float", x))
+            })?)),
+        },
+        Value::Boolean(x) => Node::Leaf(CfgValue::Bool(x)),
+        Value::Array(x) => Node::List(x),
+        // Maps with keys that aren't strings are kept as they were written, so that they can be written back.
+        Value::Hash(x) if x.keys().any(|k| k.as_str().is_none()) => {
+            Node::Leaf(CfgValue::Opaque("yaml".into(), yaml_source(&Value::Hash(x))?))
+        },
+        Value::Hash(x) => Node::Map(x.into_iter()
+            .map(|(k, v)| (k.into_string().expect("keys are strings"), v))
+            .collect()),
+        Value::Null => Node::Leaf(CfgValue::Null),
+        Value::BadValue => Node::Leaf(CfgValue::BadValue),
+        Value::Alias(x) => Node::Leaf(CfgValue::Alias(x))
+    })
 }
 
-/// Only works if the value is a json `Map`.
-pub(crate) fn yaml_to_cfg(value: Value) -> CfgMap {
-    if let CfgValue::Map(x) = yamlval_to_cfgval(value) {
-        x
-    } else {
-        panic!("Yaml value passed wasn't a Hash.")
+/// Only works if the value is a yaml `Hash`.
+pub(crate) fn yaml_to_cfg(value: Value) -> Result<CfgMap, CfgError> {
+    match value.as_hash() {
+        None => return Err(CfgError::NotAMap),
+        Some(hash) if hash.keys().any(|k| k.as_str().is_none()) => {
+            return Err(CfgError::Unrepresentable("a root map key isn't a string".into()));
+        },
+        Some(_) => {},
+    }
+
+    match convert(value, split_yamlval)? {
+        CfgValue::Map(map) => Ok(map),
+        _ => unreachable!("hashes are converted into maps"),
     }
-}
\ No newline at end of file
+}
+
+/// Converts `value` into a yaml value, where `path` is its path, which is reported if it can't be converted.
+/// Keys are sorted, so that the same map is always written the same way.
+pub(crate) fn cfg_to_yaml(path: &str, value: &CfgValue) -> Result<Value, CfgError> {
+    Ok(match value {
+        CfgValue::Int(i) => Value::Integer(*i),
+        // Written the same way yaml reads integers too large for an `i64`.
+        CfgValue::UInt(u) => Value::Real(u.to_string()),
+        CfgValue::Float(f) if f.is_nan() => Value::Real(".nan".into()),
+        CfgValue::Float(f) if f.is_infinite() => Value::Real(if *f > 0.0 { ".inf" } else { "-.inf" }.into()),
+        // `Debug` always includes a decimal point, so that the value isn't read back as an integer.
+        CfgValue::Float(f) => Value::Real(format!("{:?}", f)),
+        CfgValue::Str(s) => Value::String(s.clone()),
+        CfgValue::Bool(b) => Value::Boolean(*b),
+        CfgValue::Map(map) => {
+            let mut entries: Vec<(&String, &CfgValue)> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+
+            Value::Hash(entries.into_iter()
+                .map(|(key, value)| Ok((Value::String(key.clone()), cfg_to_yaml(&super::join_path(path, key), value)?)))
+                .collect::<Result<_, CfgError>>()?)
+        },
+        CfgValue::List(list) => Value::Array(list.iter().enumerate()
+            .map(|(i, value)| cfg_to_yaml(&super::join_path(path, &i.to_string()), value))
+            .collect::<Result<_, _>>()?),
+        #[cfg(feature = "from_toml")]
+        CfgValue::Datetime(datetime) => Value::String(datetime.to_string()),
+        CfgValue::Null => Value::Null,
+        CfgValue::BadValue | CfgValue::Alias(_) => {
+            return Err(CfgError::Unrepresentable(format!("`{}` is a yaml alias or bad value", path)));
+        },
+        CfgValue::Opaque(format, source) if format == "yaml" => std::str::from_utf8(source).ok()
+            .and_then(|source| YamlLoader::load_from_str(source).ok())
+            .and_then(|mut documents| documents.pop())
+            .ok_or_else(|| CfgError::Unrepresentable(format!("`{}` is an opaque yaml value that isn't valid yaml", path)))?,
+        CfgValue::Opaque(format, _) => {
+            return Err(CfgError::Unrepresentable(format!("`{}` is an opaque {} value, which yaml can't represent", path, format)));
+        },
+    })
+}
@@ -0,0 +1,44 @@
+use super::CfgMap;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// An immutable `CfgMap`, created by `CfgMap::freeze`.
+///
+/// The map can be read like a normal `CfgMap`, but none of its mutating methods are available.
+/// Cloning it is cheap, as all clones share the same map.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrozenCfgMap {
+    map: Arc<CfgMap>,
+}
+
+impl FrozenCfgMap {
+    pub(crate) fn new(map: CfgMap) -> FrozenCfgMap {
+        FrozenCfgMap { map: Arc::new(map) }
+    }
+
+    /// Creates a mutable copy of the map.
+    ///
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable};
+    ///
+    /// let frozen = CfgMap::new().freeze();
+    ///
+    /// let mut thawed = frozen.thaw();
+    /// thawed.add("port", Int(80));
+    ///
+    /// assert!(frozen.get("port").is_none());
+    /// assert!(thawed.get("port").check_that(IsExactlyInt(80)));
+    /// ```
+    pub fn thaw(&self) -> CfgMap {
+        CfgMap::clone(&self.map)
+    }
+}
+
+impl Deref for FrozenCfgMap {
+    type Target = CfgMap;
+
+    fn deref(&self) -> &CfgMap {
+        &self.map
+    }
+}
@@ -0,0 +1,148 @@
+use super::{CfgError, CfgHashMap, CfgMap, CfgValue};
+use std::fmt;
+
+/// The key that marks a map as including another file.
+const INCLUDE_KEY: &str = "$include";
+
+/// The maximum number of includes that can be nested within each other.
+const MAX_DEPTH: usize = 16;
+
+/// Resolves the includes within a value, loading the included files using `loader`. `stack` contains
+/// the files currently being included, so that files including any of them can be detected as cycles.
+struct Resolver<F> {
+    loader: F,
+    stack: Vec<String>,
+}
+
+impl<E, F> Resolver<F>
+    where E: fmt::Display, F: FnMut(&str) -> Result<CfgMap, E>
+{
+    fn resolve_value(&mut self, path: &str, value: &CfgValue) -> Result<CfgValue, CfgError> {
+        match value {
+            CfgValue::Map(map) => match map.internal_map.get(INCLUDE_KEY) {
+                Some(CfgValue::Str(file)) => {
+                    if self.stack.contains(file) {
+                        return Err(CfgError::IncludeCycle(path.to_string()));
+                    }
+                    if self.stack.len() >= MAX_DEPTH {
+                        return Err(CfgError::IncludeTooDeep(path.to_string()));
+                    }
+
+                    let loaded = (self.loader)(file)
+                        .map_err(|e| CfgError::IncludeFailed { path: path.to_string(), reason: e.to_string() })?;
+
+                    self.stack.push(file.clone());
+                    let mut resolved = self.resolve_map(path, &loaded)?;
+                    self.stack.pop();
+
+                    // Any other keys alongside the include override the keys of the included file.
+                    for (key, value) in map.iter().filter(|(k, _)| *k != INCLUDE_KEY) {
+                        let full = super::join_path(path, key);
+                        resolved.insert(key.clone(), self.resolve_value(&full, value)?);
+                    }
+                    Ok(CfgValue::Map(CfgMap::with_internal_map(resolved)))
+                },
+                _ => Ok(CfgValue::Map(CfgMap::with_internal_map(self.resolve_map(path, map)?))),
+            },
+            CfgValue::List(list) => list.iter().enumerate()
+                .map(|(i, value)| self.resolve_value(&super::join_path(path, &i.to_string()), value))
+                .collect::<Result<_, _>>()
+                .map(CfgValue::List),
+            other => Ok(other.clone()),
+        }
+    }
+
+    fn resolve_map(&mut self, path: &str, map: &CfgMap) -> Result<CfgHashMap, CfgError> {
+        // Keys are resolved in order, so that files are loaded and errors are reported in the same order every time.
+        let mut entries: Vec<(&String, &CfgValue)> = map.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+
+        entries.into_iter()
+            .map(|(key, value)| {
+                self.resolve_value(&super::join_path(path, key), value).map(|value| (key.clone(), value))
+            })
+            .collect()
+    }
+}
+
+/// Gets the contents of `map` with every include resolved using `loader`.
+pub(crate) fn resolve<E, F>(map: &CfgMap, loader: F) -> Result<CfgHashMap, CfgError>
+    where E: fmt::Display, F: FnMut(&str) -> Result<CfgMap, E>
+{
+    Resolver { loader, stack: Vec::new() }.resolve_map("", map)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CfgError, CfgMap, CfgType, CfgValue::*, Checkable, Condition::*};
+
+    fn include(file: &str) -> crate::CfgValue {
+        let mut map = CfgMap::new();
+        map.add("$include", Str(file.into())).unwrap();
+        Map(map)
+    }
+
+    #[test]
+    fn nested_and_cyclic() {
+        let load = |file: &str| -> Result<CfgMap, String> {
+            let mut map = CfgMap::new();
+            match file {
+                "db" => { map.add("port", Int(5432)).unwrap(); map.add("pool", include("pool")).unwrap(); },
+                "pool" => { map.add("size", Int(4)).unwrap(); },
+                "self" => { map.add("again", include("self")).unwrap(); },
+                _ => return Err(format!("{} not found", file)),
+            }
+            Ok(map)
+        };
+
+        let mut cmap = CfgMap::new();
+        cmap.add("dbs", List(vec![include("db")])).unwrap();
+        cmap.resolve_includes(load).unwrap();
+        assert!(cmap.get("dbs/0/port").check_that(IsExactlyInt(5432)));
+        assert!(cmap.get("dbs/0/pool/size").check_that(IsExactlyInt(4)));
+
+        cmap.add("loop", include("self")).unwrap();
+        assert_eq!(cmap.resolve_includes(load), Err(CfgError::IncludeCycle("loop/again".into())));
+
+        cmap.add("loop", include("missing")).unwrap();
+        assert_eq!(cmap.resolve_includes(load), Err(CfgError::IncludeFailed {
+            path: "loop".into(),
+            reason: "missing not found".into(),
+        }));
+    }
+
+    #[test]
+    fn locks_and_pins() {
+        let load = |_: &str| -> Result<CfgMap, String> {
+            let mut map = CfgMap::new();
+            map.add("port", Str("5432".into())).unwrap();
+            Ok(map)
+        };
+
+        let mut cmap = CfgMap::new();
+        cmap.add_force("sec/db", include("db")).unwrap();
+        cmap.lock_path("sec/db");
+        assert_eq!(cmap.resolve_includes(load), Err(CfgError::Locked("sec/db".into())));
+        assert!(cmap.get("sec/db/$include").is_some());
+
+        let mut cmap = CfgMap::new();
+        cmap.add("db", include("db")).unwrap();
+        cmap.pin_type("db/port", CfgType::Int).unwrap();
+        assert!(matches!(cmap.resolve_includes(load), Err(CfgError::TypeMismatch { .. })));
+        assert!(cmap.get("db/$include").is_some());
+    }
+
+    #[test]
+    fn depth_limit() {
+        let mut cmap = CfgMap::new();
+        cmap.add("root", include("0")).unwrap();
+
+        // Every file includes the next one, so the chain never ends.
+        let result = cmap.resolve_includes(|file| -> Result<CfgMap, String> {
+            let mut map = CfgMap::new();
+            map.add("next", include(&(file.parse::<usize>().unwrap() + 1).to_string())).unwrap();
+            Ok(map)
+        });
+        assert!(matches!(result, Err(CfgError::IncludeTooDeep(_))));
+    }
+}
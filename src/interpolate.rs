@@ -0,0 +1,162 @@
+use super::{CfgError, CfgMap, CfgValue};
+use std::collections::HashMap;
+
+/// Expands every `${...}` within `text`, replacing it with whatever `lookup` returns for its contents.
+/// `$$` expands to a single `$`, so that `$${a}` expands to `${a}`. `path` is the path of `text`,
+/// which is reported if a `${` is never closed.
+pub(crate) fn expand<F>(text: &str, path: &str, mut lookup: F) -> Result<String, CfgError>
+    where F: FnMut(&str) -> Result<String, CfgError>
+{
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(after) = rest.strip_prefix("$$") {
+            expanded.push('$');
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("${") {
+            let end = after.find('}').ok_or_else(|| CfgError::UnresolvedRef(path.to_string()))?;
+            expanded.push_str(&lookup(&after[..end])?);
+            rest = &after[end + 1..];
+        } else {
+            expanded.push('$');
+            rest = &rest[1..];
+        }
+    }
+
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Interpolates the strings within a map. `stack` contains the paths currently being interpolated,
+/// so that strings referring back to any of them can be detected as cycles.
+struct Interpolator<'a> {
+    root: &'a CfgMap,
+    done: HashMap<String, String>,
+    stack: Vec<String>,
+}
+
+impl<'a> Interpolator<'a> {
+    /// Gets the interpolated string at `path`, whose original contents are `text`.
+    fn interpolate(&mut self, path: &str, text: &str) -> Result<String, CfgError> {
+        if let Some(done) = self.done.get(path) {
+            return Ok(done.clone());
+        }
+
+        self.stack.push(path.to_string());
+        let interpolated = expand(text, path, |target| self.value_of(path, target))?;
+        self.stack.pop();
+
+        self.done.insert(path.to_string(), interpolated.clone());
+        Ok(interpolated)
+    }
+
+    /// Gets the value at `target` as a string, as referred to by the string at `path`.
+    fn value_of(&mut self, path: &str, target: &str) -> Result<String, CfgError> {
+        if self.stack.iter().any(|p| p == target) {
+            return Err(CfgError::RefCycle(path.to_string()));
+        }
+
+        let root: &'a CfgMap = self.root;
+        match root.get_untracked(target) {
+            Some(CfgValue::Str(text)) => self.interpolate(target, text),
+            Some(CfgValue::Int(i)) => Ok(i.to_string()),
+            Some(CfgValue::UInt(u)) => Ok(u.to_string()),
+            Some(CfgValue::Float(f)) => Ok(f.to_string()),
+            Some(CfgValue::Bool(b)) => Ok(b.to_string()),
+            _ => Err(CfgError::UnresolvedRef(path.to_string())),
+        }
+    }
+}
+
+/// Gets the path and interpolated contents of every string within `map` that changes when interpolated.
+pub(crate) fn interpolate(map: &CfgMap) -> Result<Vec<(String, String)>, CfgError> {
+    let mut interpolator = Interpolator { root: map, done: HashMap::new(), stack: Vec::new() };
+    let mut changed = Vec::new();
+
+    for (path, value) in map.walk() {
+        if let CfgValue::Str(text) = value {
+            let interpolated = interpolator.interpolate(&path, text)?;
+            if interpolated != *text {
+                changed.push((path, interpolated));
+            }
+        }
+    }
+
+    Ok(changed)
+}
+
+/// Gets the path and expanded contents of every string within `map` that changes when expanding
+/// `${NAME}` and `${NAME:-default}` using the variables returned by `var`.
+pub(crate) fn expand_env<F>(map: &CfgMap, mut var: F) -> Result<Vec<(String, String)>, CfgError>
+    where F: FnMut(&str) -> Option<String>
+{
+    let mut changed = Vec::new();
+
+    for (path, value) in map.walk() {
+        if let CfgValue::Str(text) = value {
+            let expanded = expand(text, &path, |reference| {
+                let (name, default) = match reference.find(":-") {
+                    Some(i) => (&reference[..i], Some(&reference[i + 2..])),
+                    None => (reference, None),
+                };
+
+                var(name)
+                    .or_else(|| default.map(str::to_string))
+                    .ok_or_else(|| CfgError::MissingEnvVar { path: path.clone(), name: name.to_string() })
+            })?;
+
+            if expanded != *text {
+                changed.push((path, expanded));
+            }
+        }
+    }
+
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CfgError, CfgMap, CfgValue::*, Checkable, Condition::*};
+
+    #[test]
+    fn nested_escaped_and_cyclic() {
+        let mut cmap = CfgMap::new();
+        cmap.add("host", Str("example.com".into())).unwrap();
+        cmap.add("port", Int(8080)).unwrap();
+        cmap.add("base", Str("https://${host}:${port}".into())).unwrap();
+        cmap.add("urls", list!["${base}/a", "$${base} costs $5"]).unwrap();
+
+        let mut interpolated = cmap.clone();
+        interpolated.interpolate().unwrap();
+        assert!(interpolated.get("urls/0").check_that(IsExactlyStr("https://example.com:8080/a".into())));
+        assert!(interpolated.get("urls/1").check_that(IsExactlyStr("${base} costs $5".into())));
+
+        cmap.add("host", Str("${urls/0}".into())).unwrap();
+        assert_eq!(cmap.interpolate(), Err(CfgError::RefCycle("urls/0".into())));
+
+        cmap.add("host", Str("${missing".into())).unwrap();
+        assert_eq!(cmap.interpolate(), Err(CfgError::UnresolvedRef("host".into())));
+    }
+
+    #[test]
+    fn env_defaults_and_missing() {
+        let var = |name: &str| if name == "HOST" { Some("db.local".to_string()) } else { None };
+
+        let mut cmap = CfgMap::new();
+        cmap.add("url", Str("${HOST}:${PORT:-5432}/${DB:-}".into())).unwrap();
+        cmap.add("raw", Str("$${HOST}".into())).unwrap();
+
+        let mut expanded = cmap.clone();
+        expanded.expand_env_with(var).unwrap();
+        assert!(expanded.get("url").check_that(IsExactlyStr("db.local:5432/".into())));
+        assert!(expanded.get("raw").check_that(IsExactlyStr("${HOST}".into())));
+
+        cmap.add("user", Str("${USER}".into())).unwrap();
+        assert_eq!(cmap.expand_env_with(var), Err(CfgError::MissingEnvVar { path: "user".into(), name: "USER".into() }));
+        assert!(cmap.get("url").check_that(IsExactlyStr("${HOST}:${PORT:-5432}/${DB:-}".into())));
+    }
+}
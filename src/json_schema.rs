@@ -0,0 +1,384 @@
+use super::{CfgMap, CfgValue, Condition::{self, *}, SchemaError, _Float, _Int};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The draft of JSON Schema that exported documents declare using `$schema`.
+pub const JSON_SCHEMA_DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// A condition that `Schema::to_json_schema` couldn't express in JSON Schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unexportable {
+    /// The path of the rule, which is exported as accepting any value instead.
+    pub path: String,
+
+    /// The part of the rule's condition that couldn't be exported, displayed the same way it's written.
+    pub condition: String,
+}
+
+impl fmt::Display for Unexportable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` can't be exported, because of `{}`", self.path, self.condition)
+    }
+}
+
+impl std::error::Error for Unexportable {}
+
+/// Creates a map with the given entries.
+fn object(entries: Vec<(&str, CfgValue)>) -> CfgMap {
+    let mut map = CfgMap::new();
+    map.internal_map_mut().extend(entries.into_iter().map(|(key, value)| (key.to_string(), value)));
+    map
+}
+
+fn typed(kind: &str, mut entries: Vec<(&str, CfgValue)>) -> CfgMap {
+    entries.push(("type", kind.into()));
+    object(entries)
+}
+
+fn length(n: usize) -> CfgValue {
+    CfgValue::Int(n as _Int)
+}
+
+fn strings(strings: &[String]) -> CfgValue {
+    CfgValue::List(strings.iter().map(|s| s.as_str().into()).collect())
+}
+
+/// Escapes `text` so that it's matched literally within a regex.
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        if "\\.+*?()|[]{}^$/".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Converts both conditions into JSON Schemas, listed under `key`.
+fn both<'a>(key: &str, x: &'a Condition, y: &'a Condition) -> Result<CfgMap, &'a Condition> {
+    Ok(object(vec![(key, CfgValue::List(vec![CfgValue::Map(convert(x)?), CfgValue::Map(convert(y)?)]))]))
+}
+
+/// Converts `condition` into a JSON Schema, or returns the part of it that can't be converted.
+fn convert(condition: &Condition) -> Result<CfgMap, &Condition> {
+    Ok(match condition {
+        IsInt => typed("integer", vec![]),
+        IsUInt => typed("integer", vec![("minimum", CfgValue::Int(0))]),
+        IsFloat => typed("number", vec![]),
+        IsStr => typed("string", vec![]),
+        IsList => typed("array", vec![]),
+        IsBool => typed("boolean", vec![]),
+        IsMap => typed("object", vec![]),
+
+        And(x, y) => both("allOf", x, y)?,
+        Or(x, y) => both("anyOf", x, y)?,
+        Xor(x, y) => both("oneOf", x, y)?,
+        Not(x) => object(vec![("not", CfgValue::Map(convert(x)?))]),
+        Implies(x, y) => object(vec![("if", CfgValue::Map(convert(x)?)), ("then", CfgValue::Map(convert(y)?))]),
+        AtLeastN(0, _) | TRUE => CfgMap::new(),
+        AtLeastN(1, s) => object(vec![("anyOf", CfgValue::List(
+            s.iter().map(|c| convert(c).map(CfgValue::Map)).collect::<Result<_, _>>()?
+        ))]),
+        FALSE => object(vec![("not", CfgValue::Map(CfgMap::new()))]),
+
+        IsExactlyInt(i) => typed("integer", vec![("const", CfgValue::Int(*i))]),
+        IsExactlyUInt(u) => typed("integer", vec![("const", CfgValue::UInt(*u))]),
+        IsExactlyFloat(f) => typed("number", vec![("const", CfgValue::Float(*f))]),
+        IsExactlyStr(s) => typed("string", vec![("const", s.as_str().into())]),
+        IsExactlyList(l) => typed("array", vec![("const", CfgValue::List(l.clone()))]),
+        IsExactlyMap(m) => typed("object", vec![("const", CfgValue::Map(m.contents()))]),
+        IsExactlyBool(b) => typed("boolean", vec![("const", CfgValue::Bool(*b))]),
+        IsTrue => typed("boolean", vec![("const", CfgValue::Bool(true))]),
+        IsIntInRange(min, max) => typed("integer", vec![("minimum", CfgValue::Int(*min)), ("maximum", CfgValue::Int(*max))]),
+        IsFloatInRange(min, max) => typed("number", vec![("minimum", CfgValue::Float(*min)), ("maximum", CfgValue::Float(*max))]),
+
+        IsListWith(x) => typed("array", vec![("items", CfgValue::Map(convert(x)?))]),
+        IsListWithAny(x) => typed("array", vec![("contains", CfgValue::Map(convert(x)?))]),
+        IsListWithLength(n) => typed("array", vec![("minItems", length(*n)), ("maxItems", length(*n))]),
+        IsListWithLengthAtLeast(n) => typed("array", vec![("minItems", length(*n))]),
+        IsNonEmptyList => typed("array", vec![("minItems", length(1))]),
+        IsListWithUniqueElements => typed("array", vec![("uniqueItems", CfgValue::Bool(true))]),
+
+        IsStrStartingWith(s) => typed("string", vec![("pattern", format!("^{}", escape(s)).into())]),
+        IsStrEndingWith(s) => typed("string", vec![("pattern", format!("{}$", escape(s)).into())]),
+        IsStrContaining(s) => typed("string", vec![("pattern", escape(s).into())]),
+        IsStrWithLength(n) => typed("string", vec![("minLength", length(*n)), ("maxLength", length(*n))]),
+        IsNonEmptyStr => typed("string", vec![("minLength", length(1))]),
+        IsParsableAsBool => typed("string", vec![("enum", strings(&["true".into(), "false".into()]))]),
+        IsOneOfStr(s) => typed("string", vec![("enum", strings(s))]),
+        IsIn(values) => object(vec![("enum", CfgValue::List(values.clone()))]),
+
+        IsMapWith(x) => typed("object", vec![("additionalProperties", CfgValue::Map(convert(x)?))]),
+        IsMapWithKeyValue(k, x) => typed("object", vec![
+            ("properties", CfgValue::Map(object(vec![(k, CfgValue::Map(convert(x)?))]))),
+            ("required", strings(std::slice::from_ref(k))),
+        ]),
+        HasKey(k) => typed("object", vec![("required", strings(std::slice::from_ref(k)))]),
+        HasKeys(k) => typed("object", vec![("required", strings(k))]),
+        HasOnlyKeys(k) => typed("object", vec![("propertyNames", CfgValue::Map(object(vec![("enum", strings(k))])))]),
+
+        #[cfg(feature = "from_json")]
+        IsNull => typed("null", vec![]),
+
+        #[cfg(feature = "net-validate")]
+        IsUrl => typed("string", vec![("format", "uri".into())]),
+
+        #[cfg(feature = "net-validate")]
+        IsEmail => typed("string", vec![("format", "email".into())]),
+
+        #[cfg(feature = "net-validate")]
+        IsIpAddr => typed("string", vec![("anyOf", CfgValue::List(vec![
+            CfgValue::Map(object(vec![("format", "ipv4".into())])),
+            CfgValue::Map(object(vec![("format", "ipv6".into())])),
+        ]))]),
+
+        #[cfg(feature = "net-validate")]
+        IsPort => typed("integer", vec![("minimum", CfgValue::Int(1)), ("maximum", CfgValue::Int(65535))]),
+
+        #[cfg(feature = "regex")]
+        IsStrMatching(r) => typed("string", vec![("pattern", r.as_str().into())]),
+
+        // These depend on the filesystem, custom code, or parsing that JSON Schema can't describe.
+        condition => return Err(condition),
+    })
+}
+
+/// A map within the exported document, which has a rule of its own, nested rules, or both.
+#[derive(Default)]
+struct Node {
+    schema: Option<CfgMap>,
+    optional: bool,
+    children: BTreeMap<String, Node>,
+}
+
+impl Node {
+    /// Checks whether the node is required, which it is unless its rule is optional. Nodes without a rule of
+    /// their own are required if any of their children are.
+    fn is_required(&self) -> bool {
+        match self.schema {
+            Some(_) => !self.optional,
+            None => self.children.values().any(Node::is_required),
+        }
+    }
+
+    fn into_value(self) -> CfgMap {
+        if self.children.is_empty() {
+            return self.schema.unwrap_or_default();
+        }
+
+        let required = CfgValue::List(self.children.iter()
+            .filter(|(_, node)| node.is_required())
+            .map(|(key, _)| key.as_str().into())
+            .collect());
+        let properties = self.children.into_iter().map(|(key, node)| (key, CfgValue::Map(node.into_value()))).collect();
+        let mut map = typed("object", vec![
+            ("properties", CfgValue::Map(CfgMap::with_hashmap(properties))),
+            ("required", required),
+        ]);
+        if let Some(schema) = self.schema {
+            map.internal_map_mut().insert("allOf".into(), CfgValue::List(vec![CfgValue::Map(schema)]));
+        }
+        map
+    }
+}
+
+/// Exports `rules` as a JSON Schema, where every rule is required unless it's optional, along with every rule that
+/// couldn't be exported.
+pub(crate) fn export<F: Fn(&str) -> bool>(rules: &[(String, Condition)], is_optional: F) -> (CfgMap, Vec<Unexportable>) {
+    let mut root = Node::default();
+    let mut unexportable = Vec::new();
+
+    for (path, condition) in rules {
+        let schema = convert(condition).unwrap_or_else(|part| {
+            unexportable.push(Unexportable { path: path.clone(), condition: part.to_string() });
+            CfgMap::new()
+        });
+
+        let node = path.split('/').fold(&mut root, |node, key| node.children.entry(key.to_string()).or_default());
+        node.schema = Some(schema);
+        node.optional = is_optional(path);
+    }
+
+    let mut document = root.into_value();
+    if !document.contains_key("type") {
+        document.internal_map_mut().insert("type".into(), "object".into());
+    }
+    document.internal_map_mut().insert("$schema".into(), JSON_SCHEMA_DRAFT.into());
+    (document, unexportable)
+}
+
+/// Keywords that only describe a schema, rather than restricting values, so they're skipped when importing.
+const ANNOTATIONS: &[&str] = &[
+    "$schema", "$id", "$comment", "$defs", "definitions", "title", "description", "default", "examples",
+    "deprecated", "readOnly", "writeOnly", "format",
+];
+
+/// A rule imported from a JSON Schema, along with whether it's optional.
+pub(crate) type ImportedRule = (String, Condition, bool);
+
+/// Combines two conditions using `And`, where a missing condition accepts everything.
+fn and(x: Option<Condition>, y: Condition) -> Option<Condition> {
+    Some(match x {
+        Some(x) => And(Box::new(x), Box::new(y)),
+        None => y,
+    })
+}
+
+fn unsupported(path: &str, keyword: &str) -> SchemaError {
+    SchemaError::Unsupported { path: path.to_string(), keyword: keyword.to_string() }
+}
+
+/// Converts a JSON Schema `type` into a condition.
+fn type_condition(kind: &CfgValue, path: &str) -> Result<Condition, SchemaError> {
+    let name = |kind: &CfgValue| -> Result<Condition, SchemaError> {
+        Ok(match kind.as_str().map(String::as_str) {
+            Some("integer") => IsInt,
+            Some("number") => IsFloat | IsInt,
+            Some("string") => IsStr,
+            Some("boolean") => IsBool,
+            Some("array") => IsList,
+            Some("object") => IsMap,
+            #[cfg(feature = "from_json")]
+            Some("null") => IsNull,
+            _ => return Err(unsupported(path, "type")),
+        })
+    };
+
+    match kind {
+        CfgValue::List(kinds) => kinds.iter().map(name).reduce(|x, y| Ok(x? | y?)).unwrap_or(Ok(FALSE)),
+        kind => name(kind),
+    }
+}
+
+/// Converts a schema, which is either a map or a boolean, into a condition for the value at `path`. Properties are
+/// only converted when `nested`, since otherwise they're imported as rules of their own.
+fn condition(schema: &CfgValue, path: &str, nested: bool) -> Result<Condition, SchemaError> {
+    let schema = match schema {
+        CfgValue::Bool(true) => return Ok(TRUE),
+        CfgValue::Bool(false) => return Ok(FALSE),
+        CfgValue::Map(schema) => schema,
+        _ => return Err(SchemaError::NotACondition { path: path.to_string() }),
+    };
+
+    let mut keys: Vec<&String> = schema.keys().collect();
+    keys.sort();
+    if let Some(keyword) = keys.iter().find(|key| {
+        !ANNOTATIONS.contains(&key.as_str())
+            && !["type", "enum", "const", "minimum", "maximum", "items", "properties", "required"].contains(&key.as_str())
+    }) {
+        return Err(unsupported(path, keyword));
+    }
+
+    let kind = schema.get("type");
+    let mut condition = kind.map(|kind| type_condition(kind, path)).transpose()?;
+
+    if let Some(values) = schema.get("enum") {
+        let values = values.as_list().ok_or_else(|| unsupported(path, "enum"))?;
+        let strings: Option<Vec<String>> = values.iter().map(|value| value.as_str().cloned()).collect();
+        condition = and(condition, match strings {
+            Some(strings) => IsOneOfStr(strings),
+            None => IsIn(values.clone()),
+        });
+    }
+    if let Some(value) = schema.get("const") {
+        condition = and(condition, IsIn(vec![value.clone()]));
+    }
+
+    let (minimum, maximum) = (schema.get("minimum"), schema.get("maximum"));
+    if minimum.is_some() || maximum.is_some() {
+        let bound = |value: Option<&CfgValue>, keyword| value.map(|value| value.to_float().ok_or_else(|| unsupported(path, keyword))).transpose();
+        let (min, max) = (bound(minimum, "minimum")?, bound(maximum, "maximum")?);
+        let ints = IsIntInRange(
+            min.map_or(_Int::MIN, |min| min.ceil() as _Int),
+            max.map_or(_Int::MAX, |max| max.floor() as _Int),
+        );
+        let floats = IsFloatInRange(min.unwrap_or(_Float::NEG_INFINITY), max.unwrap_or(_Float::INFINITY));
+
+        // Ranges only apply to a numeric type, since they'd accept any other value in JSON Schema.
+        condition = and(condition, match kind.and_then(CfgValue::as_str).map(String::as_str) {
+            Some("integer") => ints,
+            Some("number") => floats | ints,
+            _ => return Err(unsupported(path, if minimum.is_some() { "minimum" } else { "maximum" })),
+        });
+    }
+
+    if let Some(items) = schema.get("items") {
+        condition = and(condition, IsListWith(Box::new(self::condition(items, &super::join_path(path, "items"), true)?)));
+    }
+
+    if nested {
+        let required = required(schema, path)?;
+        for key in &required {
+            condition = and(condition, HasKey(key.clone()));
+        }
+        if let Some(properties) = schema.get("properties") {
+            let properties = properties.as_map().ok_or_else(|| unsupported(path, "properties"))?;
+            let mut entries: Vec<(&String, &CfgValue)> = properties.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            for (key, property) in entries {
+                let value = IsMapWithKeyValue(key.clone(), Box::new(self::condition(property, &super::join_path(path, key), true)?));
+                condition = and(condition, if required.contains(key) { value } else { Implies(Box::new(HasKey(key.clone())), Box::new(value)) });
+            }
+        }
+    }
+
+    Ok(condition.unwrap_or(TRUE))
+}
+
+/// Gets the keys that a schema requires.
+fn required(schema: &CfgMap, path: &str) -> Result<Vec<String>, SchemaError> {
+    match schema.get("required") {
+        None => Ok(Vec::new()),
+        Some(required) => required.as_list()
+            .and_then(|keys| keys.iter().map(|key| key.as_str().cloned()).collect())
+            .ok_or_else(|| unsupported(path, "required")),
+    }
+}
+
+/// Imports the rules for `schema` at `path`. The properties of a required map become rules of their own, as long as
+/// the map only checks its type and requires some of them, since a missing map is then reported through those. Any
+/// other map is checked by a single rule, since a schema can't have rules both for a path and for the paths within it.
+fn import_at(schema: &CfgValue, path: &str, optional: bool, rules: &mut Vec<ImportedRule>) -> Result<(), SchemaError> {
+    let own = condition(schema, path, false)?;
+    let trivial = matches!(own, TRUE | IsMap);
+    let schema = match schema {
+        CfgValue::Map(schema) if schema.contains_key("properties") || schema.contains_key("required") => schema,
+        _ if path.is_empty() && trivial => return Ok(()),
+        _ => {
+            rules.push((path.to_string(), own, optional));
+            return Ok(());
+        }
+    };
+    let required = required(schema, path)?;
+    if !path.is_empty() && (optional || !trivial || required.is_empty()) {
+        rules.push((path.to_string(), condition(&CfgValue::Map(schema.clone()), path, true)?, optional));
+        return Ok(());
+    } else if !trivial {
+        // There's no rule for the root, which is always a map.
+        return Err(SchemaError::NotACondition { path: path.to_string() });
+    }
+
+    let properties = match schema.get("properties") {
+        Some(properties) => properties.as_map().ok_or_else(|| unsupported(path, "properties"))?.contents(),
+        None => CfgMap::new(),
+    };
+    let mut keys: Vec<&String> = properties.keys().chain(required.iter()).collect();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        if key.contains('/') {
+            return Err(unsupported(&super::join_path(path, key), "properties"));
+        }
+        let property = properties.raw_map().get(key).cloned().unwrap_or(CfgValue::Bool(true));
+        import_at(&property, &super::join_path(path, key), !required.contains(key), rules)?;
+    }
+    Ok(())
+}
+
+/// Imports the rules of a JSON Schema, whose root must describe a map.
+pub(crate) fn import(schema: &CfgMap) -> Result<Vec<ImportedRule>, SchemaError> {
+    let mut rules = Vec::new();
+    import_at(&CfgValue::Map(schema.contents()), "", false, &mut rules)?;
+    Ok(rules)
+}
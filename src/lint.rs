@@ -0,0 +1,251 @@
+//! Lint rules that look for suspicious, but not necessarily invalid, configurations.
+//!
+//! Every rule implements `LintRule`, and can be run through `CfgMap::lint_with`.
+//! `CfgMap::lint` runs all of the rules returned by `default_rules`.
+
+use super::{CfgMap, Checkable, Condition::IsListWithLength};
+use std::collections::HashMap;
+
+/// A single problem found by a `LintRule`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintFinding {
+    /// The name of the rule that produced this finding.
+    pub rule: &'static str,
+
+    /// The full path of the offending value.
+    pub path: String,
+
+    /// A human readable description of the problem.
+    pub message: String,
+}
+
+/// A rule which inspects a whole `CfgMap` and reports any findings.
+pub trait LintRule {
+    /// A short, unique name for the rule, such as `"empty-section"`.
+    fn name(&self) -> &'static str;
+
+    /// Inspects the map and returns all findings.
+    fn check(&self, map: &CfgMap) -> Vec<LintFinding>;
+}
+
+/// Returns all of the rules shipped with this crate.
+pub fn default_rules() -> Vec<Box<dyn LintRule>> {
+    vec![
+        Box::new(EmptySections),
+        Box::new(DuplicateValues),
+        Box::new(SimilarKeys),
+        Box::new(PlaintextSecrets),
+    ]
+}
+
+/// Reports maps and lists that contain nothing.
+pub struct EmptySections;
+
+impl LintRule for EmptySections {
+    fn name(&self) -> &'static str { "empty-section" }
+
+    fn check(&self, map: &CfgMap) -> Vec<LintFinding> {
+        map.get_all("**").into_iter()
+            .filter(|(_, value)| {
+                value.as_map().is_some_and(|map| map.is_empty()) || value.check_that(IsListWithLength(0))
+            })
+            .map(|(path, _)| LintFinding {
+                rule: self.name(),
+                message: format!("`{}` is empty", path),
+                path,
+            })
+            .collect()
+    }
+}
+
+/// Reports string values that are repeated under multiple keys, which usually means
+/// they should be defined once and referenced instead.
+pub struct DuplicateValues;
+
+impl LintRule for DuplicateValues {
+    fn name(&self) -> &'static str { "duplicate-value" }
+
+    fn check(&self, map: &CfgMap) -> Vec<LintFinding> {
+        let mut seen: HashMap<&str, String> = HashMap::new();
+        let mut findings = Vec::new();
+
+        for (path, value) in map.get_all("**") {
+            let s = match value.as_str() {
+                Some(s) if !s.is_empty() => s,
+                _ => continue,
+            };
+
+            match seen.get(s.as_str()) {
+                Some(first) => findings.push(LintFinding {
+                    rule: self.name(),
+                    message: format!("`{}` has the same value as `{}`", path, first),
+                    path,
+                }),
+                None => { seen.insert(s, path); }
+            }
+        }
+
+        findings
+    }
+}
+
+/// Reports sibling keys that are suspiciously similar, such as `timeout` and `timout`,
+/// or `max_size` and `maxSize`. These are usually typos. Keys that only differ by their digits, such as
+/// `node1` and `node2`, or by a trailing `s`, such as `host` and `hosts`, are expected to be different.
+pub struct SimilarKeys;
+
+impl SimilarKeys {
+    fn normalise(key: &str) -> String {
+        key.chars().filter(|c| *c != '_' && *c != '-').flat_map(char::to_lowercase).collect()
+    }
+
+    /// Checks whether the normalised keys `a` and `b` are numbered variants, or a singular and a plural.
+    fn are_variants(a: &str, b: &str) -> bool {
+        let without_digits = |key: &str| key.chars().filter(|c| !c.is_ascii_digit()).collect::<String>();
+        let is_plural = |plural: &str, singular: &str| plural.strip_suffix('s') == Some(singular);
+
+        without_digits(a) == without_digits(b) || is_plural(a, b) || is_plural(b, a)
+    }
+
+    fn are_similar(a: &str, b: &str) -> bool {
+        let (na, nb) = (Self::normalise(a), Self::normalise(b));
+        na == nb || (na.len().min(nb.len()) >= 4 && edit_distance(&na, &nb) <= 1 && !Self::are_variants(&na, &nb))
+    }
+
+    fn check_siblings(&self, path: &str, map: &CfgMap, findings: &mut Vec<LintFinding>) {
+        let mut keys: Vec<&String> = map.keys().collect();
+        keys.sort();
+
+        for (i, a) in keys.iter().enumerate() {
+            for b in keys.iter().skip(i + 1) {
+                if Self::are_similar(a, b) {
+                    let full = if path.is_empty() { b.to_string() } else { format!("{}/{}", path, b) };
+                    findings.push(LintFinding {
+                        rule: self.name(),
+                        message: format!("`{}` is suspiciously similar to its sibling `{}`", full, a),
+                        path: full,
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl LintRule for SimilarKeys {
+    fn name(&self) -> &'static str { "similar-keys" }
+
+    fn check(&self, map: &CfgMap) -> Vec<LintFinding> {
+        let mut findings = Vec::new();
+        self.check_siblings("", map, &mut findings);
+
+        for (path, value) in map.get_all("**") {
+            if let Some(submap) = value.as_map() {
+                self.check_siblings(&path, submap, &mut findings);
+            }
+        }
+
+        findings
+    }
+}
+
+/// Reports keys that look like they hold a secret, such as `password` or `api_key`,
+/// whose value is written out in plain text.
+///
+/// Values that look like references to be filled in later (`${...}`) are ignored.
+pub struct PlaintextSecrets;
+
+impl PlaintextSecrets {
+    const SECRET_NAMES: &'static [&'static str] = &["password", "passwd", "secret", "token", "apikey", "privatekey"];
+
+    fn is_secret_key(key: &str) -> bool {
+        let key = SimilarKeys::normalise(key);
+        Self::SECRET_NAMES.iter().any(|name| key.contains(name))
+    }
+}
+
+impl LintRule for PlaintextSecrets {
+    fn name(&self) -> &'static str { "plaintext-secret" }
+
+    fn check(&self, map: &CfgMap) -> Vec<LintFinding> {
+        map.get_all("**").into_iter()
+            .filter(|(path, value)| {
+                let key = path.rsplit('/').next().unwrap_or(path);
+                Self::is_secret_key(key) && value.as_str().is_some_and(|s| !s.is_empty() && !s.starts_with("${"))
+            })
+            .map(|(path, _)| LintFinding {
+                rule: self.name(),
+                message: format!("`{}` looks like a secret stored in plain text", path),
+                path,
+            })
+            .collect()
+    }
+}
+
+/// Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            curr.push((prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1));
+        }
+        prev = curr;
+    }
+
+    prev[b.len()]
+}
+
+pub(crate) fn lint(map: &CfgMap, rules: &[Box<dyn LintRule>]) -> Vec<LintFinding> {
+    let mut findings: Vec<LintFinding> = rules.iter()
+        .flat_map(|rule| rule.check(map))
+        .filter(|finding| !super::is_meta_path(&finding.path))
+        .collect();
+    findings.sort_by(|a, b| a.path.cmp(&b.path).then(a.rule.cmp(b.rule)));
+    findings
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CfgMap, CfgValue::*};
+    use super::*;
+
+    #[test]
+    fn default_rules() {
+        let mut cmap = CfgMap::new();
+        cmap.add("empty", Map(CfgMap::new())).unwrap();
+        cmap.add("db", Map(CfgMap::new())).unwrap();
+        cmap.add("db/password", Str("hunter2".into())).unwrap();
+        cmap.add("db/timeout", Int(5)).unwrap();
+        cmap.add("db/timout", Int(5)).unwrap();
+        cmap.add("db/host", Str("localhost".into())).unwrap();
+        cmap.add("cache_host", Str("localhost".into())).unwrap();
+        cmap.add("api_token", Str("${TOKEN}".into())).unwrap();
+
+        let findings: Vec<(&str, String)> = cmap.lint().into_iter().map(|f| (f.rule, f.path)).collect();
+
+        assert_eq!(findings, vec![
+            ("duplicate-value", "db/host".to_string()),
+            ("plaintext-secret", "db/password".to_string()),
+            ("similar-keys", "db/timout".to_string()),
+            ("empty-section", "empty".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn similar_keys() {
+        assert!(SimilarKeys::are_similar("max_size", "maxSize"));
+        assert!(SimilarKeys::are_similar("timeout", "timout"));
+        assert!(!SimilarKeys::are_similar("host", "port"));
+        assert!(!SimilarKeys::are_similar("a", "b"));
+        assert!(!SimilarKeys::are_similar("node1", "node2"));
+        assert!(!SimilarKeys::are_similar("node", "node1"));
+        assert!(!SimilarKeys::are_similar("api_v1", "api_v2"));
+        assert!(!SimilarKeys::are_similar("host", "hosts"));
+        assert!(!SimilarKeys::are_similar("replicas", "replica"));
+        assert!(SimilarKeys::are_similar("hosts", "hots"));
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+}
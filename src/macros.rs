@@ -83,6 +83,49 @@ macro_rules! from_str {
     };
 }
 
+#[macro_export]
+/// Creates a `CfgPath` from its segments, separated by `/`. Segments can be strings, or list indexes.
+///
+/// ## Examples:
+/// ```
+/// # use cfgmap::{CfgPath, path};
+/// let p = path!("servers" / 0 / "port");
+///
+/// assert_eq!(p, CfgPath::new("servers/0/port"));
+/// ```
+macro_rules! path {
+    ($($segment:tt)/+) => {
+        $crate::CfgPath::new(&[$(ToString::to_string(&$segment)),+].join("/"))
+    };
+}
+
+#[macro_export]
+#[cfg(feature = "from_toml")]
+/// Asserts that a `CfgMap`, written as toml using `CfgMap::to_writer`, matches the snapshot stored at a path
+/// relative to the crate being tested. Only available if using `from_toml`.
+///
+/// Maps are always written the same way, so the snapshot only changes when the map does. Setting the
+/// `CFGMAP_UPDATE_SNAPSHOTS` environment variable writes the snapshot instead, creating it if it's missing.
+///
+/// ## Panics
+///
+/// Panics if the map doesn't match the snapshot, showing both, if the snapshot can't be read or written,
+/// or if the map can't be written as toml.
+///
+/// ## Examples:
+/// ```no_run
+/// # use cfgmap::{CfgMap, CfgValue::*, assert_cfg_snapshot};
+/// let mut cmap = CfgMap::new();
+/// cmap.add("port", Int(8080)).unwrap();
+///
+/// assert_cfg_snapshot!(cmap, "snapshots/startup.toml");
+/// ```
+macro_rules! assert_cfg_snapshot {
+    ($map:expr, $path:expr $(,)?) => {
+        $crate::__assert_cfg_snapshot(&$map, &::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($path))
+    };
+}
+
 // MACROS for documenting, and implementing "is", "as" and "as_mut".
 
 macro_rules! doc_comment {
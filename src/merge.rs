@@ -0,0 +1,287 @@
+use super::{CfgError, CfgMap, CfgType, CfgValue};
+use std::collections::HashMap;
+
+/// A function that merges an existing value with an incoming one, returning the merged value.
+pub type MergeResolver = fn(&CfgValue, &CfgValue) -> CfgValue;
+
+/// Merges one `CfgMap` into another, using custom resolvers where the built-in behaviour doesn't fit.
+///
+/// By default, values from the incoming map replace existing values, except for maps, which are
+/// merged key by key. Where both maps contain a value for the same path, the resolver registered
+/// for that path is used, or otherwise the resolver registered for the type of both values, if they
+/// have the same type. Keys within the incoming map can also pick how they're merged, see `with_markers`.
+///
+/// Every value that's written is checked the same way as `CfgMap::add`, so merging fails if it would change
+/// a locked path, or the type of a pinned path, in which case nothing is merged.
+///
+/// ## Examples
+/// ```
+/// use cfgmap::{CfgMap, CfgType, CfgValue::*, Checkable, Condition::*, Merger, list, value};
+///
+/// let merger = Merger::new()
+///     .on_path("quota", |a, b| Int(a.as_int().unwrap_or(&0) + b.as_int().unwrap_or(&0)))
+///     .on_type(CfgType::List, |a, b| {
+///         let mut list = a.as_list().cloned().unwrap_or_default();
+///         for value in b.as_list().into_iter().flatten() {
+///             if !list.contains(value) {
+///                 list.push(value.clone());
+///             }
+///         }
+///         List(list)
+///     });
+///
+/// let mut base = CfgMap::new();
+/// base.add("quota", Int(10));
+/// base.add("allow", list!["a", "b"]);
+/// base.add("name", Str("base".into()));
+///
+/// let mut extra = CfgMap::new();
+/// extra.add("quota", Int(5));
+/// extra.add("allow", list!["b", "c"]);
+/// extra.add("name", Str("extra".into()));
+///
+/// merger.merge(&mut base, &extra).unwrap();
+///
+/// assert!(base.get("quota").check_that(IsExactlyInt(15)));
+/// assert!(base.get("allow").check_that(IsExactlyList(vec!["a".into(), "b".into(), "c".into()])));
+/// assert!(base.get("name").check_that(IsExactlyStr("extra".into())));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Merger {
+    paths: HashMap<String, MergeResolver>,
+    types: HashMap<CfgType, MergeResolver>,
+    markers: bool,
+}
+
+/// How a key within the incoming map asked to be merged, using the suffixes enabled by `Merger::with_markers`.
+#[derive(Clone, Copy, PartialEq)]
+enum Marker {
+    None,
+    Override,
+    Append,
+}
+
+impl Merger {
+    /// Creates a merger with only the built-in behaviour.
+    pub fn new() -> Merger {
+        Merger::default()
+    }
+
+    /// Merges the values at `path` using `resolver`.
+    pub fn on_path(mut self, path: &str, resolver: MergeResolver) -> Merger {
+        self.paths.insert(path.trim_end_matches('/').to_string(), resolver);
+        self
+    }
+
+    /// Merges values that are both of type `cfg_type` using `resolver`, unless their path has a resolver of its own.
+    pub fn on_type(mut self, cfg_type: CfgType, resolver: MergeResolver) -> Merger {
+        self.types.insert(cfg_type, resolver);
+        self
+    }
+
+    /// Lets keys within the incoming map control how they're merged, using a suffix that's removed when merging:
+    /// 
+    /// - `key!` replaces the existing value, even if both are maps, without using any resolvers.
+    /// - `key+` appends to the existing value if both are lists, or is merged as usual otherwise.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Checkable, Condition::*, Merger, list, value};
+    /// 
+    /// let mut base = CfgMap::new();
+    /// base.add("hosts", list!["a"]);
+    /// base.add("tls", Map(CfgMap::new()));
+    /// base.add("tls/enabled", Bool(true));
+    /// base.add("tls/cert", Str("cert.pem".into()));
+    /// 
+    /// let mut extra = CfgMap::new();
+    /// extra.add("hosts+", list!["b"]);
+    /// extra.add("tls!", Map(CfgMap::new()));
+    /// extra.add("tls!/enabled", Bool(false));
+    /// 
+    /// Merger::new().with_markers().merge(&mut base, &extra).unwrap();
+    /// 
+    /// assert!(base.get("hosts").check_that(IsExactlyList(vec!["a".into(), "b".into()])));
+    /// assert!(base.get("tls/enabled").check_that(IsExactlyBool(false)));
+    /// assert!(base.get("tls/cert").is_none());
+    /// ```
+    pub fn with_markers(mut self) -> Merger {
+        self.markers = true;
+        self
+    }
+
+    /// Merges every value within `from` into `into`.
+    /// 
+    /// Returns `CfgError::Locked` or `CfgError::TypeMismatch` if any value would be written to a locked path,
+    /// or change the type of a pinned path, leaving `into` unchanged.
+    pub fn merge(&self, into: &mut CfgMap, from: &CfgMap) -> Result<(), CfgError> {
+        let mut merged = into.contents();
+        self.merge_at(Some(into), "", &mut merged, from)?;
+        into.internal_map = merged.internal_map;
+        Ok(())
+    }
+
+    /// Merges every value within `from` into `into` without checking for locks or pins,
+    /// for maps built within the crate that can't have any.
+    pub(crate) fn merge_unchecked(&self, into: &mut CfgMap, from: &CfgMap) {
+        self.merge_at(None, "", into, from).expect("nothing is checked without a guard");
+    }
+
+    /// Splits the marker off of `key`, if markers are enabled.
+    fn split_marker<'k>(&self, key: &'k str) -> (&'k str, Marker) {
+        if !self.markers {
+            return (key, Marker::None);
+        }
+
+        match (key.strip_suffix('!'), key.strip_suffix('+')) {
+            (Some(key), _) => (key, Marker::Override),
+            (_, Some(key)) => (key, Marker::Append),
+            _ => (key, Marker::None),
+        }
+    }
+
+    /// Gets a copy of `value` with the markers of every key within it removed.
+    fn unmarked(&self, path: &str, value: &CfgValue) -> CfgValue {
+        match value {
+            CfgValue::Map(map) if self.markers => {
+                let mut unmarked = CfgMap::new();
+                self.merge_at(None, path, &mut unmarked, map).expect("nothing is checked without a guard");
+                CfgValue::Map(unmarked)
+            },
+            value => value.clone(),
+        }
+    }
+
+    /// Merges `from` into `into`, which is at `path`, checking every write against the locks and pins of `guard`.
+    fn merge_at(&self, guard: Option<&CfgMap>, path: &str, into: &mut CfgMap, from: &CfgMap) -> Result<(), CfgError> {
+        let check = |full: &str, value: &CfgValue| guard.map_or(Ok(()), |guard| guard.check_write(full, value));
+
+        for (key, value) in from.iter() {
+            let (key, marker) = self.split_marker(key);
+            let full = super::join_path(path, key);
+
+            let existing = match into.internal_map.get(key) {
+                Some(existing) => existing,
+                None => {
+                    let value = self.unmarked(&full, value);
+                    check(&full, &value)?;
+                    into.internal_map_mut().insert(key.to_string(), value);
+                    continue;
+                },
+            };
+
+            let merged = match (marker, existing, value) {
+                (Marker::Override, _, value) => self.unmarked(&full, value),
+                (Marker::Append, CfgValue::List(list), CfgValue::List(value)) => {
+                    CfgValue::List(list.iter().chain(value).cloned().collect())
+                },
+                (_, existing, value) => {
+                    let same_type = existing.cfg_type() == value.cfg_type();
+                    let resolver = self.paths.get(&full)
+                        .or_else(|| if same_type { self.types.get(&value.cfg_type()) } else { None });
+
+                    match (resolver, existing, value) {
+                        (Some(resolver), existing, value) => resolver(existing, value),
+                        (None, CfgValue::Map(_), CfgValue::Map(value)) => {
+                            if let Some(CfgValue::Map(existing)) = into.internal_map_mut().get_mut(key) {
+                                self.merge_at(guard, &full, existing, value)?;
+                            }
+                            continue;
+                        },
+                        (None, _, value) => self.unmarked(&full, value),
+                    }
+                },
+            };
+
+            check(&full, &merged)?;
+            into.internal_map_mut().insert(key.to_string(), merged);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CfgError, CfgMap, CfgType, CfgValue::*, Checkable, Condition::*, Merger};
+
+    #[test]
+    fn resolver_priority() {
+        let merger = Merger::new()
+            .on_type(CfgType::Int, |a, b| Int(a.as_int().unwrap() * b.as_int().unwrap()))
+            .on_path("limits/users", |a, b| Int(*a.as_int().unwrap().max(b.as_int().unwrap())));
+
+        let mut into = CfgMap::new();
+        into.add("limits", Map(CfgMap::new())).unwrap();
+        into.add("limits/users", Int(3)).unwrap();
+        into.add("limits/storage", Int(3)).unwrap();
+        into.add("limits/tier", Int(3)).unwrap();
+
+        let mut from = CfgMap::new();
+        from.add("limits", Map(CfgMap::new())).unwrap();
+        from.add("limits/users", Int(4)).unwrap();
+        from.add("limits/storage", Int(4)).unwrap();
+        from.add("limits/tier", Str("gold".into())).unwrap();
+        from.add("limits/seats", Int(4)).unwrap();
+
+        merger.merge(&mut into, &from).unwrap();
+
+        assert!(into.get("limits/users").check_that(IsExactlyInt(4)));
+        assert!(into.get("limits/storage").check_that(IsExactlyInt(12)));
+
+        // Values of different types, or that only exist in one map, use the built-in behaviour.
+        assert!(into.get("limits/tier").check_that(IsExactlyStr("gold".into())));
+        assert!(into.get("limits/seats").check_that(IsExactlyInt(4)));
+    }
+
+    #[test]
+    fn locks_and_pins() {
+        let mut into = CfgMap::new();
+        into.add_force("sec/tls/cert", Str("cert.pem".into())).unwrap();
+        into.add("port", Int(80)).unwrap();
+        into.add("hosts", list!["a"]).unwrap();
+        into.lock_path("sec/tls");
+        into.pin_type("port", CfgType::Int).unwrap();
+        into.pin_type("hosts/0", CfgType::Str).unwrap();
+
+        let merge = |into: &mut CfgMap, key: &str, value| {
+            let mut from = CfgMap::new();
+            from.add_force(key, value).unwrap();
+            Merger::new().with_markers().merge(into, &from)
+        };
+
+        let original = into.clone();
+        assert_eq!(merge(&mut into, "sec/tls/cert", Str("other.pem".into())), Err(CfgError::Locked("sec/tls/cert".into())));
+        assert_eq!(merge(&mut into, "sec/tls/key", Str("key.pem".into())), Err(CfgError::Locked("sec/tls/key".into())));
+        assert!(matches!(merge(&mut into, "port", Str("80".into())), Err(CfgError::TypeMismatch { .. })));
+        assert_eq!(merge(&mut into, "sec!", Map(CfgMap::new())), Err(CfgError::Locked("sec".into())));
+        assert!(matches!(merge(&mut into, "hosts!", list![1]), Err(CfgError::TypeMismatch { .. })));
+        assert_eq!(into, original);
+
+        // Writes that don't touch a locked or pinned path are still merged.
+        assert_eq!(merge(&mut into, "sec/level", Int(2)), Ok(()));
+        assert_eq!(merge(&mut into, "hosts+", list!["b"]), Ok(()));
+        assert!(into.get("sec/level").check_that(IsExactlyInt(2)));
+        assert!(into.get("hosts").check_that(IsListWithLength(2)));
+    }
+
+    #[test]
+    fn pinned_nested_values() {
+        let mut into = CfgMap::new();
+        into.add_force("db/port", Int(5432)).unwrap();
+        into.pin_type("db/port", CfgType::Int).unwrap();
+
+        let mut from = CfgMap::new();
+        from.add_force("db/port", Str("5432".into())).unwrap();
+        from.add_force("db/name", Str("app".into())).unwrap();
+
+        let original = into.clone();
+        let error = Merger::new().merge(&mut into, &from).unwrap_err();
+        assert_eq!(error, CfgError::TypeMismatch { path: "db/port".into(), expected: CfgType::Int, found: CfgType::Str });
+        assert_eq!(into, original);
+
+        from.add("db/port", Int(5433)).unwrap();
+        Merger::new().merge(&mut into, &from).unwrap();
+        assert!(into.get("db/port").check_that(IsExactlyInt(5433)));
+        assert!(into.get("db/name").check_that(IsStr));
+    }
+}
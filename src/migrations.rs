@@ -0,0 +1,84 @@
+use super::{CfgError, CfgMap, CfgValue};
+use std::collections::BTreeMap;
+use std::ops::Bound;
+
+/// A migration registered using `CfgMap::register_migration`.
+pub(crate) type Migration = fn(&mut CfgMap);
+
+/// The migrations registered on a map, ordered by the version they migrate to.
+#[derive(Debug, Clone)]
+pub(crate) struct Migrations {
+    /// The path of the version field, set using `CfgMap::set_version_field`.
+    pub(crate) field: String,
+
+    pub(crate) steps: BTreeMap<super::_Int, Migration>,
+}
+
+impl Default for Migrations {
+    fn default() -> Migrations {
+        Migrations { field: "version".to_string(), steps: BTreeMap::new() }
+    }
+}
+
+/// Runs every migration after the version of `map` up to `latest` in order, setting the version field after each.
+/// Returns the versions of the migrations that were run.
+pub(crate) fn migrate(map: &mut CfgMap, migrations: &Migrations, latest: super::_Int) -> Result<Vec<super::_Int>, CfgError> {
+    let field = &migrations.field;
+    let invalid = |reason: String| CfgError::InvalidVersion { path: field.clone(), reason };
+
+    let current = match map.get_untracked(field) {
+        None => 0,
+        Some(CfgValue::Int(version)) => *version,
+        Some(_) => return Err(invalid("it isn't an integer".into())),
+    };
+    if current > latest {
+        return Err(invalid(format!("version {} is newer than {}", current, latest)));
+    }
+
+    let mut applied = Vec::new();
+    for (version, migration) in migrations.steps.range((Bound::Excluded(current), Bound::Included(latest))) {
+        migration(map);
+        map.add_force(field, CfgValue::Int(*version))?;
+        applied.push(*version);
+    }
+
+    if current < latest {
+        map.add_force(field, CfgValue::Int(latest))?;
+    }
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CfgError, CfgMap, CfgValue::*, Checkable, Condition::*};
+
+    fn rename_port(cmap: &mut CfgMap) {
+        if let Some(port) = cmap.remove("http_port") {
+            cmap.add("server/port", port).unwrap();
+        }
+    }
+
+    fn add_server(cmap: &mut CfgMap) {
+        cmap.add("server", Map(CfgMap::new())).unwrap();
+    }
+
+    #[test]
+    fn ordered_migrations() {
+        let mut cmap = CfgMap::new();
+        cmap.set_version_field("meta/version");
+        cmap.add("http_port", Int(80)).unwrap();
+        cmap.register_migration(3, rename_port);
+        cmap.register_migration(2, add_server);
+
+        assert_eq!(cmap.migrate_to(4), Ok(vec![2, 3]));
+        assert!(cmap.get("server/port").check_that(IsExactlyInt(80)));
+        assert!(cmap.get("meta/version").check_that(IsExactlyInt(4)));
+
+        // Migrations that were already run aren't run again.
+        assert_eq!(cmap.migrate_to(4), Ok(vec![]));
+        assert!(matches!(cmap.migrate_to(1), Err(CfgError::InvalidVersion { .. })));
+
+        cmap.add("meta/version", Str("4".into())).unwrap();
+        assert_eq!(cmap.migrate_to(4).unwrap_err().to_string(), "the version at `meta/version` is invalid: it isn't an integer");
+    }
+}
@@ -0,0 +1,73 @@
+use std::net::IpAddr;
+
+/// Checks whether `label` is a valid domain label, made of letters, digits and hyphens, not starting or ending in a hyphen.
+fn is_label(label: &str) -> bool {
+    !label.is_empty() && label.len() <= 63
+        && !label.starts_with('-') && !label.ends_with('-')
+        && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+/// Checks whether `host` is a domain name made of one or more labels, such as `localhost` or `example.com`.
+fn is_domain(host: &str) -> bool {
+    host.len() <= 253 && host.split('.').all(is_label)
+}
+
+/// Checks whether `url` is an absolute url with a host, such as `https://example.com:8080/path?query`.
+pub(crate) fn is_url(url: &str) -> bool {
+    let (scheme, rest) = match url.split_once("://") {
+        Some(split) => split,
+        None => return false,
+    };
+
+    let valid_scheme = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c));
+    if !valid_scheme || url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return false;
+    }
+
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    // Any user information is ignored.
+    let authority = authority.rsplit('@').next().unwrap_or("");
+
+    let (host, port) = if let Some(ipv6) = authority.strip_prefix('[') {
+        match ipv6.split_once(']') {
+            Some((address, port)) if address.parse::<std::net::Ipv6Addr>().is_ok() => (address, port),
+            _ => return false,
+        }
+    } else {
+        authority.split_at(authority.find(':').unwrap_or(authority.len()))
+    };
+
+    // The port, if any, still starts with its colon.
+    let port = port.is_empty() || port.strip_prefix(':').is_some_and(|port| port.parse::<u16>().is_ok());
+
+    port && (is_domain(host) || host.parse::<IpAddr>().is_ok())
+}
+
+/// Checks whether `email` is an email address, such as `admin@example.com`. This is a basic check
+/// of its shape rather than a full validation against RFC 5322.
+pub(crate) fn is_email(email: &str) -> bool {
+    match email.split_once('@') {
+        Some((local, domain)) => !local.is_empty() && local.len() <= 64
+            && !local.starts_with('.') && !local.ends_with('.') && !local.contains("..")
+            && local.chars().all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~.".contains(c))
+            && domain.contains('.') && is_domain(domain),
+        None => false,
+    }
+}
+
+/// Checks whether `cidr` is an ip address followed by a prefix length, such as `10.0.0.0/8` or `fd00::/8`.
+pub(crate) fn is_cidr(cidr: &str) -> bool {
+    let (address, prefix) = match cidr.split_once('/') {
+        Some(split) => split,
+        None => return false,
+    };
+
+    let max = match address.parse::<IpAddr>() {
+        Ok(IpAddr::V4(_)) => 32,
+        Ok(IpAddr::V6(_)) => 128,
+        Err(_) => return false,
+    };
+
+    prefix.chars().all(|c| c.is_ascii_digit()) && prefix.parse::<u8>().is_ok_and(|prefix| prefix <= max)
+}
@@ -0,0 +1,181 @@
+use super::{CfgError, CfgMap, CfgValue, Condition};
+use std::ops::Deref;
+
+type Callback = Box<dyn FnMut(&str, Option<&CfgValue>, Option<&CfgValue>)>;
+
+/// A `CfgMap` that notifies subscribers whenever a path they're subscribed to changes.
+///
+/// The map can be read like a normal `CfgMap`, but has to be changed through the methods of the wrapper,
+/// so that subscribers can be notified. Subscribers are notified when their path changes, whether that's
+/// because the value itself changed, something within it changed, or a map containing it was replaced.
+/// Each subscriber is called with its path, along with the old and new value, where `None` means there was no value.
+///
+/// ## Examples
+/// ```
+/// use cfgmap::{CfgMap, CfgValue::*, ObservedCfgMap};
+/// use std::{cell::RefCell, rc::Rc};
+///
+/// let mut cmap = CfgMap::new();
+/// cmap.add("server", Map(CfgMap::new()));
+/// cmap.add("server/port", Int(80));
+///
+/// let changes = Rc::new(RefCell::new(Vec::new()));
+/// let mut observed = ObservedCfgMap::new(cmap);
+///
+/// let recorded = changes.clone();
+/// observed.subscribe("server/port", move |_, old, new| {
+///     recorded.borrow_mut().push((old.cloned(), new.cloned()));
+/// });
+///
+/// observed.add("server/port", Int(8080));
+/// observed.add("server/host", Str("localhost".into()));
+/// observed.remove("server");
+///
+/// assert_eq!(*changes.borrow(), vec![
+///     (Some(Int(80)), Some(Int(8080))),
+///     (Some(Int(8080)), None),
+/// ]);
+/// ```
+pub struct ObservedCfgMap {
+    map: CfgMap,
+    subscribers: Vec<(usize, String, Callback)>,
+    next_id: usize,
+}
+
+impl ObservedCfgMap {
+    /// Wraps `map`, without any subscribers.
+    pub fn new(map: CfgMap) -> ObservedCfgMap {
+        ObservedCfgMap { map, subscribers: Vec::new(), next_id: 0 }
+    }
+
+    /// Subscribes `callback` to changes of `path`, returning an id that can be passed to `unsubscribe`.
+    pub fn subscribe<F>(&mut self, path: &str, callback: F) -> usize
+        where F: FnMut(&str, Option<&CfgValue>, Option<&CfgValue>) + 'static
+    {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.subscribers.push((id, path.to_string(), Box::new(callback)));
+        id
+    }
+
+    /// Removes the subscriber with the `id` returned by `subscribe`. Returns whether it existed.
+    pub fn unsubscribe(&mut self, id: usize) -> bool {
+        let before = self.subscribers.len();
+        self.subscribers.retain(|(other, _, _)| *other != id);
+        self.subscribers.len() != before
+    }
+
+    /// Calls `CfgMap::add`, notifying subscribers of any changes.
+    pub fn add(&mut self, key: &str, value: CfgValue) -> Result<Option<CfgValue>, CfgError> {
+        self.observe(Some(&[key]), |map| map.add(key, value))
+    }
+
+    /// Calls `CfgMap::update_option`, notifying subscribers of any changes.
+    pub fn update_option(&mut self, category: &str, option: &str, to: CfgValue) -> Result<Option<CfgValue>, CfgError> {
+        let fullkey = format!("{}/{}", category, option);
+        let defaults = self.map.default_paths(option);
+        let paths: Vec<&str> = std::iter::once(fullkey.as_str()).chain(defaults.iter().map(String::as_str)).collect();
+
+        self.observe(Some(&paths), |map| map.update_option(category, option, to))
+    }
+
+    /// Calls `CfgMap::remove`, notifying subscribers of any changes.
+    pub fn remove(&mut self, key: &str) -> Option<CfgValue> {
+        self.observe(Some(&[key]), |map| map.remove(key))
+    }
+
+    /// Calls `CfgMap::remove_if`, notifying subscribers of any changes.
+    pub fn remove_if(&mut self, key: &str, condition: Condition) -> Option<CfgValue> {
+        self.observe(Some(&[key]), |map| map.remove_if(key, condition))
+    }
+
+    /// Calls `CfgMap::remove_entry`, notifying subscribers of any changes.
+    pub fn remove_entry(&mut self, key: &str) -> Option<(String, CfgValue)> {
+        self.observe(Some(&[key]), |map| map.remove_entry(key))
+    }
+
+    /// Calls `CfgMap::remove_entry_if`, notifying subscribers of any changes.
+    pub fn remove_entry_if(&mut self, key: &str, condition: Condition) -> Option<(String, CfgValue)> {
+        self.observe(Some(&[key]), |map| map.remove_entry_if(key, condition))
+    }
+
+    /// Replaces the whole map with `map`, such as after reloading it, notifying subscribers of any changes.
+    pub fn reload(&mut self, map: CfgMap) {
+        self.observe(None, |old| *old = map)
+    }
+
+    /// Unwraps the map, dropping all subscribers.
+    pub fn into_inner(self) -> CfgMap {
+        self.map
+    }
+
+    /// Applies `change` onto the map, notifying every subscriber whose value changed. Only subscribers
+    /// related to one of the `touched` paths are checked, or all of them if it's `None`.
+    fn observe<R, F: FnOnce(&mut CfgMap) -> R>(&mut self, touched: Option<&[&str]>, change: F) -> R {
+        let related = |path: &str| touched.is_none_or(|touched| touched.iter().any(|t| {
+            *t == path || super::is_within(path, t) || super::is_within(t, path)
+        }));
+
+        let before: Vec<(usize, Option<CfgValue>)> = self.subscribers.iter()
+            .enumerate()
+            .filter(|(_, (_, path, _))| related(path))
+            .map(|(i, (_, path, _))| (i, self.map.get_untracked(path).cloned()))
+            .collect();
+
+        let result = change(&mut self.map);
+
+        for (i, old) in before {
+            let (_, path, callback) = &mut self.subscribers[i];
+            let new = self.map.get_untracked(path);
+
+            if old.as_ref() != new {
+                callback(path, old.as_ref(), new);
+            }
+        }
+
+        result
+    }
+}
+
+impl Deref for ObservedCfgMap {
+    type Target = CfgMap;
+
+    fn deref(&self) -> &CfgMap {
+        &self.map
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CfgMap, CfgValue::*, ObservedCfgMap};
+    use std::{cell::RefCell, rc::Rc};
+
+    #[test]
+    fn notifications() {
+        let mut cmap = CfgMap::new();
+        cmap.add("server", Map(CfgMap::new())).unwrap();
+        cmap.add("server/port", Int(80)).unwrap();
+        cmap.add("list", list![1]).unwrap();
+
+        let paths = Rc::new(RefCell::new(Vec::new()));
+        let mut observed = ObservedCfgMap::new(cmap.clone());
+
+        for path in &["server", "list", "missing"] {
+            let paths = paths.clone();
+            observed.subscribe(path, move |path, _, _| paths.borrow_mut().push(path.to_string()));
+        }
+        let unsubscribed = observed.subscribe("server", |_, _, _| panic!("unsubscribed"));
+        assert!(observed.unsubscribe(unsubscribed));
+
+        // Unchanged values don't notify.
+        observed.add("server/port", Int(80)).unwrap();
+        observed.add("server/port", Int(8080)).unwrap();
+        observed.add("list/-", Int(2)).unwrap();
+        observed.update_option("server", "port", Int(1)).unwrap();
+        assert_eq!(*paths.borrow(), vec!["server", "list", "server"]);
+
+        paths.borrow_mut().clear();
+        observed.reload(cmap);
+        assert_eq!(*paths.borrow(), vec!["server", "list"]);
+    }
+}
@@ -0,0 +1,123 @@
+use super::{CfgMap, CfgValue};
+use std::ops::{Deref, DerefMut};
+
+/// A guard over a `CfgMap` with temporary overrides applied, created by `CfgMap::overlay`.
+///
+/// The guard can be used like the map itself. When it's dropped, every overridden path
+/// is restored to its original value, and any path that didn't exist before is removed,
+/// even if the path was locked or pinned to another type within the scope.
+pub struct Overlay<'a> {
+    map: &'a mut CfgMap,
+
+    /// The original values of every overridden path, in the order they were overridden.
+    originals: Vec<(String, Option<CfgValue>)>,
+}
+
+impl<'a> Overlay<'a> {
+    pub(crate) fn new<'b, I>(map: &'a mut CfgMap, changes: I) -> Option<Overlay<'a>>
+        where I: IntoIterator<Item = (&'b str, CfgValue)>
+    {
+        let mut overlay = Overlay { map, originals: Vec::new() };
+
+        for (path, value) in changes {
+            // Appending to a list changes the list itself, so that's what needs restoring.
+            let restored = path.strip_suffix("/-").unwrap_or(path);
+            let original = overlay.map.get_untracked(restored).cloned();
+
+            // If this fails, dropping the overlay restores any changes applied so far.
+            overlay.map.add(path, value).ok()?;
+            overlay.originals.push((restored.to_string(), original));
+        }
+
+        Some(overlay)
+    }
+}
+
+impl Deref for Overlay<'_> {
+    type Target = CfgMap;
+
+    fn deref(&self) -> &CfgMap {
+        self.map
+    }
+}
+
+impl DerefMut for Overlay<'_> {
+    fn deref_mut(&mut self) -> &mut CfgMap {
+        self.map
+    }
+}
+
+impl Drop for Overlay<'_> {
+    fn drop(&mut self) {
+        // Paths locked or pinned within the scope are still restored.
+        for (path, original) in self.originals.drain(..).rev() {
+            self.map.restore(&path, original);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CfgMap, CfgValue::*, Checkable, Condition::*};
+
+    fn cmap() -> CfgMap {
+        let mut cmap = CfgMap::new();
+        cmap.add("server", Map(CfgMap::new())).unwrap();
+        cmap.add("server/port", Int(80)).unwrap();
+        cmap.add("plugins", list!["a"]).unwrap();
+        cmap
+    }
+
+    #[test]
+    fn restores_on_drop() {
+        let mut original = cmap();
+
+        {
+            let mut overlay = original.overlay(vec![
+                ("server/port", Int(8080)),
+                ("server/host", Str("test".into())),
+                ("plugins/-", Str("b".into())),
+            ]).unwrap();
+
+            assert!(overlay.get("server/port").check_that(IsExactlyInt(8080)));
+            assert!(overlay.get("server/host").check_that(IsStr));
+            assert!(overlay.get("plugins").check_that(IsListWithLength(2)));
+
+            overlay.add("server/port", Int(1)).unwrap();
+        }
+
+        assert_eq!(original, cmap());
+    }
+
+    #[test]
+    fn failed_changes_are_rolled_back() {
+        let mut original = cmap();
+
+        assert!(original.overlay(vec![
+            ("server/port", Int(8080)),
+            ("missing/port", Int(8080)),
+        ]).is_none());
+
+        assert_eq!(original, cmap());
+    }
+
+    #[test]
+    fn restores_locked_paths() {
+        let mut original = cmap();
+
+        {
+            let mut overlay = original.overlay(vec![
+                ("server/port", Int(8080)),
+                ("server/host", Str("test".into())),
+            ]).unwrap();
+
+            overlay.lock_path("server/port");
+            overlay.lock_path("server/host");
+            assert!(overlay.get_mut("server/port").is_none());
+        }
+
+        assert!(original.get("server/port").check_that(IsExactlyInt(80)));
+        assert!(original.get("server/host").is_none());
+        assert!(original.is_locked("server/port"));
+    }
+}
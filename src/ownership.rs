@@ -0,0 +1,57 @@
+use super::CfgValue;
+use std::collections::HashMap;
+
+/// A path that was written by more than one source with differing values, found by `CfgMap::detect_conflicts`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    /// The path that was written.
+    pub path: String,
+
+    /// Every write to the path, in order, along with the name of the source that made it.
+    pub writes: Vec<(String, CfgValue)>,
+}
+
+/// Checks whether two different sources wrote differing values.
+fn is_conflict(writes: &[(String, CfgValue)]) -> bool {
+    writes.iter().enumerate().any(|(i, (source, value))| {
+        writes[i + 1..].iter().any(|(other, other_value)| source != other && value != other_value)
+    })
+}
+
+/// Finds every conflicting path within `writes`, sorted by path.
+pub(crate) fn conflicts(writes: &HashMap<String, Vec<(String, CfgValue)>>) -> Vec<Conflict> {
+    let mut conflicts: Vec<Conflict> = writes.iter()
+        .filter(|(_, writes)| is_conflict(writes))
+        .map(|(path, writes)| Conflict { path: path.clone(), writes: writes.clone() })
+        .collect();
+
+    conflicts.sort_by(|a, b| a.path.cmp(&b.path));
+    conflicts
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CfgMap, CfgValue::*};
+
+    #[test]
+    fn conflicts() {
+        let mut cmap = CfgMap::new();
+        cmap.add_from("file", "port", Int(80)).unwrap();
+        cmap.add_from("env", "port", Int(8080)).unwrap();
+        cmap.add_from("file", "host", Str("a".into())).unwrap();
+        cmap.add_from("file", "host", Str("b".into())).unwrap();
+        cmap.add_from("file", "debug", Bool(true)).unwrap();
+        cmap.add_from("env", "debug", Bool(true)).unwrap();
+
+        // Failed writes aren't recorded.
+        assert!(cmap.add_from("env", "missing/port", Int(1)).is_err());
+
+        let conflicts = cmap.detect_conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].path, "port");
+        assert_eq!(conflicts[0].writes, vec![("file".to_string(), Int(80)), ("env".to_string(), Int(8080))]);
+
+        assert_eq!(cmap.source_of("port"), Some("env"));
+        assert_eq!(cmap.source_of("missing/port"), None);
+    }
+}
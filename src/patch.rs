@@ -0,0 +1,285 @@
+use super::{CfgError, CfgMap, CfgType, CfgValue, ValidationReport};
+use super::pointer;
+use std::fmt;
+
+/// An error that occurred while applying a JSON Patch.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchError {
+    /// The patch, or one of its operations, is malformed.
+    Malformed(String),
+
+    /// The operation at `index` referenced a path that doesn't exist.
+    PathNotFound { index: usize, path: String },
+
+    /// The `test` operation at `index` didn't match.
+    TestFailed { index: usize, path: String },
+
+    /// The patch would change a path locked with `CfgMap::lock_path`.
+    Locked(String),
+
+    /// The patch would change the type of a path pinned with `CfgMap::pin_type`.
+    TypeMismatch { path: String, expected: CfgType, found: CfgType },
+
+    /// The patched map was rejected for any other reason.
+    Rejected(CfgError),
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchError::Malformed(reason) => write!(f, "malformed patch: {}", reason),
+            PatchError::PathNotFound { index, path } => write!(f, "operation {}: path `{}` not found", index, path),
+            PatchError::TestFailed { index, path } => write!(f, "operation {}: test at `{}` failed", index, path),
+            PatchError::Locked(path) => write!(f, "path `{}` is locked", path),
+            PatchError::TypeMismatch { path, expected, found } => {
+                write!(f, "path `{}` is pinned to {}, but got {}", path, expected, found)
+            },
+            PatchError::Rejected(error) => write!(f, "patch rejected: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+/// An error produced by `CfgMap::simulate`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimulateError {
+    /// The patch couldn't be applied, for the same reason `CfgMap::apply_patch` would fail.
+    Patch(PatchError),
+
+    /// The patch could be applied, but the patched map doesn't satisfy the schema.
+    Invalid(ValidationReport),
+}
+
+impl fmt::Display for SimulateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimulateError::Patch(error) => error.fmt(f),
+            SimulateError::Invalid(report) => write!(f, "the patched map is invalid:\n{}", report),
+        }
+    }
+}
+
+impl std::error::Error for SimulateError {}
+
+impl From<PatchError> for SimulateError {
+    fn from(error: PatchError) -> SimulateError {
+        SimulateError::Patch(error)
+    }
+}
+
+impl From<ValidationReport> for SimulateError {
+    fn from(report: ValidationReport) -> SimulateError {
+        SimulateError::Invalid(report)
+    }
+}
+
+/// Adds `value` at `tokens`, inserting into lists and replacing map entries.
+fn add(doc: &mut CfgMap, tokens: &[String], value: CfgValue) -> Option<()> {
+    let (last, parent) = match tokens.split_last() {
+        Some(split) => split,
+        None => {
+            doc.internal_map = value.as_map()?.internal_map.clone();
+            return Some(());
+        }
+    };
+
+    if parent.is_empty() {
+        doc.internal_map_mut().insert(last.clone(), value);
+        return Some(());
+    }
+
+    match pointer::get_mut(doc, parent)? {
+        CfgValue::Map(map) => { map.internal_map_mut().insert(last.clone(), value); },
+        CfgValue::List(list) if last == "-" => list.push(value),
+        CfgValue::List(list) => {
+            let index = pointer::parse_index(last).filter(|i| *i <= list.len())?;
+            list.insert(index, value);
+        },
+        _ => return None
+    }
+
+    Some(())
+}
+
+/// Removes the value at `tokens`, returning it.
+fn remove(doc: &mut CfgMap, tokens: &[String]) -> Option<CfgValue> {
+    let (last, parent) = tokens.split_last()?;
+
+    if parent.is_empty() {
+        return doc.internal_map_mut().remove(last);
+    }
+
+    match pointer::get_mut(doc, parent)? {
+        CfgValue::Map(map) => map.internal_map_mut().remove(last),
+        CfgValue::List(list) => {
+            let index = pointer::parse_index(last).filter(|i| *i < list.len())?;
+            Some(list.remove(index))
+        },
+        _ => None
+    }
+}
+
+/// Gets a copy of the value at `tokens`. The empty pointer refers to the whole map.
+fn get(doc: &CfgMap, tokens: &[String]) -> Option<CfgValue> {
+    if tokens.is_empty() {
+        Some(CfgValue::Map(doc.clone()))
+    } else {
+        pointer::get(doc, tokens).cloned()
+    }
+}
+
+/// Applies a single operation onto `doc`.
+fn apply_operation(doc: &mut CfgMap, index: usize, operation: &CfgValue) -> Result<(), PatchError> {
+    let member = |name: &str| operation.get(name);
+    let pointer_member = |name: &str| -> Result<(String, Vec<String>), PatchError> {
+        let path = member(name).and_then(|p| p.as_str())
+            .ok_or_else(|| PatchError::Malformed(format!("operation {} has no `{}` string", index, name)))?;
+        let tokens = pointer::parse(path)
+            .ok_or_else(|| PatchError::Malformed(format!("operation {} has an invalid pointer `{}`", index, path)))?;
+        Ok((path.clone(), tokens))
+    };
+    let value_member = || member("value").cloned()
+        .ok_or_else(|| PatchError::Malformed(format!("operation {} has no `value`", index)));
+
+    let op = member("op").and_then(|op| op.as_str())
+        .ok_or_else(|| PatchError::Malformed(format!("operation {} has no `op` string", index)))?;
+    let (path, tokens) = pointer_member("path")?;
+    let not_found = |path: String| PatchError::PathNotFound { index, path };
+
+    match op.as_str() {
+        "add" => add(doc, &tokens, value_member()?).ok_or_else(|| not_found(path)),
+        "remove" => remove(doc, &tokens).map(|_| ()).ok_or_else(|| not_found(path)),
+        "replace" => {
+            let value = value_member()?;
+            if tokens.is_empty() {
+                add(doc, &tokens, value).ok_or_else(|| not_found(path))
+            } else {
+                pointer::get_mut(doc, &tokens)
+                    .map(|old| *old = value)
+                    .ok_or_else(|| not_found(path))
+            }
+        },
+        "move" => {
+            let (from, from_tokens) = pointer_member("from")?;
+            if from_tokens == tokens {
+                return get(doc, &tokens).map(|_| ()).ok_or_else(|| not_found(from));
+            }
+            if tokens.starts_with(&from_tokens) {
+                return Err(PatchError::Malformed(format!("operation {} moves `{}` into itself", index, from)));
+            }
+            let value = remove(doc, &from_tokens).ok_or_else(|| not_found(from))?;
+            add(doc, &tokens, value).ok_or_else(|| not_found(path))
+        },
+        "copy" => {
+            let (from, from_tokens) = pointer_member("from")?;
+            let value = get(doc, &from_tokens).ok_or_else(|| not_found(from))?;
+            add(doc, &tokens, value).ok_or_else(|| not_found(path))
+        },
+        "test" => {
+            let expected = value_member()?;
+            match get(doc, &tokens) {
+                Some(actual) if actual == expected => Ok(()),
+                Some(_) => Err(PatchError::TestFailed { index, path }),
+                None => Err(not_found(path)),
+            }
+        },
+        other => Err(PatchError::Malformed(format!("operation {} has an unknown op `{}`", index, other)))
+    }
+}
+
+/// Applies all operations in `patch` onto a copy of `doc`, returning the copy if all of them succeed.
+pub(crate) fn apply(doc: &CfgMap, patch: &CfgValue) -> Result<CfgMap, PatchError> {
+    let operations = patch.as_list()
+        .ok_or_else(|| PatchError::Malformed("patch isn't a list of operations".into()))?;
+
+    let mut patched = doc.clone();
+    for (index, operation) in operations.iter().enumerate() {
+        apply_operation(&mut patched, index, operation)?;
+    }
+
+    Ok(patched)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CfgMap, CfgType, CfgValue, CfgValue::*, Checkable, Condition::*, PatchError, Schema, SimulateError};
+
+    fn op(op: &str, path: &str, extra: Vec<(&str, CfgValue)>) -> CfgValue {
+        let mut map = CfgMap::new();
+        map.add("op", op.into()).unwrap();
+        map.add("path", path.into()).unwrap();
+        for (k, v) in extra {
+            map.add(k, v).unwrap();
+        }
+        Map(map)
+    }
+
+    fn doc() -> CfgMap {
+        let mut cmap = CfgMap::new();
+        cmap.add("server", Map(CfgMap::new())).unwrap();
+        cmap.add("server/port", Int(80)).unwrap();
+        cmap.add("plugins", list!["a", "c"]).unwrap();
+        cmap
+    }
+
+    #[test]
+    fn operations() {
+        let mut cmap = doc();
+        let patch = List(vec![
+            op("add", "/plugins/1", vec![("value", "b".into())]),
+            op("add", "/plugins/-", vec![("value", "d".into())]),
+            op("replace", "/server/port", vec![("value", Int(443))]),
+            op("copy", "/backup", vec![("from", "/server".into())]),
+            op("move", "/server/host", vec![("from", "/plugins/3".into())]),
+            op("remove", "/plugins/0", vec![]),
+            op("test", "/backup/port", vec![("value", Int(443))]),
+        ]);
+
+        assert_eq!(cmap.apply_patch(&patch), Ok(()));
+        assert!(cmap.get("plugins").check_that(IsExactlyList(vec!["b".into(), "c".into()])));
+        assert!(cmap.get("server/port").check_that(IsExactlyInt(443)));
+        assert!(cmap.get("server/host").check_that(IsExactlyStr("d".into())));
+        assert!(cmap.get("backup/port").check_that(IsExactlyInt(443)));
+        assert!(cmap.get("backup/host").is_none());
+    }
+
+    #[test]
+    fn atomic_on_failure() {
+        let mut cmap = doc();
+        let patch = List(vec![
+            op("replace", "/server/port", vec![("value", Int(443))]),
+            op("test", "/server/port", vec![("value", Int(80))]),
+        ]);
+
+        assert_eq!(cmap.apply_patch(&patch), Err(PatchError::TestFailed { index: 1, path: "/server/port".into() }));
+        assert_eq!(cmap, doc());
+
+        let patch = List(vec![op("remove", "/server/host", vec![])]);
+        assert_eq!(cmap.apply_patch(&patch), Err(PatchError::PathNotFound { index: 0, path: "/server/host".into() }));
+
+        let patch = List(vec![op("move", "/server/inner", vec![("from", "/server".into())])]);
+        assert!(matches!(cmap.apply_patch(&patch), Err(PatchError::Malformed(_))));
+
+        let patch = List(vec![op("frobnicate", "/server", vec![])]);
+        assert!(matches!(cmap.apply_patch(&patch), Err(PatchError::Malformed(_))));
+
+        assert!(matches!(cmap.apply_patch(&Int(5)), Err(PatchError::Malformed(_))));
+        assert_eq!(cmap, doc());
+    }
+
+    #[test]
+    fn pinned_types() {
+        let mut cmap = doc();
+        cmap.pin_type("server/port", CfgType::Int).unwrap();
+
+        let patch = List(vec![op("replace", "/server/port", vec![("value", "x".into())])]);
+        let error = PatchError::TypeMismatch { path: "server/port".into(), expected: CfgType::Int, found: CfgType::Str };
+        assert_eq!(cmap.apply_patch(&patch), Err(error.clone()));
+        assert_eq!(cmap.simulate(&patch, &Schema::default()).unwrap_err(), SimulateError::Patch(error));
+
+        let patch = List(vec![op("replace", "/server", vec![("value", Map(CfgMap::new()))])]);
+        assert_eq!(cmap.apply_patch(&patch), Ok(()));
+        assert!(cmap.get("server/port").is_none());
+    }
+}
@@ -0,0 +1,109 @@
+use std::fmt;
+
+/// A segment of a path, which is used as a map key, or as a list index.
+pub(crate) trait Segment {
+    fn key(&self) -> &str;
+    fn index(&self) -> Option<usize>;
+}
+
+impl Segment for &str {
+    fn key(&self) -> &str {
+        self
+    }
+
+    fn index(&self) -> Option<usize> {
+        self.parse().ok()
+    }
+}
+
+impl Segment for (&str, Option<usize>) {
+    fn key(&self) -> &str {
+        self.0
+    }
+
+    fn index(&self) -> Option<usize> {
+        self.1
+    }
+}
+
+/// A path that's parsed once, so that it can be used to read the same value many times
+/// without parsing it every time. Use `CfgMap::get_at` and similar methods to use it.
+///
+/// A path can be created from a string using the same syntax as `CfgMap::get`, or using the `path!` macro.
+///
+/// ## Examples
+/// ```
+/// use cfgmap::{CfgPath, path};
+///
+/// let path = CfgPath::new("servers/0/port");
+/// assert_eq!(path.as_str(), "servers/0/port");
+/// assert_eq!(path, path!("servers" / 0 / "port"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CfgPath {
+    path: String,
+
+    /// The byte range of each segment within `path`, along with the segment parsed as a list index.
+    segments: Vec<(usize, usize, Option<usize>)>,
+}
+
+impl CfgPath {
+    /// Parses `path`.
+    pub fn new(path: &str) -> CfgPath {
+        let mut start = 0;
+        let segments = path.split('/')
+            .map(|segment| {
+                let parsed = (start, start + segment.len(), segment.parse().ok());
+                start += segment.len() + 1;
+                parsed
+            })
+            .collect();
+
+        CfgPath { path: path.to_string(), segments }
+    }
+
+    /// Gets the path as a string.
+    pub fn as_str(&self) -> &str {
+        &self.path
+    }
+
+    /// Iterates over the parsed segments of the path.
+    pub(crate) fn segments(&self) -> impl Iterator<Item = (&str, Option<usize>)> {
+        self.segments.iter().map(move |(start, end, index)| (&self.path[*start..*end], *index))
+    }
+}
+
+impl From<&str> for CfgPath {
+    fn from(path: &str) -> Self {
+        CfgPath::new(path)
+    }
+}
+
+impl fmt::Display for CfgPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CfgMap, CfgPath, CfgValue::*};
+
+    #[test]
+    fn same_as_strings() {
+        let mut cmap = CfgMap::new();
+        cmap.add("a", Map(CfgMap::new())).unwrap();
+        cmap.add("a/", Int(1)).unwrap();
+        cmap.add("a/list", list![1, 2]).unwrap();
+        cmap.add("a/10", Int(3)).unwrap();
+
+        for path in &["a", "a/", "a/list/1", "a/list/x", "a/10", "a/list/1/b", "", "missing/0"] {
+            assert_eq!(cmap.get_at(&CfgPath::new(path)), cmap.get(path), "{}", path);
+        }
+
+        cmap.add_at(&path!("a" / "list" / "-"), Int(3)).unwrap();
+        *cmap.get_mut_at(&path!("a" / 10)).unwrap() = Int(4);
+        assert_eq!(cmap.get("a/list/2"), Some(&Int(3)));
+        assert_eq!(cmap.get("a/10"), Some(&Int(4)));
+    }
+}
@@ -0,0 +1,33 @@
+use super::{CfgError, CfgType, CfgValue};
+use std::collections::HashMap;
+
+/// Resolves a relative path within `value`, indexing into both maps and lists.
+fn value_at<'a>(value: &'a CfgValue, path: &str) -> Option<&'a CfgValue> {
+    path.split('/').try_fold(value, |value, segment| match value {
+        CfgValue::Map(map) => map.internal_map.get(segment),
+        CfgValue::List(list) => segment.parse().ok().and_then(|i: usize| list.get(i)),
+        _ => None,
+    })
+}
+
+/// Checks that `value`, about to be added at `path`, doesn't change the type of a pinned path.
+/// This includes pinned paths nested within `value`, if it's a map or list.
+pub(crate) fn check(pins: &HashMap<String, CfgType>, path: &str, value: &CfgValue) -> Result<(), CfgError> {
+    for (pinned, &expected) in pins {
+        let found = if pinned == path {
+            Some(value)
+        } else {
+            pinned.strip_prefix(path)
+                .and_then(|rest| rest.strip_prefix('/'))
+                .and_then(|rest| value_at(value, rest))
+        };
+
+        if let Some(found) = found {
+            if found.cfg_type() != expected {
+                return Err(CfgError::TypeMismatch { path: pinned.clone(), expected, found: found.cfg_type() });
+            }
+        }
+    }
+
+    Ok(())
+}
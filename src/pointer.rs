@@ -0,0 +1,53 @@
+use super::CfgMap;
+use super::CfgValue;
+
+/// Splits a JSON Pointer (RFC 6901) into its unescaped reference tokens.
+///
+/// Returns `None` if the pointer is not empty and doesn't start with `/`.
+pub(crate) fn parse(pointer: &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        return Some(Vec::new());
+    }
+
+    pointer.strip_prefix('/').map(|rest| {
+        rest.split('/').map(|token| token.replace("~1", "/").replace("~0", "~")).collect()
+    })
+}
+
+/// Parses a reference token as a list index. Leading zeros aren't allowed,
+/// and neither is `-`, since it refers to an element that doesn't exist yet.
+pub(crate) fn parse_index(token: &str) -> Option<usize> {
+    if token.is_empty() || (token.len() > 1 && token.starts_with('0')) || !token.bytes().all(|b| b.is_ascii_digit()) {
+        None
+    } else {
+        token.parse().ok()
+    }
+}
+
+fn child<'a>(value: &'a CfgValue, token: &str) -> Option<&'a CfgValue> {
+    match value {
+        CfgValue::Map(map) => map.internal_map.get(token),
+        CfgValue::List(list) => parse_index(token).and_then(|i| list.get(i)),
+        _ => None
+    }
+}
+
+fn child_mut<'a>(value: &'a mut CfgValue, token: &str) -> Option<&'a mut CfgValue> {
+    match value {
+        CfgValue::Map(map) => map.internal_map_mut().get_mut(token),
+        CfgValue::List(list) => parse_index(token).and_then(move |i| list.get_mut(i)),
+        _ => None
+    }
+}
+
+/// Resolves the reference tokens starting from the root map.
+pub(crate) fn get<'a>(map: &'a CfgMap, tokens: &[String]) -> Option<&'a CfgValue> {
+    let (first, rest) = tokens.split_first()?;
+    rest.iter().try_fold(map.internal_map.get(first)?, |value, token| child(value, token))
+}
+
+/// Resolves the reference tokens starting from the root map, mutably.
+pub(crate) fn get_mut<'a>(map: &'a mut CfgMap, tokens: &[String]) -> Option<&'a mut CfgValue> {
+    let (first, rest) = tokens.split_first()?;
+    rest.iter().try_fold(map.internal_map_mut().get_mut(first)?, |value, token| child_mut(value, token))
+}
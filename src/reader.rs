@@ -0,0 +1,154 @@
+use super::{CfgError, CfgMap};
+use std::io::Read;
+
+/// The format of a document read using `CfgMap::from_reader`, or written using `CfgMap::to_writer`.
+/// Each format is only available if using its feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// A `JSON` document. Only available if using `from_json`.
+    #[cfg(feature = "from_json")]
+    Json,
+
+    /// A `TOML` document. Only available if using `from_toml`.
+    #[cfg(feature = "from_toml")]
+    Toml,
+
+    /// A `YAML` document. Only available if using `from_yaml`.
+    #[cfg(feature = "from_yaml")]
+    Yaml,
+}
+
+/// Reads every remaining byte of `reader` into a string, for parsers that can't read incrementally.
+#[cfg(any(feature = "from_toml", feature = "from_yaml"))]
+fn read_string<R: Read>(mut reader: R) -> Result<String, CfgError> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text).map_err(|e| CfgError::LoadFailed(e.to_string()))?;
+    Ok(text)
+}
+
+#[cfg(feature = "from_json")]
+pub(crate) fn read_json<R: Read>(reader: R) -> Result<CfgMap, CfgError> {
+    let value: serde_json::Value = serde_json::from_reader(reader).map_err(|e| CfgError::LoadFailed(e.to_string()))?;
+    super::from_json::json_to_cfg(value)
+}
+
+#[cfg(feature = "from_toml")]
+pub(crate) fn read_toml<R: Read>(reader: R) -> Result<CfgMap, CfgError> {
+    let value: toml::Value = toml::from_str(&read_string(reader)?).map_err(|e| CfgError::LoadFailed(e.to_string()))?;
+    super::from_toml::toml_to_cfg(value)
+}
+
+#[cfg(feature = "from_yaml")]
+pub(crate) fn read_yaml<R: Read>(reader: R) -> Result<CfgMap, CfgError> {
+    let documents = yaml_rust::YamlLoader::load_from_str(&read_string(reader)?)
+        .map_err(|e| CfgError::LoadFailed(e.to_string()))?;
+    super::from_yaml::yaml_to_cfg(documents.into_iter().next().ok_or(CfgError::NotAMap)?)
+}
+
+pub(crate) fn read<R: Read>(reader: R, format: Format) -> Result<CfgMap, CfgError> {
+    match format {
+        #[cfg(feature = "from_json")]
+        Format::Json => read_json(reader),
+        #[cfg(feature = "from_toml")]
+        Format::Toml => read_toml(reader),
+        #[cfg(feature = "from_yaml")]
+        Format::Yaml => read_yaml(reader),
+    }
+}
+
+/// Reads every remaining byte of `reader` without blocking, then parses them the same way as `read`.
+#[cfg(feature = "async")]
+pub(crate) async fn read_async<R: tokio::io::AsyncRead + Unpin>(mut reader: R, format: Format) -> Result<CfgMap, CfgError> {
+    use tokio::io::AsyncReadExt;
+
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).await.map_err(|e| CfgError::LoadFailed(e.to_string()))?;
+    read(bytes.as_slice(), format)
+}
+
+#[cfg(all(test, feature = "from_json", feature = "from_toml", feature = "from_yaml"))]
+mod test {
+    use crate::{CfgError, CfgMap, CfgValue::*, Checkable, Condition::*, Format, Schema};
+
+    #[test]
+    fn every_format() {
+        let json = CfgMap::from_reader(r#"{ "db": { "port": 5432 } }"#.as_bytes(), Format::Json).unwrap();
+        let toml = CfgMap::from_reader("[db]\nport = 5432\n".as_bytes(), Format::Toml).unwrap();
+        let yaml = CfgMap::from_reader("db:\n  port: 5432\n".as_bytes(), Format::Yaml).unwrap();
+
+        for cmap in &[json, toml, yaml] {
+            assert!(cmap.get("db/port").check_that(IsExactlyInt(5432)));
+        }
+
+        assert!(matches!(CfgMap::from_json_reader("{ \"db\": ".as_bytes()), Err(CfgError::LoadFailed(_))));
+        assert!(matches!(CfgMap::from_toml_reader("db = = 1".as_bytes()), Err(CfgError::LoadFailed(_))));
+        assert_eq!(CfgMap::from_yaml_reader("".as_bytes()), Err(CfgError::NotAMap));
+        assert_eq!(CfgMap::from_json_reader("[1]".as_bytes()), Err(CfgError::NotAMap));
+    }
+
+    #[test]
+    fn validated() {
+        let mut rules = CfgMap::new();
+        rules.add_force("db/port", Str("int in 1..65535".into())).unwrap();
+        rules.add("db/name", Str("warning: str".into())).unwrap();
+        let schema = Schema::from_map(&rules).unwrap();
+
+        let cmap = CfgMap::from_reader_validated("[db]\nport = 5432\n".as_bytes(), Format::Toml, &schema).unwrap();
+        assert!(cmap.get("db/port").check_that(IsExactlyInt(5432)));
+
+        let path = std::env::temp_dir().join(format!("cfgmap-validated-{}.yaml", std::process::id()));
+        std::fs::write(&path, "db:\n  port: 0\n").unwrap();
+        let error = CfgMap::from_file_validated(&path, Format::Yaml, &schema).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        match error {
+            CfgError::Invalid(report) => assert_eq!(report.failures.len(), 1),
+            error => panic!("unexpected error: {}", error),
+        }
+
+        let error = CfgMap::from_reader_validated("{".as_bytes(), Format::Json, &schema).unwrap_err();
+        assert!(matches!(error, CfgError::LoadFailed(_)));
+    }
+
+    #[test]
+    fn provenance() {
+        let path = std::env::temp_dir().join(format!("cfgmap-provenance-{}.yaml", std::process::id()));
+        std::fs::write(&path, "db:\n  port: 5432\n  hosts: [a, b]\n").unwrap();
+        let mut cmap = CfgMap::from_file(&path, Format::Yaml).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let source = path.display().to_string();
+        assert_eq!(cmap.provenance("db/port"), Some(source.as_str()));
+        assert_eq!(cmap.provenance("db/hosts/1"), Some(source.as_str()));
+        assert_eq!(cmap.provenance("db"), None);
+
+        cmap.add_from("env", "db/port", Int(6543)).unwrap();
+        assert_eq!(cmap.provenance("db/port"), Some("env"));
+        assert_eq!(cmap.detect_conflicts()[0].writes[0], (source, Int(5432)));
+
+        let read = CfgMap::from_reader("db:\n  port: 5432\n".as_bytes(), Format::Yaml).unwrap();
+        assert_eq!(read.provenance("db/port"), None);
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn every_format_async() {
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let path = std::env::temp_dir().join(format!("cfgmap-async-{}.toml", std::process::id()));
+        std::fs::write(&path, "[db]\nport = 5432\n").unwrap();
+
+        let (file, missing, reader) = runtime.block_on(async {
+            (
+                CfgMap::from_file_async(&path, Format::Toml).await,
+                CfgMap::from_file_async(path.with_extension("missing"), Format::Toml).await,
+                CfgMap::from_reader_async("db:\n  port: 5432\n".as_bytes(), Format::Yaml).await,
+            )
+        });
+        std::fs::remove_file(&path).unwrap();
+
+        let file = file.unwrap();
+        assert!(file.get("db/port").check_that(IsExactlyInt(5432)));
+        assert_eq!(file.provenance("db/port"), Some(path.display().to_string().as_str()));
+        assert!(matches!(missing, Err(CfgError::LoadFailed(_))));
+        assert!(reader.unwrap().get("db/port").check_that(IsExactlyInt(5432)));
+    }
+}
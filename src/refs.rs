@@ -0,0 +1,212 @@
+use super::{CfgError, CfgHashMap, CfgMap, CfgValue};
+
+/// The key that marks a map as a reference to another path.
+const REF_KEY: &str = "$ref";
+
+/// Gets a copy of `value` with every reference within it resolved. `stack` contains the targets
+/// currently being resolved, so that references back to any of them can be detected as cycles.
+fn resolve_value(root: &CfgMap, path: &str, value: &CfgValue, stack: &mut Vec<String>) -> Result<CfgValue, CfgError> {
+    match value {
+        CfgValue::Map(map) => match map.internal_map.get(REF_KEY) {
+            Some(CfgValue::Str(target)) => {
+                if stack.contains(target) {
+                    return Err(CfgError::RefCycle(path.to_string()));
+                }
+
+                let found = root.get_untracked(target)
+                    .ok_or_else(|| CfgError::UnresolvedRef(path.to_string()))?;
+
+                stack.push(target.clone());
+                let resolved = resolve_value(root, target, found, stack)?;
+                stack.pop();
+
+                let overrides: Vec<(&String, &CfgValue)> = map.iter().filter(|(k, _)| *k != REF_KEY).collect();
+                if overrides.is_empty() {
+                    return Ok(resolved);
+                }
+
+                // Any other keys alongside the reference override the keys of the referenced map.
+                let mut resolved = match resolved {
+                    CfgValue::Map(resolved) => resolved,
+                    _ => return Err(CfgError::UnresolvedRef(path.to_string())),
+                };
+                for (key, value) in overrides {
+                    let full = super::join_path(path, key);
+                    resolved.internal_map_mut().insert(key.clone(), resolve_value(root, &full, value, stack)?);
+                }
+                Ok(CfgValue::Map(resolved))
+            },
+            _ => Ok(CfgValue::Map(CfgMap::with_internal_map(resolve_map(root, path, map, stack)?))),
+        },
+        CfgValue::List(list) => list.iter().enumerate()
+            .map(|(i, value)| resolve_value(root, &super::join_path(path, &i.to_string()), value, stack))
+            .collect::<Result<_, _>>()
+            .map(CfgValue::List),
+        other => Ok(other.clone()),
+    }
+}
+
+fn resolve_map(root: &CfgMap, path: &str, map: &CfgMap, stack: &mut Vec<String>) -> Result<CfgHashMap, CfgError> {
+    // Keys are resolved in order, so that the same error is reported every time.
+    let mut entries: Vec<(&String, &CfgValue)> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    entries.into_iter()
+        .map(|(key, value)| {
+            resolve_value(root, &super::join_path(path, key), value, stack).map(|value| (key.clone(), value))
+        })
+        .collect()
+}
+
+/// Gets the contents of `map` with every reference resolved.
+pub(crate) fn resolve(map: &CfgMap) -> Result<CfgHashMap, CfgError> {
+    resolve_map(map, "", map, &mut Vec::new())
+}
+
+/// Gets the path that `value` refers to, if it's a reference.
+fn target(value: &CfgValue) -> Option<&String> {
+    match value {
+        CfgValue::Map(map) => match map.internal_map.get(REF_KEY) {
+            Some(CfgValue::Str(target)) => Some(target),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Gets the value at `key` within `value`, if it's a map or list.
+fn child<'a>(value: &'a CfgValue, key: &str) -> Option<&'a CfgValue> {
+    match value {
+        CfgValue::Map(map) => map.internal_map.get(key),
+        CfgValue::List(list) => key.parse().ok().and_then(|i: usize| list.get(i)),
+        _ => None,
+    }
+}
+
+/// Finds the value at `path`, following every reference along the path, along with the path it was found at.
+/// The value itself isn't followed if it's a reference. Each target followed is pushed onto `stack`.
+fn find<'a>(root: &'a CfgMap, path: &str, stack: &mut Vec<String>) -> Result<Option<(String, &'a CfgValue)>, CfgError> {
+    let mut found: Option<(String, &CfgValue)> = None;
+
+    for key in path.split('/').filter(|key| !key.is_empty()) {
+        let next = match found {
+            None => root.internal_map.get(key).map(|value| (key.to_string(), value)),
+            Some((mut at, mut value)) => loop {
+                // Keys alongside a reference override the referenced map, so they're found without following it.
+                let overridden = key != REF_KEY && child(value, key).is_some();
+                let target = match target(value) {
+                    Some(target) if !overridden => target,
+                    _ => break child(value, key).map(|value| (super::join_path(&at, key), value)),
+                };
+
+                if stack.contains(target) {
+                    return Err(CfgError::RefCycle(at));
+                }
+                stack.push(target.clone());
+
+                let (target_at, target_value) = find(root, target, stack)?
+                    .ok_or_else(|| CfgError::UnresolvedRef(at.clone()))?;
+                at = target_at;
+                value = target_value;
+            },
+        };
+
+        found = match next {
+            Some(next) => Some(next),
+            None => return Ok(None),
+        };
+    }
+
+    Ok(found)
+}
+
+/// Gets a copy of the value at `path` within `map`, with every reference along the path and within the value
+/// resolved, without resolving any other references.
+pub(crate) fn get(map: &CfgMap, path: &str) -> Result<Option<CfgValue>, CfgError> {
+    let mut stack = Vec::new();
+    match find(map, path, &mut stack)? {
+        Some((at, value)) => resolve_value(map, &at, value, &mut stack).map(Some),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CfgError, CfgMap, CfgType, CfgValue::*, Checkable, Condition::*};
+
+    fn reference(target: &str) -> crate::CfgValue {
+        let mut map = CfgMap::new();
+        map.add("$ref", Str(target.into())).unwrap();
+        Map(map)
+    }
+
+    #[test]
+    fn nested_and_cyclic() {
+        let mut cmap = CfgMap::new();
+        cmap.add("shared", Map(CfgMap::new())).unwrap();
+        cmap.add("shared/db", Map(CfgMap::new())).unwrap();
+        cmap.add("shared/db/port", Int(5432)).unwrap();
+        cmap.add("shared/alias", reference("shared/db")).unwrap();
+        cmap.add("services", List(vec![reference("shared/alias"), reference("shared/db/port")])).unwrap();
+
+        let mut resolved = cmap.clone();
+        resolved.resolve_refs().unwrap();
+        assert!(resolved.get("services/0/port").check_that(IsExactlyInt(5432)));
+        assert!(resolved.get("services/1").check_that(IsExactlyInt(5432)));
+
+        cmap.add("shared/db/next", reference("shared/alias")).unwrap();
+        assert_eq!(cmap.resolve_refs(), Err(CfgError::RefCycle("shared/db/next".into())));
+
+        cmap.add("shared/db/next", reference("missing")).unwrap();
+        assert_eq!(cmap.resolve_refs(), Err(CfgError::UnresolvedRef("shared/db/next".into())));
+    }
+
+    #[test]
+    fn lazily() {
+        let mut cmap = CfgMap::new();
+        cmap.add("shared", Map(CfgMap::new())).unwrap();
+        cmap.add("shared/db", Map(CfgMap::new())).unwrap();
+        cmap.add("shared/db/port", Int(5432)).unwrap();
+        cmap.add("shared/db/hosts", list!["a", "b"]).unwrap();
+        cmap.add("shared/alias", reference("shared/db")).unwrap();
+        cmap.add("api", reference("shared/alias")).unwrap();
+        cmap.add("api/port", Int(6543)).unwrap();
+        cmap.add("services", List(vec![reference("api"), reference("shared/db/hosts/1")])).unwrap();
+
+        // Every path reads the same as it would after resolving the whole map.
+        let mut resolved = cmap.clone();
+        resolved.resolve_refs().unwrap();
+        for (path, value) in resolved.walk_all() {
+            assert_eq!(cmap.get_resolved(&path), Ok(Some(value.clone())), "{}", path);
+        }
+        assert_eq!(cmap.get_resolved("api/missing"), Ok(None));
+        assert_eq!(cmap.get("api/hosts"), None);
+
+        // Only the references that are followed have to be resolvable.
+        cmap.add("broken", reference("missing")).unwrap();
+        cmap.add("shared/db/next", reference("shared/alias")).unwrap();
+        assert_eq!(cmap.get_resolved("api/hosts/0"), Ok(Some(Str("a".into()))));
+        assert_eq!(cmap.get_resolved("broken/port"), Err(CfgError::UnresolvedRef("broken".into())));
+        assert_eq!(cmap.get_resolved("api/next/port"), Err(CfgError::RefCycle("shared/db/next".into())));
+        assert_eq!(cmap.get_resolved("api"), Err(CfgError::RefCycle("shared/db/next".into())));
+    }
+
+    #[test]
+    fn locks_and_pins() {
+        let mut cmap = CfgMap::new();
+        cmap.add_force("shared/port", Str("5432".into())).unwrap();
+        cmap.add_force("sec/tls", reference("shared")).unwrap();
+        cmap.lock_path("sec");
+
+        let original = cmap.clone();
+        assert_eq!(cmap.resolve_refs(), Err(CfgError::Locked("sec".into())));
+        assert_eq!(cmap, original);
+
+        let mut cmap = CfgMap::new();
+        cmap.add_force("shared/port", Str("5432".into())).unwrap();
+        cmap.add("db", reference("shared")).unwrap();
+        cmap.pin_type("db/port", CfgType::Int).unwrap();
+        assert!(matches!(cmap.resolve_refs(), Err(CfgError::TypeMismatch { .. })));
+        assert!(cmap.get("db/$ref").is_some());
+    }
+}
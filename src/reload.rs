@@ -0,0 +1,278 @@
+use super::CfgMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Describes what is needed for a change to a path to take effect.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReloadKind {
+    /// The change can be applied while the service is running.
+    HotReloadable,
+
+    /// The service has to be restarted for the change to take effect.
+    RestartRequired,
+}
+
+/// A mapping of paths to the `ReloadKind` of any change to them.
+///
+/// A path covers its whole subtree, so marking `server` as `RestartRequired` also applies to
+/// `server/port`. If multiple paths cover a change, the most specific one is used. Any change
+/// that isn't covered by any path uses the policy's fallback kind.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReloadPolicy {
+    paths: Vec<(String, ReloadKind)>,
+    fallback: ReloadKind,
+}
+
+/// The result of classifying the differences between two maps using a `ReloadPolicy`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ReloadPlan {
+    /// Changed paths that require a restart.
+    pub restart: Vec<String>,
+
+    /// Changed paths that can be applied while running.
+    pub hot: Vec<String>,
+}
+
+impl ReloadPlan {
+    /// Checks whether any of the changes require a restart.
+    pub fn needs_restart(&self) -> bool {
+        !self.restart.is_empty()
+    }
+
+    /// Checks whether there were any changes at all.
+    pub fn is_empty(&self) -> bool {
+        self.restart.is_empty() && self.hot.is_empty()
+    }
+}
+
+/// Checks whether `path` is `ancestor`, or within its subtree.
+fn is_within(path: &str, ancestor: &str) -> bool {
+    path == ancestor || path.starts_with(ancestor) && path[ancestor.len()..].starts_with('/')
+}
+
+impl ReloadPolicy {
+    /// Creates a new policy, where any changes not covered by a path are classified as `fallback`.
+    pub fn new(fallback: ReloadKind) -> ReloadPolicy {
+        ReloadPolicy { paths: Vec::new(), fallback }
+    }
+
+    /// Classifies any change within `path` as `kind`.
+    pub fn with(mut self, path: &str, kind: ReloadKind) -> ReloadPolicy {
+        self.paths.push((path.trim_end_matches('/').to_string(), kind));
+        self
+    }
+
+    /// Classifies a single changed path.
+    ///
+    /// If the change replaces a whole subtree which contains a path requiring a restart,
+    /// the change requires a restart as well.
+    pub fn classify(&self, path: &str) -> ReloadKind {
+        let covering = self.paths.iter()
+            .filter(|(p, _)| is_within(path, p))
+            .max_by_key(|(p, _)| p.len())
+            .map_or(self.fallback, |(_, kind)| *kind);
+
+        let restart_within = self.paths.iter()
+            .any(|(p, kind)| *kind == ReloadKind::RestartRequired && is_within(p, path));
+
+        if restart_within { ReloadKind::RestartRequired } else { covering }
+    }
+
+    /// Compares `old` and `new`, and classifies every path that changed between them.
+    ///
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, ReloadPolicy, ReloadKind::*};
+    ///
+    /// let policy = ReloadPolicy::new(RestartRequired)
+    ///     .with("logging", HotReloadable)
+    ///     .with("server/port", RestartRequired);
+    ///
+    /// let mut old = CfgMap::new();
+    /// old.add("logging", Map(CfgMap::new()));
+    /// old.add("logging/level", Str("info".into()));
+    /// old.add("server", Map(CfgMap::new()));
+    /// old.add("server/port", Int(80));
+    ///
+    /// let mut new = old.clone();
+    /// new.add("logging/level", Str("debug".into()));
+    ///
+    /// let plan = policy.reload_plan(&old, &new);
+    /// assert!(!plan.needs_restart());
+    /// assert_eq!(plan.hot, vec!["logging/level"]);
+    ///
+    /// new.add("server/port", Int(8080));
+    /// let plan = policy.reload_plan(&old, &new);
+    /// assert!(plan.needs_restart());
+    /// assert_eq!(plan.restart, vec!["server/port"]);
+    /// ```
+    pub fn reload_plan(&self, old: &CfgMap, new: &CfgMap) -> ReloadPlan {
+        let mut plan = ReloadPlan::default();
+
+        for path in old.diff(new).paths() {
+            match self.classify(path) {
+                ReloadKind::RestartRequired => plan.restart.push(path.clone()),
+                ReloadKind::HotReloadable => plan.hot.push(path.clone()),
+            }
+        }
+
+        plan
+    }
+}
+
+type Validator = Box<dyn Fn(&CfgMap) -> Result<(), String>>;
+
+/// Guards swapping in a reloaded map, so that reloads happen at most once every `min_interval`,
+/// and only if the reloaded map validates. This way, a half-written file doesn't replace a working map.
+pub struct ReloadGate {
+    min_interval: Duration,
+    validator: Option<Validator>,
+    last_swap: Option<Instant>,
+}
+
+/// The reason `ReloadGate::swap` rejected a reloaded map.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReloadRejected {
+    /// The last swap happened too recently. Contains the time left until the next one is allowed.
+    TooSoon(Duration),
+
+    /// The reloaded map failed validation, for the given reason.
+    Invalid(String),
+}
+
+impl fmt::Display for ReloadRejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReloadRejected::TooSoon(left) => write!(f, "reloaded too soon, try again in {:?}", left),
+            ReloadRejected::Invalid(reason) => write!(f, "reloaded map is invalid: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ReloadRejected {}
+
+impl fmt::Debug for ReloadGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReloadGate")
+            .field("min_interval", &self.min_interval)
+            .field("last_swap", &self.last_swap)
+            .finish()
+    }
+}
+
+impl ReloadGate {
+    /// Creates a new gate, allowing a swap at most once every `min_interval`, without any validation.
+    pub fn new(min_interval: Duration) -> ReloadGate {
+        ReloadGate { min_interval, validator: None, last_swap: None }
+    }
+
+    /// Only allows swapping in maps for which `validator` returns `Ok`.
+    pub fn validate_with<F>(mut self, validator: F) -> ReloadGate
+        where F: Fn(&CfgMap) -> Result<(), String> + 'static
+    {
+        self.validator = Some(Box::new(validator));
+        self
+    }
+
+    /// Replaces `current` with `new` if enough time passed since the last swap, and `new` validates,
+    /// returning the old map. Otherwise, `current` is left untouched.
+    ///
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Checkable, Condition::*, ReloadGate, ReloadRejected};
+    /// use std::time::Duration;
+    ///
+    /// let mut gate = ReloadGate::new(Duration::from_secs(0)).validate_with(|map| {
+    ///     if map.get("port").check_that(IsInt) { Ok(()) } else { Err("`port` must be an integer".into()) }
+    /// });
+    ///
+    /// let mut current = CfgMap::new();
+    /// current.add("port", Int(80));
+    ///
+    /// // A half-written file, that's missing the port.
+    /// let result = gate.swap(&mut current, CfgMap::new());
+    /// assert_eq!(result, Err(ReloadRejected::Invalid("`port` must be an integer".into())));
+    /// assert!(current.get("port").check_that(IsExactlyInt(80)));
+    ///
+    /// let mut reloaded = CfgMap::new();
+    /// reloaded.add("port", Int(8080));
+    ///
+    /// let old = gate.swap(&mut current, reloaded).unwrap();
+    /// assert!(old.get("port").check_that(IsExactlyInt(80)));
+    /// assert!(current.get("port").check_that(IsExactlyInt(8080)));
+    /// ```
+    pub fn swap(&mut self, current: &mut CfgMap, new: CfgMap) -> Result<CfgMap, ReloadRejected> {
+        let now = Instant::now();
+
+        if let Some(last) = self.last_swap {
+            let elapsed = now.duration_since(last);
+            if elapsed < self.min_interval {
+                return Err(ReloadRejected::TooSoon(self.min_interval - elapsed));
+            }
+        }
+
+        if let Some(validator) = &self.validator {
+            validator(&new).map_err(ReloadRejected::Invalid)?;
+        }
+
+        self.last_swap = Some(now);
+        Ok(std::mem::replace(current, new))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::CfgValue::*;
+    use ReloadKind::*;
+
+    #[test]
+    fn classify() {
+        let policy = ReloadPolicy::new(HotReloadable)
+            .with("server/", RestartRequired)
+            .with("server/timeouts", HotReloadable)
+            .with("db/pool/size", RestartRequired);
+
+        assert_eq!(policy.classify("server/port"), RestartRequired);
+        assert_eq!(policy.classify("server/timeouts/read"), HotReloadable);
+        assert_eq!(policy.classify("serverless"), HotReloadable);
+        assert_eq!(policy.classify("db/pool/timeout"), HotReloadable);
+
+        // Replacing all of `db` also replaces `db/pool/size`.
+        assert_eq!(policy.classify("db"), RestartRequired);
+    }
+
+    #[test]
+    fn plan() {
+        let policy = ReloadPolicy::new(RestartRequired).with("features", HotReloadable);
+
+        let mut old = CfgMap::new();
+        old.add("features", list!["a"]).unwrap();
+        old.add("name", Str("svc".into())).unwrap();
+
+        let mut new = CfgMap::new();
+        new.add("features", list!["a", "b"]).unwrap();
+        new.add("threads", Int(4)).unwrap();
+
+        let plan = policy.reload_plan(&old, &new);
+        assert_eq!(plan.hot, vec!["features/1"]);
+        assert_eq!(plan.restart, vec!["name", "threads"]);
+        assert!(policy.reload_plan(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn gate() {
+        let mut gate = ReloadGate::new(Duration::from_secs(3600));
+        let mut current = CfgMap::new();
+
+        let mut reloaded = CfgMap::new();
+        reloaded.add("port", Int(80)).unwrap();
+
+        assert!(gate.swap(&mut current, reloaded.clone()).unwrap().is_empty());
+        match gate.swap(&mut current, CfgMap::new()) {
+            Err(ReloadRejected::TooSoon(left)) => assert!(left <= Duration::from_secs(3600)),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(current, reloaded);
+    }
+}
@@ -0,0 +1,41 @@
+use super::{CfgMap, CfgValue};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Tries to find the name of the machine, first through the environment and then through `/etc/hostname`.
+fn hostname() -> Option<String> {
+    ["HOSTNAME", "COMPUTERNAME"].iter()
+        .find_map(|var| std::env::var(var).ok())
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// Collects information about the running process, and hashes each of the `sources`.
+pub(crate) fn runtime_info<P: AsRef<Path>>(sources: &[P]) -> io::Result<CfgMap> {
+    let mut info = CfgMap::new();
+
+    if let Some(hostname) = hostname() {
+        info.internal_map_mut().insert("hostname".into(), CfgValue::Str(hostname));
+    }
+
+    info.internal_map_mut().insert("pid".into(), CfgValue::Int(std::process::id().into()));
+
+    let loaded_at = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    info.internal_map_mut().insert("loaded_at".into(), CfgValue::Int(loaded_at as i64));
+
+    let sources = sources.iter().map(|source| {
+        let source = source.as_ref();
+        let hash = super::fingerprint::of_bytes(&fs::read(source)?);
+
+        let mut entry = CfgMap::new();
+        entry.internal_map_mut().insert("path".into(), CfgValue::Str(source.display().to_string()));
+        entry.internal_map_mut().insert("hash".into(), CfgValue::Str(format!("{:016x}", hash)));
+        Ok(CfgValue::Map(entry))
+    }).collect::<io::Result<Vec<CfgValue>>>()?;
+    info.internal_map_mut().insert("sources".into(), CfgValue::List(sources));
+
+    Ok(info)
+}
@@ -0,0 +1,686 @@
+use super::{CfgMap, CfgValue, CheckError, Condition, ConditionParseError, Coverage, Incompatibility, Severity, Unexportable, ValidationReport};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+/// An error produced when a schema can't be loaded using `Schema::from_map` or `Schema::from_json_schema`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaError {
+    /// The value at `path` isn't a string or a map, so it can't describe a condition.
+    NotACondition { path: String },
+
+    /// The condition at `path` couldn't be parsed.
+    Invalid { path: String, error: ConditionParseError },
+
+    /// The JSON Schema at `path` uses a keyword, or a value for it, that can't be imported.
+    Unsupported { path: String, keyword: String },
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::NotACondition { path } => write!(f, "`{}` isn't a condition or a map of conditions", path),
+            SchemaError::Invalid { path, error } => write!(f, "`{}` is an {}", path, error),
+            SchemaError::Unsupported { path, keyword } => write!(f, "`{}` uses `{}` in a way that isn't supported", path, keyword),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// A set of rules that a configuration must satisfy, written in the configuration format itself,
+/// so that the rules can be kept beside the configurations they validate.
+///
+/// Each string within the schema is a condition, written the same way as for `Condition::parse`, which
+/// the value at the same path must satisfy. Every path within the schema must be present.
+///
+/// A condition can start with `warning:` or `info:`, in which case the rule fails with that `Severity` rather
+/// than as an error, so it's reported without making the configuration invalid. After that, it can start with
+/// `optional:`, in which case the rule is skipped if the value, or any optional map containing it, is missing.
+///
+/// ## Examples
+/// ```
+/// use cfgmap::{CfgMap, CfgValue::*, Schema};
+///
+/// let mut schema = CfgMap::new();
+/// schema.add("server", Map(CfgMap::new()));
+/// schema.add("server/port", Str("int in 1..65535".into()));
+/// schema.add("server/host", Str("str".into()));
+/// let schema = Schema::from_map(&schema).unwrap();
+///
+/// let mut cmap = CfgMap::new();
+/// cmap.add("server", Map(CfgMap::new()));
+/// cmap.add("server/port", Int(80));
+/// assert_eq!(schema.validate(&cmap).to_string(), "`server/host` is missing");
+///
+/// cmap.add("server/host", Str("localhost".into()));
+/// assert!(schema.validate(&cmap).is_valid());
+/// ```
+///
+/// Rules that only warn are still reported:
+/// ```
+/// use cfgmap::{CfgMap, CfgValue::*, Schema};
+///
+/// let mut schema = CfgMap::new();
+/// schema.add("workers", Str("warning: int in 1..64".into()));
+/// let schema = Schema::from_map(&schema).unwrap();
+///
+/// let mut cmap = CfgMap::new();
+/// cmap.add("workers", Int(128));
+///
+/// let report = schema.validate(&cmap);
+/// assert!(report.is_valid());
+/// assert_eq!(report.to_string(), "warning: `workers` doesn't satisfy `IsIntInRange(1, 64)`");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Schema {
+    rules: Vec<(String, Condition)>,
+    severities: HashMap<String, Severity>,
+    optional: HashSet<String>,
+    fixers: HashMap<String, Fixer>,
+}
+
+type FixerFn = dyn Fn(Option<&CfgValue>) -> Option<CfgValue> + Send + Sync;
+
+/// The function attached to a rule using `Schema::fix_with`.
+#[derive(Clone)]
+struct Fixer(Arc<FixerFn>);
+
+impl fmt::Debug for Fixer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Fixer")
+    }
+}
+
+/// Splits the severity from the start of a condition, which is an error unless it starts with `warning:` or `info:`.
+fn split_severity(text: &str) -> (Severity, &str) {
+    let trimmed = text.trim_start();
+    for &(prefix, severity) in &[("error:", Severity::Error), ("warning:", Severity::Warning), ("info:", Severity::Info)] {
+        if let Some(rest) = trimmed.strip_prefix(prefix) {
+            return (severity, rest);
+        }
+    }
+    (Severity::Error, text)
+}
+
+impl Schema {
+    /// Loads a schema from a map, where each string is parsed into a condition for the value at its path.
+    pub fn from_map(map: &CfgMap) -> Result<Schema, SchemaError> {
+        let mut schema = Schema::default();
+        schema.load(map, "")?;
+        Ok(schema)
+    }
+
+    /// Loads the rules of `map`, which is at `path`, with their paths in sorted order.
+    fn load(&mut self, map: &CfgMap, path: &str) -> Result<(), SchemaError> {
+        let mut entries: Vec<(&String, &CfgValue)> = map.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+
+        for (key, value) in entries {
+            let path = super::join_path(path, key);
+            match value {
+                CfgValue::Map(map) => self.load(map, &path)?,
+                CfgValue::Str(text) => {
+                    let (severity, text) = split_severity(text);
+                    let (optional, text) = match text.trim_start().strip_prefix("optional:") {
+                        Some(rest) => (true, rest),
+                        None => (false, text),
+                    };
+                    match Condition::parse(text) {
+                        Ok(condition) => self.set_severity(&path, severity).set_optional(&path, optional).rules.push((path, condition)),
+                        Err(error) => return Err(SchemaError::Invalid { path, error }),
+                    }
+                },
+                _ => return Err(SchemaError::NotACondition { path }),
+            }
+        }
+        Ok(())
+    }
+
+    /// Imports a JSON Schema, such as one read using `CfgMap::from_file`, so that configurations can be validated
+    /// against existing JSON Schemas without rewriting them.
+    /// 
+    /// The common subset of JSON Schema is supported: `type`, `enum`, `const`, `minimum` and `maximum` for integers
+    /// and numbers, `required`, `properties` and `items`. Properties become rules of their own, which are optional
+    /// unless they're required, down to any map that can't be checked through its properties alone, such as an
+    /// optional one, whose properties become part of its condition instead, the same as those of list items. Keywords
+    /// that only describe the schema, such as `description` and `format`, are skipped, and any other keyword, such as
+    /// `$ref` or `pattern`, is rejected with `SchemaError::Unsupported`.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Schema};
+    /// 
+    /// let mut port = CfgMap::new();
+    /// port.add("type", Str("integer".into()));
+    /// port.add("maximum", Int(65535));
+    /// let mut properties = CfgMap::new();
+    /// properties.add("port", Map(port));
+    /// properties.add("host", Map(CfgMap::new()));
+    /// 
+    /// let mut document = CfgMap::new();
+    /// document.add("properties", Map(properties));
+    /// document.add("required", List(vec![Str("port".into())]));
+    /// let schema = Schema::from_json_schema(&document).unwrap();
+    /// assert!(schema.is_optional("host"));
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("port", Int(70000));
+    /// assert_eq!(schema.validate(&cmap).to_string(), "`port` doesn't satisfy `IsInt & IsIntInRange(-9223372036854775808, 65535)`");
+    /// ```
+    pub fn from_json_schema(schema: &CfgMap) -> Result<Schema, SchemaError> {
+        let mut imported = Schema::default();
+        for (path, condition, optional) in super::json_schema::import(schema)? {
+            imported.set_optional(&path, optional).rules.push((path, condition));
+        }
+        Ok(imported)
+    }
+
+    /// Returns every path within the schema, along with the condition its value must satisfy.
+    pub fn rules(&self) -> &[(String, Condition)] {
+        &self.rules
+    }
+
+    /// Gets the severity of the rule at `path`, which is `Severity::Error` unless it was changed.
+    pub fn severity_of(&self, path: &str) -> Severity {
+        self.severities.get(path).copied().unwrap_or_default()
+    }
+
+    /// Changes the severity of the rule at `path`.
+    pub fn set_severity(&mut self, path: &str, severity: Severity) -> &mut Schema {
+        match severity {
+            Severity::Error => self.severities.remove(path),
+            severity => self.severities.insert(path.to_string(), severity),
+        };
+        self
+    }
+
+    /// Checks whether the rule at `path` is optional.
+    pub fn is_optional(&self, path: &str) -> bool {
+        self.optional.contains(path)
+    }
+
+    /// Changes whether the rule at `path` is optional, so that it's skipped if the value, or any optional map
+    /// containing it, is missing.
+    pub fn set_optional(&mut self, path: &str, optional: bool) -> &mut Schema {
+        if optional {
+            self.optional.insert(path.to_string());
+        } else {
+            self.optional.remove(path);
+        }
+        self
+    }
+
+    /// Checks whether the rule at `path` is skipped for `map`, because an optional path covering it is missing.
+    fn skips(&self, map: &CfgMap, path: &str) -> bool {
+        self.optional.iter()
+            .any(|optional| (optional == path || super::is_within(path, optional)) && map.get_untracked(optional).is_none())
+    }
+
+    /// Gets the rules that apply to `map`, skipping those whose optional paths are missing.
+    fn applicable<'a>(&'a self, map: &'a CfgMap) -> impl Iterator<Item = &'a (String, Condition)> + 'a {
+        self.rules.iter().filter(move |(path, _)| !self.skips(map, path))
+    }
+
+    /// Checks every rule of the schema against `map` the same way as `CfgMap::validate_all`, reporting each
+    /// failure with the severity of its rule.
+    pub fn validate(&self, map: &CfgMap) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        for (path, condition) in self.applicable(map) {
+            if let Err(failure) = map.check_path(path, condition) {
+                report.push(self.severity_of(path), failure);
+            }
+        }
+        report
+    }
+
+    /// Attaches a fixer to the rule at `path`, which `validate_and_fix` calls with the value at `path`, or `None`
+    /// if it's missing, whenever the rule fails. The fixer returns the repaired value, or `None` if it can't be repaired.
+    pub fn fix_with<F>(&mut self, path: &str, fixer: F) -> &mut Schema
+    where F: Fn(Option<&CfgValue>) -> Option<CfgValue> + Send + Sync + 'static {
+        self.fixers.insert(path.to_string(), Fixer(Arc::new(fixer)));
+        self
+    }
+
+    /// Attaches a canonical default to the rule at `path`, which `validate_and_fix` adds in place of the value
+    /// at `path` whenever the rule fails, such as when an optional section is missing.
+    pub fn fix_default(&mut self, path: &str, default: CfgValue) -> &mut Schema {
+        self.fix_with(path, move |_| Some(default.clone()))
+    }
+
+    /// Checks every rule of the schema against `map` the same way as `validate`, but repairs the value of every
+    /// failing rule that has a fixer attached using `fix_with` or `fix_default`. The paths that were repaired are
+    /// reported within `ValidationReport::fixed`, while every other failure is reported as usual.
+    /// 
+    /// A repaired value is only added if it satisfies the rule, and is added the same way as `CfgMap::add_force`,
+    /// creating any missing maps along its path. Values that would change a locked path, or the type of a pinned
+    /// path, are left as they are.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable, Schema};
+    /// 
+    /// let mut rules = CfgMap::new();
+    /// rules.add("port", Str("int".into()));
+    /// rules.add("host", Str("str".into()));
+    /// let mut schema = Schema::from_map(&rules).unwrap();
+    /// schema.fix_with("port", |value| value?.as_str()?.trim().parse().ok().map(Int));
+    /// 
+    /// let mut cmap = CfgMap::new();
+    /// cmap.add("port", Str("8080".into()));
+    /// 
+    /// let report = schema.validate_and_fix(&mut cmap);
+    /// assert_eq!(report.to_string(), "`host` is missing\nfixed `port`");
+    /// assert!(cmap.get("port").check_that(IsExactlyInt(8080)));
+    /// ```
+    pub fn validate_and_fix(&self, map: &mut CfgMap) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        for (path, condition) in &self.rules {
+            if self.skips(map, path) {
+                continue;
+            }
+            let failure = match map.check_path(path, condition) {
+                Ok(()) => continue,
+                Err(failure) => failure,
+            };
+
+            let fixed = self.fixers.get(path)
+                .and_then(|Fixer(fixer)| fixer(map.get(path)))
+                .filter(|value| condition.execute(value).to_bool())
+                .is_some_and(|value| map.add_force(path, value).is_ok());
+
+            if fixed {
+                report.fixed.push(path.clone());
+            } else {
+                report.push(self.severity_of(path), failure);
+            }
+        }
+        report
+    }
+
+    /// Converts the schema back into a map of conditions, which `from_map` loads into the same rules. Rules that
+    /// aren't errors start with their severity, such as `warning:`, followed by `optional:` if they're optional.
+    pub fn to_cfgmap(&self) -> CfgMap {
+        let mut map = CfgMap::new();
+        for (path, condition) in &self.rules {
+            let optional = if self.is_optional(path) { "optional: " } else { "" };
+            let text = match self.severity_of(path) {
+                Severity::Error => format!("{}{}", optional, condition),
+                severity => format!("{}: {}{}", severity, optional, condition),
+            };
+            map.add_force(path, CfgValue::Str(text)).expect("schema paths only go through maps");
+        }
+        map
+    }
+
+    /// Exports the schema as a JSON Schema document, so that editors and external validators can check
+    /// configurations against it. The document can be written as JSON using `CfgMap::to_writer`.
+    /// 
+    /// Every path within the schema is required, unless its rule is optional. Types, exact values, ranges, string and list lengths, allowed
+    /// strings and keys, and combinations using `&`, `|` and `!` are exported. Floats are exported as `number`,
+    /// which JSON Schema also allows integers for.
+    /// 
+    /// Conditions that JSON Schema can't describe, such as `IsDurationStr`, `IsExistingPath` or `Satisfies`, are
+    /// returned alongside the document, and their rules are exported as accepting any value. Since only errors
+    /// make a configuration invalid, rules that aren't errors aren't exported either.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, Checkable, Schema};
+    /// 
+    /// let mut rules = CfgMap::new();
+    /// rules.add("port", Str("int in 1..65535".into()));
+    /// rules.add("timeout", Str("duration_str".into()));
+    /// let (document, unexportable) = Schema::from_map(&rules).unwrap().to_json_schema();
+    /// 
+    /// assert!(document.get("properties/port/maximum").check_that(IsExactlyInt(65535)));
+    /// assert!(document.get("required").check_that(IsExactlyList(vec!["port".into(), "timeout".into()])));
+    /// assert_eq!(unexportable[0].to_string(), "`timeout` can't be exported, because of `IsDurationStr`");
+    /// ```
+    pub fn to_json_schema(&self) -> (CfgMap, Vec<Unexportable>) {
+        let errors: Vec<(String, Condition)> = self.rules.iter()
+            .filter(|(path, _)| self.severity_of(path) == Severity::Error)
+            .cloned()
+            .collect();
+        super::json_schema::export(&errors, |path| self.is_optional(path))
+    }
+
+    /// Gets the condition of the rule at `path`, if there's one.
+    fn rule(&self, path: &str) -> Option<&Condition> {
+        self.rules.iter().find(|(rule, _)| rule == path).map(|(_, condition)| condition)
+    }
+
+    /// Checks whether this schema is compatible with an `old` version of it, reporting every breaking change, so
+    /// that changes to a schema can be gated the same way as changes to an API.
+    /// 
+    /// A change is breaking if it removes a required rule, requires a path that wasn't required before, or narrows a rule so
+    /// that it might reject values the old rule accepted. Only rules with `Severity::Error` are enforced, so warnings
+    /// and infos can always be added, removed or narrowed.
+    /// 
+    /// Rules are compared by their types, ranges and the strings they allow, including through `&` and `|`. Any other
+    /// change to a rule, such as changing a `!`, is assumed to narrow it, since it can't be compared in general.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Schema};
+    /// 
+    /// let mut old = CfgMap::new();
+    /// old.add("port", Str("int in 1..65535".into()));
+    /// old.add("host", Str("str".into()));
+    /// let old = Schema::from_map(&old).unwrap();
+    /// 
+    /// let mut new = CfgMap::new();
+    /// new.add("port", Str("int".into()));
+    /// new.add("host", Str("str".into()));
+    /// assert!(Schema::from_map(&new).unwrap().is_compatible_with(&old).is_ok());
+    /// 
+    /// new.add("port", Str("int in 1024..65535".into()));
+    /// let breaking = Schema::from_map(&new).unwrap().is_compatible_with(&old).unwrap_err();
+    /// assert_eq!(breaking[0].to_string(), "`port` was narrowed from `IsIntInRange(1, 65535)` to `IsIntInRange(1024, 65535)`");
+    /// ```
+    pub fn is_compatible_with(&self, old: &Schema) -> Result<(), Vec<Incompatibility>> {
+        let mut breaking = Vec::new();
+
+        for (path, old_condition) in &old.rules {
+            let enforced = old.severity_of(path) == Severity::Error;
+            let condition = match self.rule(path) {
+                Some(condition) => condition,
+                None if enforced && !old.is_optional(path) => {
+                    breaking.push(Incompatibility::Removed { path: path.clone() });
+                    continue;
+                },
+                None => continue,
+            };
+
+            // Rules that aren't errors accept everything.
+            let old_condition = if enforced { old_condition.clone() } else { Condition::TRUE };
+            if self.severity_of(path) == Severity::Error && !super::compat::accepts_all(condition, &old_condition) {
+                breaking.push(Incompatibility::Narrowed {
+                    path: path.clone(),
+                    from: old_condition.to_string(),
+                    to: condition.to_string(),
+                });
+            }
+        }
+
+        for (path, _) in &self.rules {
+            let required = self.severity_of(path) == Severity::Error && !self.is_optional(path);
+            let required_before = old.rule(path).is_some() && old.severity_of(path) == Severity::Error && !old.is_optional(path);
+            if required && !required_before {
+                breaking.push(Incompatibility::Added { path: path.clone() });
+            }
+        }
+
+        if breaking.is_empty() { Ok(()) } else { Err(breaking) }
+    }
+
+    /// Reports how well the schema covers `samples`: the rules whose value is missing from every sample, and the
+    /// values within the samples that no rule checks. This helps keep large schemas in step with the configurations
+    /// they describe, such as by checking the coverage of every example configuration in a test.
+    /// 
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Schema};
+    /// 
+    /// let mut rules = CfgMap::new();
+    /// rules.add("port", Str("int".into()));
+    /// rules.add("legacy", Str("optional: bool".into()));
+    /// let schema = Schema::from_map(&rules).unwrap();
+    /// 
+    /// let mut sample = CfgMap::new();
+    /// sample.add("port", Int(80));
+    /// sample.add("debug", Bool(true));
+    /// 
+    /// let coverage = schema.coverage([&sample]);
+    /// assert_eq!(coverage.unexercised, vec!["legacy"]);
+    /// assert_eq!(coverage.uncovered, vec!["debug"]);
+    /// assert_eq!(coverage.to_string(), "`legacy` is never exercised\n`debug` has no rule");
+    /// ```
+    pub fn coverage<'a, I: IntoIterator<Item = &'a CfgMap>>(&self, samples: I) -> Coverage {
+        super::coverage::coverage(self, samples)
+    }
+
+    /// Checks every rule with `Severity::Error` against `map`, stopping at the first failure.
+    pub fn check(&self, map: &CfgMap) -> Result<(), CheckError> {
+        self.applicable(map)
+            .filter(|(path, _)| self.severity_of(path) == Severity::Error)
+            .try_for_each(|(path, condition)| map.check_path(path, condition))
+    }
+}
+
+#[cfg(all(test, feature = "from_json"))]
+mod test {
+    use crate::{CfgMap, CheckError, CfgValue::*, Incompatibility, Schema, SchemaError, Severity, Unexportable};
+
+    #[test]
+    fn json_schema() {
+        let schema = CfgMap::from_json(serde_json::json!({
+            "server": { "port": "int in 1..65535", "host": "str & non_empty_str" },
+            "workers": "int | parsable_int",
+        }));
+        let schema = Schema::from_map(&schema).unwrap();
+        let paths: Vec<&str> = schema.rules().iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(paths, vec!["server/host", "server/port", "workers"]);
+
+        let cmap = CfgMap::from_json(serde_json::json!({
+            "server": { "port": 0, "host": "" },
+            "workers": "4",
+        }));
+        assert_eq!(schema.validate(&cmap).to_string(), [
+            "`server/host` doesn't satisfy `IsStr & IsNonEmptyStr`",
+            "`server/port` doesn't satisfy `IsIntInRange(1, 65535)`",
+        ].join("\n"));
+        assert_eq!(schema.check(&cmap).unwrap_err().path(), "server/host");
+
+        let mut invalid = CfgMap::new();
+        invalid.add("port", Str("int in 1".into())).unwrap();
+        assert!(matches!(Schema::from_map(&invalid), Err(SchemaError::Invalid { path, .. }) if path == "port"));
+        invalid.add("port", Int(1)).unwrap();
+        assert_eq!(Schema::from_map(&invalid).unwrap_err().to_string(), "`port` isn't a condition or a map of conditions");
+    }
+
+    #[test]
+    fn severities() {
+        let schema = CfgMap::from_json(serde_json::json!({
+            "port": "int",
+            "workers": "warning: int in 1..64",
+            "legacy": "info: !str",
+        }));
+        let mut schema = Schema::from_map(&schema).unwrap();
+        assert_eq!(schema.severity_of("workers"), Severity::Warning);
+
+        let cmap = CfgMap::from_json(serde_json::json!({ "port": 80, "workers": 128, "legacy": "on" }));
+        let report = schema.validate(&cmap);
+        assert!(report.is_valid());
+        assert_eq!(report.to_string(), [
+            "warning: `workers` doesn't satisfy `IsIntInRange(1, 64)`",
+            "info: `legacy` doesn't satisfy `!IsStr`",
+        ].join("\n"));
+        assert_eq!(schema.check(&cmap), Ok(()));
+
+        schema.set_severity("workers", Severity::Error);
+        let report = schema.validate(&cmap);
+        assert_eq!(report.entries().map(|(severity, _)| severity).collect::<Vec<_>>(), vec![Severity::Error, Severity::Info]);
+        assert!(report.into_result().is_err());
+    }
+
+    #[test]
+    fn fixes() {
+        let schema = CfgMap::from_json(serde_json::json!({
+            "level": "exactly_str(\"info\") | exactly_str(\"debug\")",
+            "workers": "int",
+            "logging": "map",
+            "port": "int",
+            "name": "str",
+        }));
+        let mut schema = Schema::from_map(&schema).unwrap();
+        schema
+            .fix_with("level", |value| value?.as_str().map(|s| Str(s.to_lowercase())))
+            .fix_with("workers", |value| value?.as_str()?.parse().ok().map(Int))
+            .fix_default("logging", Map(CfgMap::new()))
+            .fix_default("port", Str("80".into()))
+            .fix_default("name", Str("app".into()));
+
+        let mut cmap = CfgMap::from_json(serde_json::json!({ "level": "DEBUG", "workers": "4", "port": "x", "name": 5 }));
+        cmap.lock_path("name");
+
+        let report = schema.validate_and_fix(&mut cmap);
+        assert_eq!(report.fixed, vec!["level", "logging", "workers"]);
+        let failed: Vec<&str> = report.failures.iter().map(|failure| failure.path()).collect();
+        assert_eq!(failed, vec!["name", "port"]);
+
+        assert_eq!(cmap.get("level"), Some(&Str("debug".into())));
+        assert_eq!(cmap.get("workers"), Some(&Int(4)));
+        assert_eq!(cmap.get("logging"), Some(&Map(CfgMap::new())));
+        assert_eq!(cmap.get("port"), Some(&Str("x".into())));
+        assert_eq!(cmap.get("name"), Some(&Int(5)));
+    }
+
+    #[test]
+    fn compatibility() {
+        let schema = |json| Schema::from_map(&CfgMap::from_json(json)).unwrap();
+        let old = schema(serde_json::json!({
+            "port": "int in 1..65535",
+            "level": "one_of(\"info\", \"debug\")",
+            "ratio": "float in 0.0..1.0 | exactly_int(0)",
+            "tags": "list_with(str)",
+            "name": "str",
+            "legacy": "warning: str",
+        }));
+
+        let widened = schema(serde_json::json!({
+            "port": "int",
+            "level": "one_of(\"info\", \"debug\", \"trace\") | exactly_str(\"off\")",
+            "ratio": "float | int",
+            "tags": "list",
+            "name": "str",
+            "legacy": "warning: int",
+            "extra": "info: str",
+        }));
+        assert_eq!(widened.is_compatible_with(&old), Ok(()));
+        assert_eq!(old.is_compatible_with(&old), Ok(()));
+
+        let narrowed = schema(serde_json::json!({
+            "port": "int in 1024..65535",
+            "level": "exactly_str(\"info\")",
+            "ratio": "float in 0.0..1.0",
+            "tags": "list_with(str) & list_len(2)",
+            "host": "str",
+        }));
+        let breaking = narrowed.is_compatible_with(&old).unwrap_err();
+        let paths: Vec<&str> = breaking.iter().map(Incompatibility::path).collect();
+        assert_eq!(paths, vec!["level", "name", "port", "ratio", "tags", "host"]);
+        assert_eq!(breaking[1], Incompatibility::Removed { path: "name".into() });
+        assert_eq!(breaking[5].to_string(), "`host` is now required");
+    }
+
+    #[test]
+    fn exports() {
+        let rules = CfgMap::from_json(serde_json::json!({
+            "server": { "port": "int in 1..65535", "host": "str & !exactly_str(\"\")" },
+            "level": "one_of(\"info\", \"debug\")",
+            "tags": "list_with(starts_with(\"a.b\")) | exactly_int(0)",
+            "paths": "list_with(existing_path)",
+            "legacy": "warning: map",
+        }));
+        let schema = Schema::from_map(&rules).unwrap();
+        assert_eq!(Schema::from_map(&schema.to_cfgmap()).unwrap().to_cfgmap(), schema.to_cfgmap());
+        assert_eq!(schema.to_cfgmap().get("legacy"), Some(&Str("warning: IsMap".into())));
+
+        let (document, unexportable) = schema.to_json_schema();
+        let expected = serde_json::json!({
+            "$schema": crate::JSON_SCHEMA_DRAFT,
+            "type": "object",
+            "required": ["level", "paths", "server", "tags"],
+            "properties": {
+                "level": { "type": "string", "enum": ["info", "debug"] },
+                "paths": {},
+                "server": {
+                    "type": "object",
+                    "required": ["host", "port"],
+                    "properties": {
+                        "host": { "allOf": [{ "type": "string" }, { "not": { "type": "string", "const": "" } }] },
+                        "port": { "type": "integer", "minimum": 1, "maximum": 65535 },
+                    },
+                },
+                "tags": { "anyOf": [
+                    { "type": "array", "items": { "type": "string", "pattern": "^a\\.b" } },
+                    { "type": "integer", "const": 0 },
+                ] },
+            },
+        });
+        assert_eq!(document, CfgMap::from_json(expected));
+        assert_eq!(unexportable, vec![Unexportable { path: "paths".into(), condition: "IsExistingPath".into() }]);
+    }
+
+    #[test]
+    fn imports() {
+        let document = CfgMap::from_json(serde_json::json!({
+            "$schema": crate::JSON_SCHEMA_DRAFT,
+            "type": "object",
+            "required": ["server"],
+            "properties": {
+                "server": {
+                    "type": "object",
+                    "description": "Where to listen.",
+                    "required": ["port"],
+                    "properties": {
+                        "port": { "type": "integer", "minimum": 1, "maximum": 65535 },
+                        "host": { "type": "string" },
+                    },
+                },
+                "level": { "enum": ["info", "debug"] },
+                "tags": { "type": "array", "items": { "type": "object", "required": ["name"] } },
+            },
+        }));
+        let schema = Schema::from_json_schema(&document).unwrap();
+        let paths: Vec<&str> = schema.rules().iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(paths, vec!["level", "server/host", "server/port", "tags"]);
+        assert!(schema.is_optional("level") && schema.is_optional("server/host") && !schema.is_optional("server/port"));
+        assert_eq!(schema.to_cfgmap().get("server/host"), Some(&Str("optional: IsStr".into())));
+
+        let mut cmap = CfgMap::from_json(serde_json::json!({ "server": { "port": 8080 } }));
+        assert!(schema.validate(&cmap).is_valid());
+        assert_eq!(Schema::from_map(&schema.to_cfgmap()).unwrap().to_cfgmap(), schema.to_cfgmap());
+        assert!(schema.is_compatible_with(&Schema::from_map(&schema.to_cfgmap()).unwrap()).is_ok());
+
+        cmap.add("tags", List(vec![Map(CfgMap::new())])).unwrap();
+        cmap.add("server/port", Int(0)).unwrap();
+        let report = schema.validate(&cmap);
+        let failures: Vec<&str> = report.failures.iter().map(CheckError::path).collect();
+        assert_eq!(failures, vec!["server/port", "tags"]);
+        assert!(schema.validate(&CfgMap::new()).failures.iter().any(|failure| failure.path() == "server/port"));
+
+        let unsupported = CfgMap::from_json(serde_json::json!({ "properties": { "name": { "type": "string", "pattern": "^a" } } }));
+        assert_eq!(
+            Schema::from_json_schema(&unsupported).unwrap_err(),
+            SchemaError::Unsupported { path: "name".into(), keyword: "pattern".into() },
+        );
+    }
+
+    #[test]
+    fn coverage() {
+        let rules = CfgMap::from_json(serde_json::json!({
+            "server": { "port": "int", "host": "optional: str" },
+            "tags": "list_with(str)",
+        }));
+        let schema = Schema::from_map(&rules).unwrap();
+        let first = CfgMap::from_json(serde_json::json!({
+            "server": { "port": 80, "timeout": 5 },
+            "tags": ["a", "b"],
+        }));
+        let second = CfgMap::from_json(serde_json::json!({
+            "server": { "port": 81, "tls": { "cert": "a.pem" } },
+            "debug": true,
+        }));
+
+        let coverage = schema.coverage([&first, &second]);
+        assert_eq!(coverage.unexercised, vec!["server/host"]);
+        assert_eq!(coverage.uncovered, vec!["debug", "server/timeout", "server/tls/cert"]);
+        assert!(!coverage.is_complete());
+
+        let complete = CfgMap::from_json(serde_json::json!({ "server": { "port": 80, "host": "a" }, "tags": [] }));
+        assert!(schema.coverage([&complete]).is_complete());
+        assert_eq!(schema.coverage([]).unexercised.len(), 3);
+    }
+}
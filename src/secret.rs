@@ -0,0 +1,93 @@
+use super::{CfgMap, CfgValue};
+use std::collections::HashSet;
+use std::fmt;
+
+/// Formats a map the same way as its `Debug` implementation, but with the values at `secrets` redacted.
+#[derive(Clone, Copy)]
+pub(crate) struct RedactedMap<'a> {
+    pub(crate) map: &'a CfgMap,
+    pub(crate) path: &'a str,
+    pub(crate) secrets: &'a HashSet<String>,
+}
+
+/// Formats the entries of a map, redacting the values at `secrets`.
+struct Entries<'a>(RedactedMap<'a>);
+
+/// Formats a value the same way as its `Debug` implementation, but with the values at `secrets` redacted.
+struct RedactedValue<'a> {
+    value: &'a CfgValue,
+    path: String,
+    secrets: &'a HashSet<String>,
+}
+
+impl fmt::Debug for RedactedMap<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CfgMap")
+            .field("internal_map", &Entries(*self))
+            .field("default", &self.map.default)
+            .field("fallbacks", &self.map.fallbacks)
+            .finish()
+    }
+}
+
+impl fmt::Debug for Entries<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let RedactedMap { map, path, secrets } = self.0;
+
+        f.debug_map()
+            .entries(map.iter().map(|(key, value)| {
+                (key, RedactedValue { value, path: super::join_path(path, key), secrets })
+            }))
+            .finish()
+    }
+}
+
+impl fmt::Debug for RedactedValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.secrets.contains(&self.path) {
+            return f.write_str("***");
+        }
+
+        match self.value {
+            CfgValue::Map(map) => f.debug_tuple("Map")
+                .field(&RedactedMap { map, path: &self.path, secrets: self.secrets })
+                .finish(),
+            CfgValue::List(list) => f.debug_tuple("List")
+                .field(&list.iter().enumerate().map(|(i, value)| {
+                    RedactedValue { value, path: super::join_path(&self.path, &i.to_string()), secrets: self.secrets }
+                }).collect::<Vec<_>>())
+                .finish(),
+            value => value.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CfgMap, CfgValue::*};
+
+    #[test]
+    fn redacted_debug() {
+        let mut cmap = CfgMap::new();
+        cmap.add("db", Map(CfgMap::new())).unwrap();
+        cmap.add("db/password", Str("hunter2".into())).unwrap();
+        cmap.add("db/port", Int(5432)).unwrap();
+        cmap.add("keys", list!["secret-key"]).unwrap();
+        cmap.add_from("env", "token", Str("abc123".into())).unwrap();
+
+        cmap.mark_secret("**/password");
+        cmap.mark_secret("keys/0");
+        cmap.mark_secret("token");
+
+        for output in &[format!("{:?}", cmap), format!("{:#?}", cmap)] {
+            assert!(!output.contains("hunter2"));
+            assert!(!output.contains("secret-key"));
+            assert!(!output.contains("abc123"));
+            assert!(output.contains("***"));
+            assert!(output.contains("5432"));
+        }
+
+        assert!(cmap.is_secret("db/password"));
+        assert!(!cmap.is_secret("db/port"));
+    }
+}
@@ -0,0 +1,104 @@
+use super::{CfgError, CfgMap, CfgValue, Checkable, Condition};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// A `CfgMap` that can be shared and changed between threads. Only available if using `sync`.
+///
+/// Cloning it is cheap, and every clone refers to the same map. Since values can't be borrowed
+/// past the lock, getters return copies of the values. Use `read` and `write` for anything else.
+///
+/// ## Examples
+/// ```
+/// use cfgmap::{CfgMap, CfgValue::*, Condition::*, SharedCfgMap};
+/// use std::thread;
+///
+/// let mut cmap = CfgMap::new();
+/// cmap.add("port", Int(80));
+///
+/// let shared = SharedCfgMap::new(cmap);
+/// let handle = shared.clone();
+///
+/// thread::spawn(move || handle.add("port", Int(8080)).unwrap()).join().unwrap();
+///
+/// assert!(shared.check_that("port", IsExactlyInt(8080)));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SharedCfgMap {
+    map: Arc<RwLock<CfgMap>>,
+}
+
+impl SharedCfgMap {
+    /// Wraps `map` so that it can be shared.
+    pub fn new(map: CfgMap) -> SharedCfgMap {
+        SharedCfgMap { map: Arc::new(RwLock::new(map)) }
+    }
+
+    /// Locks the map for reading, blocking until it's available.
+    ///
+    /// A panic while the map was locked doesn't prevent it from being used afterwards,
+    /// since every change to a `CfgMap` leaves it in a valid state.
+    pub fn read(&self) -> RwLockReadGuard<'_, CfgMap> {
+        self.map.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Locks the map for writing, blocking until it's available.
+    ///
+    /// See `read` for how panics are handled.
+    pub fn write(&self) -> RwLockWriteGuard<'_, CfgMap> {
+        self.map.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Gets a copy of the value at `key`. See `CfgMap::get` for more details.
+    pub fn get(&self, key: &str) -> Option<CfgValue> {
+        self.read().get(key).cloned()
+    }
+
+    /// Checks whether the value at `key` satisfies `condition`.
+    pub fn check_that(&self, key: &str, condition: Condition) -> bool {
+        self.read().get(key).check_that(condition)
+    }
+
+    /// Gets a copy of an option. See `CfgMap::get_option` for more details.
+    pub fn get_option(&self, category: &str, option: &str) -> Option<CfgValue> {
+        self.read().get_option(category, option).cloned()
+    }
+
+    /// Updates an option. See `CfgMap::update_option` for more details.
+    pub fn update_option(&self, category: &str, option: &str, to: CfgValue) -> Result<Option<CfgValue>, CfgError> {
+        self.write().update_option(category, option, to)
+    }
+
+    /// Adds a new entry. See `CfgMap::add` for more details.
+    pub fn add(&self, key: &str, value: CfgValue) -> Result<Option<CfgValue>, CfgError> {
+        self.write().add(key, value)
+    }
+
+    /// Gets a copy of the whole map.
+    pub fn snapshot(&self) -> CfgMap {
+        self.read().clone()
+    }
+
+    /// Atomically replaces the whole map with `map`, such as after reloading it, returning the old map.
+    ///
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgValue::*, Condition::*, SharedCfgMap};
+    ///
+    /// let shared = SharedCfgMap::new(CfgMap::new());
+    ///
+    /// let mut reloaded = CfgMap::new();
+    /// reloaded.add("port", Int(80));
+    ///
+    /// let old = shared.replace(reloaded);
+    /// assert!(old.is_empty());
+    /// assert!(shared.check_that("port", IsExactlyInt(80)));
+    /// ```
+    pub fn replace(&self, map: CfgMap) -> CfgMap {
+        std::mem::replace(&mut *self.write(), map)
+    }
+}
+
+impl From<CfgMap> for SharedCfgMap {
+    fn from(map: CfgMap) -> Self {
+        SharedCfgMap::new(map)
+    }
+}
@@ -0,0 +1,67 @@
+use super::{CfgMap, Format};
+use std::path::Path;
+
+/// The environment variable which, when set to anything other than `0`, makes `assert_cfg_snapshot!`
+/// write snapshots instead of comparing against them.
+pub const UPDATE_SNAPSHOTS: &str = "CFGMAP_UPDATE_SNAPSHOTS";
+
+/// Compares `map`, written as toml, against the snapshot at `path`, or writes it there if `update` is set.
+fn check(map: &CfgMap, path: &Path, update: bool) -> Result<(), String> {
+    let mut written = Vec::new();
+    map.to_writer(&mut written, Format::Toml).map_err(|e| format!("couldn't write the map as toml: {}", e))?;
+    let written = String::from_utf8(written).expect("toml is always written as utf-8");
+
+    if update {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("couldn't create `{}`: {}", parent.display(), e))?;
+        }
+        return std::fs::write(path, written).map_err(|e| format!("couldn't write `{}`: {}", path.display(), e));
+    }
+
+    let stored = std::fs::read_to_string(path).map_err(|e| {
+        format!("couldn't read the snapshot `{}`: {}\nset {}=1 to create it", path.display(), e, UPDATE_SNAPSHOTS)
+    })?;
+    if stored == written {
+        Ok(())
+    } else {
+        Err(format!(
+            "the map doesn't match the snapshot `{}`\n--- snapshot\n{}\n--- map\n{}\nset {}=1 to update it",
+            path.display(), stored.trim_end(), written.trim_end(), UPDATE_SNAPSHOTS,
+        ))
+    }
+}
+
+#[doc(hidden)]
+/// Used by `assert_cfg_snapshot!`, which resolves `path` relative to the crate being tested.
+pub fn __assert_cfg_snapshot(map: &CfgMap, path: &Path) {
+    let update = std::env::var_os(UPDATE_SNAPSHOTS).is_some_and(|value| value != "0");
+    if let Err(message) = check(map, path, update) {
+        panic!("{}", message);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::check;
+    use crate::{CfgMap, CfgValue::*};
+
+    #[test]
+    fn snapshots() {
+        let mut cmap = CfgMap::new();
+        cmap.add("name", Str("app".into())).unwrap();
+        cmap.add_force("db/port", Int(5432)).unwrap();
+
+        let path = std::env::temp_dir().join(format!("cfgmap-snapshot-{}", std::process::id())).join("startup.toml");
+        assert!(check(&cmap, &path, false).unwrap_err().contains("CFGMAP_UPDATE_SNAPSHOTS=1 to create it"));
+
+        check(&cmap, &path, true).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "name = \"app\"\n\n[db]\nport = 5432\n");
+        crate::assert_cfg_snapshot!(cmap, &path);
+
+        cmap.add("db/port", Int(5433)).unwrap();
+        let message = check(&cmap, &path, false).unwrap_err();
+        assert!(message.contains("port = 5432") && message.contains("port = 5433"));
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,171 @@
+use super::{CfgMap, CfgValue, Merger};
+use std::collections::BTreeMap;
+
+/// A collection of named `CfgMap`s, such as one per tenant or profile, which share a map of defaults.
+///
+/// Each name only stores its own overrides. Reading a value falls back to the defaults if it isn't
+/// overridden, and `resolve` merges the two into a complete map, the same way as `CfgMap::options_in`.
+///
+/// ## Examples
+/// ```
+/// use cfgmap::{CfgMap, CfgStore, CfgValue::*, Checkable, Condition::*};
+///
+/// let mut defaults = CfgMap::new();
+/// defaults.add("plan", Str("free".into()));
+/// defaults.add("limits", Map(CfgMap::new()));
+/// defaults.add("limits/users", Int(5));
+/// defaults.add("limits/storage", Int(1));
+///
+/// let mut acme = CfgMap::new();
+/// acme.add("limits", Map(CfgMap::new()));
+/// acme.add("limits/users", Int(500));
+///
+/// let mut store = CfgStore::new(defaults);
+/// store.insert("acme", acme);
+/// store.insert("initech", CfgMap::new());
+///
+/// assert!(store.get("acme", "limits/users").check_that(IsExactlyInt(500)));
+/// assert!(store.get("acme", "plan").check_that(IsExactlyStr("free".into())));
+/// assert!(store.get("initech", "limits/users").check_that(IsExactlyInt(5)));
+/// assert!(store.get("unknown", "plan").is_none());
+///
+/// let acme = store.resolve("acme").unwrap();
+/// assert!(acme.get("limits/storage").check_that(IsExactlyInt(1)));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CfgStore {
+    defaults: CfgMap,
+    overrides: BTreeMap<String, CfgMap>,
+}
+
+impl CfgStore {
+    /// Creates an empty store, where every name falls back to `defaults`.
+    pub fn new(defaults: CfgMap) -> CfgStore {
+        CfgStore { defaults, overrides: BTreeMap::new() }
+    }
+
+    /// Gets the defaults shared by every name.
+    pub fn defaults(&self) -> &CfgMap {
+        &self.defaults
+    }
+
+    /// Gets the defaults shared by every name, for changing them.
+    pub fn defaults_mut(&mut self) -> &mut CfgMap {
+        &mut self.defaults
+    }
+
+    /// Adds `name` with its own `overrides`, returning the overrides it had before, if any.
+    pub fn insert(&mut self, name: &str, overrides: CfgMap) -> Option<CfgMap> {
+        self.overrides.insert(name.to_string(), overrides)
+    }
+
+    /// Removes `name`, returning its overrides.
+    pub fn remove(&mut self, name: &str) -> Option<CfgMap> {
+        self.overrides.remove(name)
+    }
+
+    /// Checks whether `name` was added to the store.
+    pub fn contains(&self, name: &str) -> bool {
+        self.overrides.contains_key(name)
+    }
+
+    /// Iterates over every name within the store, in sorted order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.overrides.keys().map(String::as_str)
+    }
+
+    /// Gets the overrides of `name`, without the defaults.
+    pub fn overrides(&self, name: &str) -> Option<&CfgMap> {
+        self.overrides.get(name)
+    }
+
+    /// Gets the overrides of `name`, without the defaults, for changing them.
+    pub fn overrides_mut(&mut self, name: &str) -> Option<&mut CfgMap> {
+        self.overrides.get_mut(name)
+    }
+
+    /// Gets the value at `key` for `name`, falling back to the defaults if it isn't overridden.
+    /// Returns `None` if `name` isn't within the store.
+    pub fn get(&self, name: &str, key: &str) -> Option<&CfgValue> {
+        self.overrides.get(name)?.get(key).or_else(|| self.defaults.get(key))
+    }
+
+    /// Gets the complete map of `name`, which is the defaults merged with its overrides.
+    /// Where both contain a map for the same key, the maps are merged too.
+    pub fn resolve(&self, name: &str) -> Option<CfgMap> {
+        let overrides = self.overrides.get(name)?;
+
+        let mut resolved = self.defaults.contents();
+        Merger::new().merge_unchecked(&mut resolved, overrides);
+        Some(resolved)
+    }
+
+    /// Validates the complete map of every name using `validate`, returning every failure along with its name.
+    ///
+    /// ## Examples
+    /// ```
+    /// use cfgmap::{CfgMap, CfgStore, CfgValue::*, Checkable, Condition::*};
+    ///
+    /// let mut defaults = CfgMap::new();
+    /// defaults.add("port", Int(80));
+    ///
+    /// let mut broken = CfgMap::new();
+    /// broken.add("port", Str("eighty".into()));
+    ///
+    /// let mut store = CfgStore::new(defaults);
+    /// store.insert("ok", CfgMap::new());
+    /// store.insert("broken", broken);
+    ///
+    /// let failures = store.validate(|_, map| {
+    ///     if map.get("port").check_that(IsInt) { Ok(()) } else { Err("`port` must be an integer") }
+    /// });
+    ///
+    /// assert_eq!(failures, vec![("broken".to_string(), "`port` must be an integer")]);
+    /// ```
+    pub fn validate<E, F>(&self, mut validate: F) -> Vec<(String, E)>
+        where F: FnMut(&str, &CfgMap) -> Result<(), E>
+    {
+        self.overrides.keys()
+            .filter_map(|name| {
+                let resolved = self.resolve(name)?;
+                validate(name, &resolved).err().map(|e| (name.clone(), e))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CfgMap, CfgStore, CfgValue::*, Checkable, Condition::*};
+
+    #[test]
+    fn overrides_and_defaults() {
+        let mut defaults = CfgMap::new();
+        defaults.add("db", Map(CfgMap::new())).unwrap();
+        defaults.add("db/host", Str("localhost".into())).unwrap();
+        defaults.add("db/port", Int(5432)).unwrap();
+
+        let mut store = CfgStore::new(defaults);
+        store.insert("b", CfgMap::new());
+        store.insert("a", CfgMap::new());
+        store.overrides_mut("a").unwrap().add("db", Map(CfgMap::new())).unwrap();
+        store.overrides_mut("a").unwrap().add("db/port", Int(6543)).unwrap();
+
+        assert_eq!(store.names().collect::<Vec<_>>(), vec!["a", "b"]);
+
+        // Values missing from a partial map within the overrides still fall back to the defaults.
+        assert!(store.get("a", "db/host").check_that(IsExactlyStr("localhost".into())));
+        let a = store.resolve("a").unwrap();
+        assert!(a.get("db/host").check_that(IsExactlyStr("localhost".into())));
+        assert!(a.get("db/port").check_that(IsExactlyInt(6543)));
+
+        // Changing the defaults affects every name.
+        store.defaults_mut().add("db/host", Str("db.local".into())).unwrap();
+        assert!(store.get("b", "db/host").check_that(IsExactlyStr("db.local".into())));
+        assert!(store.resolve("a").unwrap().get("db/host").check_that(IsExactlyStr("db.local".into())));
+
+        assert!(store.remove("a").is_some());
+        assert!(!store.contains("a"));
+        assert!(store.resolve("a").is_none());
+    }
+}
@@ -0,0 +1,66 @@
+use super::CfgMap;
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+/// A set of paths that can be recorded through a shared reference, such as
+/// the paths read from a `CfgMap` while tracking access.
+#[derive(Debug, Default)]
+pub(crate) struct PathLog {
+    paths: Mutex<HashSet<String>>,
+}
+
+impl PathLog {
+    /// Records `path`, returning whether it wasn't recorded before.
+    pub(crate) fn record(&self, path: &str) -> bool {
+        let mut paths = self.paths.lock().unwrap_or_else(|e| e.into_inner());
+        !paths.contains(path) && paths.insert(path.to_string())
+    }
+
+    /// Gets every value within `map` that was never read, including as part of a map or list that was read.
+    pub(crate) fn unused(&self, map: &CfgMap) -> Vec<String> {
+        let paths = self.paths.lock().unwrap_or_else(|e| e.into_inner());
+
+        map.walk()
+            .map(|(path, _)| path)
+            .filter(|path| !paths.iter().any(|read| read == path || super::is_within(path, read)))
+            .collect()
+    }
+}
+
+impl Clone for PathLog {
+    fn clone(&self) -> PathLog {
+        let paths = self.paths.lock().unwrap_or_else(|e| e.into_inner());
+        PathLog { paths: Mutex::new(paths.clone()) }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CfgMap, CfgValue::*};
+
+    #[test]
+    fn unused_keys() {
+        let mut cmap = CfgMap::new();
+        cmap.add("server", Map(CfgMap::new())).unwrap();
+        cmap.add("server/port", Int(80)).unwrap();
+        cmap.add("server/prot", Str("http".into())).unwrap();
+        cmap.add("list", list![1, 2]).unwrap();
+        cmap.add("default", Map(CfgMap::new())).unwrap();
+        cmap.add("default/timeout", Int(5)).unwrap();
+        cmap.default = "default/".into();
+
+        // Nothing is read before tracking starts, nor by changes.
+        cmap.get("list");
+        cmap.track_access();
+        cmap.add("list/-", Int(3)).unwrap();
+        cmap.move_path("server/prot", "server/protocol").unwrap();
+
+        cmap.get("server/port");
+        cmap.get("server/missing");
+        cmap.get_option("server", "timeout");
+        assert_eq!(cmap.unused_keys(), vec!["list/0", "list/1", "list/2", "server/protocol"]);
+
+        cmap.get("list");
+        assert_eq!(cmap.unused_keys(), vec!["server/protocol"]);
+    }
+}
@@ -0,0 +1,332 @@
+use super::{from_json, CfgError, CfgMap, CfgValue};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::ops::{Deref, DerefMut};
+
+/// Starts the line of the `i`th entry within a map or list at `path`, whose key is `key`, returning its path.
+fn start_line(path: &str, key: &str, i: usize, text: &mut String, paths: &mut Vec<String>) -> String {
+    let path = super::join_path(path, key);
+    text.push_str(if i == 0 { "\n" } else { "\n," });
+    paths.push(path.clone());
+    path
+}
+
+/// Writes `value` as json, starting each value within it on a new line, and pushes the path of the value
+/// that each new line belongs to onto `paths`. Closing brackets are on their own line, which belongs to the
+/// map or list that they close.
+fn write_lines(value: &Value, path: &str, text: &mut String, paths: &mut Vec<String>) {
+    let close = match value {
+        Value::Object(map) => {
+            text.push('{');
+            for (i, (key, value)) in map.iter().enumerate() {
+                let path = start_line(path, key, i, text, paths);
+                text.push_str(&Value::String(key.clone()).to_string());
+                text.push(':');
+                write_lines(value, &path, text, paths);
+            }
+            '}'
+        },
+        Value::Array(list) => {
+            text.push('[');
+            for (i, value) in list.iter().enumerate() {
+                let path = start_line(path, &i.to_string(), i, text, paths);
+                write_lines(value, &path, text, paths);
+            }
+            ']'
+        },
+        value => return text.push_str(&value.to_string()),
+    };
+
+    text.push('\n');
+    paths.push(path.to_string());
+    text.push(close);
+}
+
+/// Finds the path of the value within `json`, at `path`, that a `T` couldn't be deserialized from. `serde_json`
+/// only reports where an error is within a document, so the value is written with each value on its own line,
+/// and the line of the error is mapped back to a path.
+fn failed_path<T: DeserializeOwned>(path: &str, json: &Value) -> String {
+    let mut text = String::new();
+    let mut paths = vec![path.to_string()];
+    write_lines(json, path, &mut text, &mut paths);
+
+    match serde_json::from_str::<T>(&text) {
+        Err(e) if e.line() > 0 => paths.swap_remove(e.line().min(paths.len()) - 1),
+        _ => path.to_string(),
+    }
+}
+
+/// Deserializes `value` into a `T`, where `path` is its path within `map`. If it can't be deserialized, the path
+/// of the value that failed is reported, along with the source it came from, if it was recorded.
+pub(crate) fn deserialize<T: DeserializeOwned>(map: &CfgMap, path: &str, value: &CfgValue) -> Result<T, CfgError> {
+    let json = from_json::cfg_to_json(path, value)?;
+    T::deserialize(&json).map_err(|e| {
+        let failed = failed_path::<T>(path, &json);
+
+        // Options missing from `path` come from its defaults.
+        let option = failed.strip_prefix(path).unwrap_or("").trim_start_matches('/');
+        let source = std::iter::once(failed.clone())
+            .chain(if option.is_empty() { Vec::new() } else { map.default_paths(option) })
+            .find(|path| map.get_untracked(path).is_some())
+            .and_then(|path| map.provenance(&path).map(str::to_string));
+
+        let reason = match source {
+            Some(source) => format!("{} (from `{}`)", e, source),
+            None => e.to_string(),
+        };
+        CfgError::SyncFailed { path: failed, reason }
+    })
+}
+
+/// Gets the parts of `updated` that differ from `original`. For maps, this only contains the keys that changed.
+fn changes(original: &CfgValue, updated: CfgValue) -> Option<CfgValue> {
+    match (original, updated) {
+        (CfgValue::Map(original), CfgValue::Map(updated)) => {
+            let mut changed = CfgMap::new();
+            for (key, value) in updated.iter() {
+                let value = match original.internal_map.get(key) {
+                    Some(original) => changes(original, value.clone()),
+                    None => Some(value.clone()),
+                };
+                if let Some(value) = value {
+                    changed.internal_map_mut().insert(key.clone(), value);
+                }
+            }
+            if changed.is_empty() { None } else { Some(CfgValue::Map(changed)) }
+        },
+        (original, updated) => if *original == updated { None } else { Some(updated) },
+    }
+}
+
+/// Writes `changed`, as found by `changes`, into `map` at `path`. Maps that already exist are written into key by key,
+/// so that they keep their own state, such as their default path and fallbacks.
+fn write_changes(map: &mut CfgMap, path: &str, changed: CfgValue) -> Result<(), CfgError> {
+    match (map.get_untracked(path), changed) {
+        (Some(CfgValue::Map(_)), CfgValue::Map(changed)) => {
+            // Keys are written in order, so that the same error is reported every time.
+            let mut entries: Vec<(&String, &CfgValue)> = changed.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+
+            for (key, value) in entries {
+                write_changes(map, &super::join_path(path, key), value.clone())?;
+            }
+            Ok(())
+        },
+        (_, changed) => map.add_force(path, changed).map(|_| ()),
+    }
+}
+
+/// A typed copy of part of a `CfgMap`, which is written back into the map when dropped.
+/// Only available if using `typed`. See `CfgMap::sync` for more details.
+pub struct Synced<'a, T: Serialize> {
+    map: &'a mut CfgMap,
+    path: String,
+    value: T,
+    original: CfgValue,
+    done: bool,
+}
+
+impl<'a, T: Serialize + DeserializeOwned> Synced<'a, T> {
+    /// Deserializes `original`, to be written back into `map` at `path`.
+    pub(crate) fn new(map: &'a mut CfgMap, path: &str, original: CfgValue) -> Result<Synced<'a, T>, CfgError> {
+        let path = path.trim_end_matches('/').to_string();
+        let value = deserialize(map, &path, &original)?;
+
+        Ok(Synced { map, path, value, original, done: false })
+    }
+}
+
+impl<'a, T: Serialize> Synced<'a, T> {
+    /// Writes the typed copy back into the map, returning any error that would be ignored when dropped.
+    pub fn finish(mut self) -> Result<(), CfgError> {
+        self.done = true;
+        self.write()
+    }
+
+    /// Writes whatever changed within the typed copy back into the map. Other keys are left as they were.
+    fn write(&mut self) -> Result<(), CfgError> {
+        let json = serde_json::to_value(&self.value)
+            .map_err(|e| CfgError::SyncFailed { path: self.path.clone(), reason: e.to_string() })?;
+
+        let changed = match changes(&self.original, from_json::json_to_value(json)?) {
+            Some(changed) => changed,
+            None => return Ok(()),
+        };
+
+        // Changes are written into a copy first, so that nothing is written if any of them fail.
+        let mut updated = self.map.clone();
+        write_changes(&mut updated, &self.path, changed)?;
+        *self.map = updated;
+        Ok(())
+    }
+}
+
+impl<'a, T: Serialize> Deref for Synced<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<'a, T: Serialize> DerefMut for Synced<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<'a, T: Serialize> Drop for Synced<'a, T> {
+    fn drop(&mut self) {
+        if !self.done {
+            let _ = self.write();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CfgError, CfgMap, CfgValue::*, Checkable, Condition::*};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    struct Server {
+        host: String,
+        port: i64,
+        tags: Vec<String>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Service {
+        name: String,
+        server: Server,
+    }
+
+    #[test]
+    fn nested_defaults_only_write_changes() {
+        let mut cmap = CfgMap::new();
+        cmap.default = "default".into();
+        cmap.add("default", Map(CfgMap::new())).unwrap();
+        cmap.add("default/server", Map(CfgMap::new())).unwrap();
+        cmap.add("default/server/host", Str("localhost".into())).unwrap();
+        cmap.add("default/server/port", Int(80)).unwrap();
+        cmap.add("default/server/tags", list![]).unwrap();
+        cmap.add("api", Map(CfgMap::new())).unwrap();
+        cmap.add("api/name", Str("api".into())).unwrap();
+
+        let mut api = cmap.sync_options::<Service>("api").unwrap();
+        assert_eq!(api.server.host, "localhost");
+        api.server.port = 8080;
+        api.finish().unwrap();
+
+        assert!(cmap.get("api/server/port").check_that(IsExactlyInt(8080)));
+        assert!(cmap.get("api/server/host").is_none());
+        assert!(cmap.get("default/server/port").check_that(IsExactlyInt(80)));
+
+        // Missing fields without a default still fail.
+        cmap.add("default/server", Map(CfgMap::new())).unwrap();
+        assert!(matches!(cmap.options_as::<Service>("api"), Err(CfgError::SyncFailed { .. })));
+    }
+
+    #[test]
+    fn nested_maps_keep_their_state() {
+        let mut server = CfgMap::new();
+        server.default = "base".into();
+        server.fallbacks = vec!["shared".into()];
+        server.add("base", Map(CfgMap::new())).unwrap();
+        server.add("base/timeout", Int(30)).unwrap();
+        server.add("host", Str("localhost".into())).unwrap();
+        server.add("port", Int(80)).unwrap();
+        server.add("tags", list![]).unwrap();
+
+        let mut cmap = CfgMap::new();
+        cmap.add("server", Map(server)).unwrap();
+
+        let mut synced = cmap.sync::<Server>("server").unwrap();
+        synced.port = 8080;
+        synced.tags.push("a".into());
+        synced.finish().unwrap();
+
+        let server = cmap.get("server").unwrap().as_map().unwrap();
+        assert_eq!(server.default, "base");
+        assert_eq!(server.fallbacks, vec!["shared".to_string()]);
+        assert!(server.get_option("", "timeout").check_that(IsExactlyInt(30)));
+        assert!(server.get("port").check_that(IsExactlyInt(8080)));
+        assert!(server.get("tags").check_that(IsExactlyList(vec!["a".into()])));
+
+        // Nothing is written if any of the changes can't be.
+        cmap.lock_path("server/tags");
+        let mut synced = cmap.sync::<Server>("server").unwrap();
+        synced.port = 1;
+        synced.tags.clear();
+        assert_eq!(synced.finish(), Err(CfgError::Locked("server/tags".into())));
+        assert!(cmap.get("server/port").check_that(IsExactlyInt(8080)));
+    }
+
+    #[test]
+    fn errors_report_the_source() {
+        let mut cmap = CfgMap::new();
+        cmap.default = "default".into();
+        cmap.add_from("defaults.yaml", "default", Map(CfgMap::new())).unwrap();
+        cmap.add_from("defaults.yaml", "default/server", Map(CfgMap::new())).unwrap();
+        cmap.add_from("defaults.yaml", "default/server/host", Str("localhost".into())).unwrap();
+        cmap.add_from("defaults.yaml", "default/server/port", Str("eighty".into())).unwrap();
+        cmap.add("api", Map(CfgMap::new())).unwrap();
+        cmap.add_from("prod.yaml", "api/name", Int(1)).unwrap();
+        cmap.add("api/server", Map(CfgMap::new())).unwrap();
+        cmap.add_from("prod.yaml", "api/server/tags", list!["a", 2]).unwrap();
+
+        // The first field that fails is reported, along with the source it came from.
+        let error = cmap.options_as::<Service>("api").err().unwrap();
+        assert_eq!(error, CfgError::SyncFailed {
+            path: "api/name".into(),
+            reason: "invalid type: integer `1`, expected a string (from `prod.yaml`)".into(),
+        });
+
+        cmap.add("api/name", Str("api".into())).unwrap();
+        let error = cmap.options_as::<Service>("api").err().unwrap();
+        assert!(matches!(&error, CfgError::SyncFailed { path, reason }
+            if path == "api/server/port" && reason.ends_with("(from `defaults.yaml`)")));
+
+        cmap.add("api/server/port", Int(80)).unwrap();
+        let error = cmap.sync::<Server>("api/server").err().unwrap();
+        assert!(matches!(&error, CfgError::SyncFailed { path, reason }
+            if path == "api/server/tags/1" && reason.ends_with("(from `prod.yaml`)")));
+
+        // Values without a recorded source, or missing fields, are reported without one.
+        cmap.add("api/server/tags", list![]).unwrap();
+        let error = cmap.sync::<Server>("api/server").err().unwrap();
+        assert_eq!(error, CfgError::SyncFailed { path: "api/server".into(), reason: "missing field `host`".into() });
+    }
+
+    #[test]
+    fn write_back_and_keep_unknown_keys() {
+        let mut cmap = CfgMap::new();
+        cmap.add("server", Map(CfgMap::new())).unwrap();
+        cmap.add("server/host", Str("localhost".into())).unwrap();
+        cmap.add("server/port", Int(80)).unwrap();
+        cmap.add("server/tags", list!["a"]).unwrap();
+        cmap.add("server/extra", Bool(true)).unwrap();
+
+        {
+            let mut server = cmap.sync::<Server>("server").unwrap();
+            server.port = 8080;
+            server.tags.push("b".into());
+        }
+
+        assert!(cmap.get("server/port").check_that(IsExactlyInt(8080)));
+        assert!(cmap.get("server/tags").check_that(IsExactlyList(vec!["a".into(), "b".into()])));
+        assert!(cmap.get("server/extra").check_that(IsTrue));
+
+        // Changes to a locked path can't be written, but unchanged values can still be synced.
+        cmap.lock_path("server/port");
+        assert_eq!(cmap.sync::<Server>("server").unwrap().finish(), Ok(()));
+
+        let mut server = cmap.sync::<Server>("server").unwrap();
+        server.port = 1;
+        assert_eq!(server.finish(), Err(CfgError::Locked("server/port".into())));
+        assert!(cmap.get("server/port").check_that(IsExactlyInt(8080)));
+
+        assert!(matches!(cmap.sync::<Server>("server/host"), Err(CfgError::SyncFailed { .. })));
+        assert_eq!(cmap.sync::<Server>("missing").err(), Some(CfgError::InvalidPath("missing".into())));
+    }
+}
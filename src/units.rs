@@ -0,0 +1,110 @@
+use std::convert::TryFrom;
+use std::time::Duration;
+
+/// Splits a number, which may have a fractional part, from the start of `text`, followed by its unit
+/// made of letters, with any whitespace between them skipped. Returns the number's text, the unit, and the rest.
+fn split_quantity(text: &str) -> Option<(&str, &str, &str)> {
+    let len = text.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(text.len());
+    let (number, rest) = text.split_at(len);
+    if !number.starts_with(|c: char| c.is_ascii_digit()) || number.matches('.').count() > 1 {
+        return None;
+    }
+
+    let rest = rest.trim_start();
+    let len = rest.find(|c: char| !c.is_alphabetic()).unwrap_or(rest.len());
+    let (unit, rest) = rest.split_at(len);
+    Some((number, unit, rest))
+}
+
+/// Multiplies `number` by `scale`, using integers where possible so that large whole numbers stay exact.
+fn scale(number: &str, scale: u64) -> Option<u128> {
+    match number.parse::<u64>() {
+        Ok(whole) => Some(whole as u128 * scale as u128),
+        Err(_) => {
+            let scaled = number.parse::<f64>().ok()? * scale as f64;
+            if scaled.is_finite() && scaled < u128::MAX as f64 { Some(scaled.round() as u128) } else { None }
+        },
+    }
+}
+
+/// Parses a duration made of one or more numbers followed by their unit, such as `30s`, `1.5h` or `1h 30m`.
+/// The units are `ns`, `us` (or `µs`), `ms`, `s`, `m`, `h` and `d`.
+pub(crate) fn parse_duration(text: &str) -> Option<Duration> {
+    let mut rest = text.trim();
+    if rest.is_empty() {
+        return None;
+    }
+
+    let mut nanos: u128 = 0;
+    while !rest.is_empty() {
+        let (number, unit, after) = split_quantity(rest)?;
+        let unit = match unit {
+            "ns" => 1,
+            "us" | "µs" => 1_000,
+            "ms" => 1_000_000,
+            "s" => 1_000_000_000,
+            "m" => 60_000_000_000,
+            "h" => 3_600_000_000_000,
+            "d" => 86_400_000_000_000,
+            _ => return None,
+        };
+        nanos = nanos.checked_add(scale(number, unit)?)?;
+        rest = after.trim_start();
+    }
+
+    let secs = u64::try_from(nanos / 1_000_000_000).ok()?;
+    Some(Duration::new(secs, (nanos % 1_000_000_000) as u32))
+}
+
+/// Parses a size in bytes made of a number followed by its unit, such as `512B`, `10MB` or `1.5 GiB`,
+/// where the units are case insensitive. `KB`, `MB`, `GB`, `TB` and `PB` are powers of 1000, while
+/// `KiB`, `MiB`, `GiB`, `TiB` and `PiB` are powers of 1024. Fractions of a byte are rounded.
+pub(crate) fn parse_byte_size(text: &str) -> Option<u64> {
+    let (number, unit, rest) = split_quantity(text.trim())?;
+    if !rest.is_empty() {
+        return None;
+    }
+
+    let unit = match unit.to_ascii_lowercase().as_str() {
+        "b" => 1,
+        "kb" => 1_000,
+        "mb" => 1_000_000,
+        "gb" => 1_000_000_000,
+        "tb" => 1_000_000_000_000,
+        "pb" => 1_000_000_000_000_000,
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        "tib" => 1 << 40,
+        "pib" => 1 << 50,
+        _ => return None,
+    };
+    u64::try_from(scale(number, unit)?).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_byte_size, parse_duration};
+    use std::time::Duration;
+
+    #[test]
+    fn durations_and_sizes() {
+        assert_eq!(parse_duration("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_duration("1h 30m"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("1.5ms"), Some(Duration::from_micros(1500)));
+        assert_eq!(parse_duration("2d1ns"), Some(Duration::new(172_800, 1)));
+        assert_eq!(parse_duration("30"), None);
+        assert_eq!(parse_duration("5 minutes"), None);
+        assert_eq!(parse_duration("-5s"), None);
+        assert_eq!(parse_duration(""), None);
+
+        assert_eq!(parse_byte_size("10MB"), Some(10_000_000));
+        assert_eq!(parse_byte_size("512 KiB"), Some(524_288));
+        assert_eq!(parse_byte_size("1.5gib"), Some(1_610_612_736));
+        assert_eq!(parse_byte_size("18446744073709551615B"), Some(u64::MAX));
+        assert_eq!(parse_byte_size("16384PiB"), None);
+        assert_eq!(parse_byte_size("10MB 5KB"), None);
+        assert_eq!(parse_byte_size("1..5MB"), None);
+        assert_eq!(parse_byte_size("1024"), None);
+    }
+}
@@ -0,0 +1,138 @@
+use super::{CfgMap, CfgValue};
+
+/// An iterator over the values within a `CfgMap` and all of its nested maps and lists,
+/// created by `CfgMap::walk` or `CfgMap::walk_all`.
+///
+/// Values are visited depth-first, with map keys in sorted order and list elements in index order.
+/// The traversal uses its own stack, so deeply nested configurations can't overflow the call stack.
+pub struct Walk<'a> {
+    stack: Vec<(String, &'a CfgValue)>,
+    nodes: bool,
+}
+
+impl<'a> Walk<'a> {
+    pub(crate) fn new(map: &'a CfgMap, nodes: bool) -> Walk<'a> {
+        let mut walk = Walk { stack: Vec::new(), nodes };
+        walk.push_map("", map);
+        walk
+    }
+
+    fn push_map(&mut self, path: &str, map: &'a CfgMap) {
+        let mut children: Vec<(&String, &'a CfgValue)> = map.iter().collect();
+        children.sort_by(|a, b| b.0.cmp(a.0));
+        self.stack.extend(children.into_iter().map(|(k, v)| (super::join_path(path, k), v)));
+    }
+
+    fn push_list(&mut self, path: &str, list: &'a [CfgValue]) {
+        self.stack.extend(list.iter().enumerate().rev().map(|(i, v)| (super::join_path(path, &i.to_string()), v)));
+    }
+}
+
+impl<'a> Iterator for Walk<'a> {
+    type Item = (String, &'a CfgValue);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (path, value) = self.stack.pop()?;
+
+            match value {
+                CfgValue::Map(map) => self.push_map(&path, map),
+                CfgValue::List(list) => self.push_list(&path, list),
+                _ => return Some((path, value)),
+            }
+
+            if self.nodes {
+                return Some((path, value));
+            }
+        }
+    }
+}
+
+/// Calls `f` on every value within `map`, depth-first, visiting maps and lists before their contents.
+pub(crate) fn visit_mut<F: FnMut(&str, &mut CfgValue)>(map: &mut CfgMap, mut f: F) {
+    fn push_children<'a>(stack: &mut Vec<(String, &'a mut CfgValue)>, path: &str, map: &'a mut CfgMap) {
+        let mut children: Vec<(&String, &'a mut CfgValue)> = map.internal_map_mut().iter_mut().collect();
+        children.sort_by(|a, b| b.0.cmp(a.0));
+        stack.extend(children.into_iter().map(|(k, v)| (super::join_path(path, k), v)));
+    }
+
+    let mut stack = Vec::new();
+    push_children(&mut stack, "", map);
+
+    while let Some((path, value)) = stack.pop() {
+        f(&path, value);
+
+        match value {
+            CfgValue::Map(map) => push_children(&mut stack, &path, map),
+            CfgValue::List(list) => {
+                stack.extend(list.iter_mut().enumerate().rev().map(|(i, v)| (super::join_path(&path, &i.to_string()), v)));
+            },
+            _ => {}
+        }
+    }
+}
+
+/// Whether a value is a map or a list with nothing in it.
+fn is_empty_container(value: &CfgValue) -> bool {
+    match value {
+        CfgValue::Map(map) => map.is_empty(),
+        CfgValue::List(list) => list.is_empty(),
+        _ => false,
+    }
+}
+
+/// Applies `keep` to the children of `value`, if it's a map or list. Returns whether
+/// `value` itself should be kept, which is not the case if removing children emptied it.
+fn retain_value<F: FnMut(&str, &CfgValue) -> bool>(path: &str, value: &mut CfgValue, keep: &mut F) -> bool {
+    if is_empty_container(value) {
+        return true;
+    }
+
+    match value {
+        CfgValue::Map(map) => retain_map(path, map, keep),
+        CfgValue::List(list) => {
+            let mut index = 0;
+            list.retain_mut(|element| {
+                let full = super::join_path(path, &index.to_string());
+                index += 1;
+                keep(&full, element) && retain_value(&full, element, keep)
+            });
+        },
+        _ => {}
+    }
+
+    !is_empty_container(value)
+}
+
+/// Removes every value within `map` for which `keep` returns false, pruning emptied maps and lists.
+pub(crate) fn retain_map<F: FnMut(&str, &CfgValue) -> bool>(path: &str, map: &mut CfgMap, keep: &mut F) {
+    map.internal_map_mut().retain(|key, value| {
+        let full = super::join_path(path, key);
+        keep(&full, value) && retain_value(&full, value, keep)
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CfgMap, CfgValue::*};
+
+    #[test]
+    fn deep_nesting() {
+        let mut value = Int(0);
+        for _ in 0..10_000 {
+            value = List(vec![value]);
+        }
+
+        let mut cmap = CfgMap::new();
+        cmap.add("deep", value).unwrap();
+
+        assert_eq!(cmap.walk().count(), 1);
+        assert_eq!(cmap.walk_all().count(), 10_001);
+
+        // Nested values have to be dropped iteratively as well.
+        let mut value = cmap.remove("deep").unwrap();
+        while let List(mut inner) = value {
+            value = inner.pop().unwrap();
+        }
+    }
+}
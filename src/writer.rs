@@ -0,0 +1,646 @@
+use super::{CfgError, CfgMap, CfgValue, Format};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Options for writing a document using `CfgMap::to_writer_with` or `CfgMap::to_file_with`.
+/// 
+/// Keys are always written in sorted order, so that the same map is always written the same way.
+/// By default, nested values are indented by 2 spaces, only empty lists and maps are written on a single line,
+/// floats are written the same way as their format would, and documents end with a newline.
+/// 
+/// ## Examples
+/// ```
+/// # #[cfg(feature = "from_json")] {
+/// use cfgmap::{CfgMap, CfgValue::*, Format, WriteOptions, list, value};
+/// 
+/// let mut cmap = CfgMap::new();
+/// cmap.add("ports", list![80, 443]);
+/// cmap.add("name", Str("app".into()));
+/// 
+/// let mut document = Vec::new();
+/// cmap.to_writer_with(&mut document, Format::Json, &WriteOptions::new().indent(4).inline(2)).unwrap();
+/// 
+/// assert_eq!(String::from_utf8(document).unwrap(), "{\n    \"name\": \"app\",\n    \"ports\": [80, 443]\n}\n");
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteOptions {
+    indent: usize,
+    inline: usize,
+    trailing_newline: bool,
+    float_precision: Option<usize>,
+    integral_floats: bool,
+}
+
+impl Default for WriteOptions {
+    fn default() -> WriteOptions {
+        WriteOptions { indent: 2, inline: 0, trailing_newline: true, float_precision: None, integral_floats: true }
+    }
+}
+
+impl WriteOptions {
+    /// Creates the default options.
+    pub fn new() -> WriteOptions {
+        WriteOptions::default()
+    }
+
+    /// Indents nested values by `indent` spaces. TOML doesn't indent nested tables, so this only affects
+    /// JSON and YAML, where YAML is always indented by at least 2 spaces.
+    pub fn indent(mut self, indent: usize) -> WriteOptions {
+        self.indent = indent;
+        self
+    }
+
+    /// Writes lists and maps with at most `max` entries on a single line, as long as none of the entries are
+    /// lists or maps themselves. TOML always writes lists on a single line, so for TOML this only decides
+    /// which maps are written as inline tables.
+    pub fn inline(mut self, max: usize) -> WriteOptions {
+        self.inline = max;
+        self
+    }
+
+    /// Decides whether documents end with a newline.
+    pub fn trailing_newline(mut self, trailing_newline: bool) -> WriteOptions {
+        self.trailing_newline = trailing_newline;
+        self
+    }
+
+    /// Rounds floats to at most `digits` decimal places, without any trailing zeros, so that `0.1 + 0.2` is written
+    /// as `0.3` rather than `0.30000000000000004`. Infinite and NaN floats are written the same way regardless.
+    pub fn float_precision(mut self, digits: usize) -> WriteOptions {
+        self.float_precision = Some(digits);
+        self
+    }
+
+    /// Decides whether floats without a fractional part are written with a trailing `.0`, such as `2.0`, or as `2`.
+    /// Floats written without it are read back as integers.
+    pub fn integral_floats(mut self, integral_floats: bool) -> WriteOptions {
+        self.integral_floats = integral_floats;
+        self
+    }
+
+    /// Formats the float `value`, which its format would write as `written`.
+    fn float(&self, value: f64, written: String) -> String {
+        if !value.is_finite() {
+            return written;
+        }
+
+        let mut text = match self.float_precision {
+            Some(digits) => {
+                let mut text = format!("{:.*}", digits, value);
+                if text.contains('.') {
+                    text.truncate(text.trim_end_matches('0').len());
+                }
+                if text.ends_with('.') || !text.contains('.') {
+                    text.truncate(text.trim_end_matches('.').len());
+                    text.push_str(".0");
+                }
+                text
+            },
+            None => written,
+        };
+
+        if !self.integral_floats && text.ends_with(".0") {
+            text.truncate(text.len() - 2);
+        }
+        text
+    }
+
+    /// Checks whether a list or map with `len` entries is written on a single line.
+    fn inlines(&self, len: usize, nested: bool) -> bool {
+        len == 0 || (len <= self.inline && !nested)
+    }
+
+    #[cfg(any(feature = "from_json", feature = "from_yaml"))]
+    fn pad(&self, depth: usize) -> String {
+        " ".repeat(self.indent * depth)
+    }
+}
+
+/// Serializes `map` into a document in the given `format`.
+pub(crate) fn serialize(map: &CfgMap, format: Format, options: &WriteOptions) -> Result<String, CfgError> {
+    // The runtime information isn't part of the configuration, so it isn't written.
+    let mut contents = map.contents();
+    if contents.contains_key(super::META_KEY) {
+        contents.internal_map_mut().retain(|key, _| !super::is_meta_path(key));
+    }
+    let root = CfgValue::Map(contents);
+    let mut text = String::new();
+
+    match format {
+        #[cfg(feature = "from_json")]
+        Format::Json => {
+            emit_json::value(&super::from_json::cfg_to_json("", &root)?, options, 0, &mut text);
+            text.push('\n');
+        },
+        #[cfg(feature = "from_toml")]
+        Format::Toml => match super::from_toml::cfg_to_toml("", &root)? {
+            ::toml::Value::Table(table) => emit_toml::table(&mut Vec::new(), &table, options, &mut text),
+            _ => unreachable!("maps are converted into tables"),
+        },
+        #[cfg(feature = "from_yaml")]
+        Format::Yaml => {
+            let options = options.indent(options.indent.max(2));
+            emit_yaml::document(&super::from_yaml::cfg_to_yaml("", &root)?, &options, &mut text);
+        },
+    }
+
+    while text.ends_with('\n') {
+        text.pop();
+    }
+    if options.trailing_newline {
+        text.push('\n');
+    }
+    Ok(text)
+}
+
+#[cfg(feature = "from_json")]
+mod emit_json {
+    use super::WriteOptions;
+    use serde_json::Value;
+
+    fn is_nested(value: &Value) -> bool {
+        value.is_array() || value.is_object()
+    }
+
+    pub(super) fn value(value: &Value, options: &WriteOptions, depth: usize, out: &mut String) {
+        let (open, close, entries) = match value {
+            Value::Array(list) => ('[', ']', list.iter().map(|v| (None, v)).collect::<Vec<_>>()),
+            Value::Object(map) => {
+                let mut entries: Vec<(Option<&String>, &Value)> = map.iter().map(|(k, v)| (Some(k), v)).collect();
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+                ('{', '}', entries)
+            },
+            Value::Number(number) if number.is_f64() => {
+                return out.push_str(&options.float(number.as_f64().unwrap_or_default(), value.to_string()));
+            },
+            scalar => return out.push_str(&scalar.to_string()),
+        };
+
+        let inline = options.inlines(entries.len(), entries.iter().any(|(_, v)| is_nested(v)));
+        out.push(open);
+        for (i, (key, value)) in entries.iter().enumerate() {
+            out.push_str(if i == 0 { "" } else if inline { ", " } else { "," });
+            if !inline {
+                out.push('\n');
+                out.push_str(&options.pad(depth + 1));
+            }
+            if let Some(key) = key {
+                out.push_str(&Value::String(key.to_string()).to_string());
+                out.push_str(": ");
+            }
+            self::value(value, options, depth + 1, out);
+        }
+        if !inline {
+            out.push('\n');
+            out.push_str(&options.pad(depth));
+        }
+        out.push(close);
+    }
+}
+
+#[cfg(feature = "from_toml")]
+mod emit_toml {
+    use super::WriteOptions;
+    use toml::Value;
+    use toml::value::Table;
+
+    fn key(key: &str) -> String {
+        let bare = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if bare { key.to_string() } else { Value::String(key.to_string()).to_string() }
+    }
+
+    fn sorted(table: &Table) -> Vec<(&String, &Value)> {
+        let mut entries: Vec<(&String, &Value)> = table.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        entries
+    }
+
+    fn is_table_array(value: &Value) -> bool {
+        value.as_array().is_some_and(|list| !list.is_empty() && list.iter().all(Value::is_table))
+    }
+
+    /// Checks whether `value` is written after a header, rather than after its key within its parent.
+    fn has_header(value: &Value, options: &WriteOptions) -> bool {
+        match value {
+            Value::Table(table) => !options.inlines(table.len(), table.values().any(|v| v.is_table() || v.is_array())),
+            value => is_table_array(value),
+        }
+    }
+
+    fn inline(value: &Value, options: &WriteOptions) -> String {
+        match value {
+            Value::Array(list) => format!("[{}]", list.iter().map(|v| inline(v, options)).collect::<Vec<_>>().join(", ")),
+            Value::Table(table) if table.is_empty() => "{}".to_string(),
+            Value::Table(table) => format!("{{ {} }}", sorted(table).into_iter()
+                .map(|(k, v)| format!("{} = {}", key(k), inline(v, options)))
+                .collect::<Vec<_>>()
+                .join(", ")),
+            Value::Float(float) => options.float(*float, value.to_string()),
+            scalar => scalar.to_string(),
+        }
+    }
+
+    fn header(out: &mut String, path: &[String], array: bool) {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        let path = path.iter().map(|k| key(k)).collect::<Vec<_>>().join(".");
+        out.push_str(&if array { format!("[[{}]]\n", path) } else { format!("[{}]\n", path) });
+    }
+
+    /// Writes the contents of `table`, whose header has already been written if it needs one.
+    pub(super) fn table(path: &mut Vec<String>, table: &Table, options: &WriteOptions, out: &mut String) {
+        let (nested, values): (Vec<_>, Vec<_>) = sorted(table).into_iter().partition(|(_, v)| has_header(v, options));
+
+        for (k, v) in values {
+            out.push_str(&format!("{} = {}\n", key(k), inline(v, options)));
+        }
+
+        for (k, v) in nested {
+            path.push(k.clone());
+            match v {
+                Value::Table(nested) => {
+                    // Tables that only contain other tables don't need their own header.
+                    if nested.values().any(|v| !has_header(v, options)) {
+                        header(out, path, false);
+                    }
+                    self::table(path, nested, options, out);
+                },
+                Value::Array(list) => for nested in list.iter().filter_map(Value::as_table) {
+                    header(out, path, true);
+                    self::table(path, nested, options, out);
+                },
+                _ => unreachable!("only tables and arrays of tables have headers"),
+            }
+            path.pop();
+        }
+    }
+}
+
+#[cfg(feature = "from_yaml")]
+mod emit_yaml {
+    use super::WriteOptions;
+    use yaml_rust::Yaml;
+
+    fn is_nested(value: &Yaml) -> bool {
+        matches!(value, Yaml::Array(_) | Yaml::Hash(_))
+    }
+
+    fn is_inline(value: &Yaml, options: &WriteOptions) -> bool {
+        match value {
+            Yaml::Array(list) => options.inlines(list.len(), list.iter().any(is_nested)),
+            Yaml::Hash(hash) => options.inlines(hash.len(), hash.values().any(is_nested)),
+            _ => true,
+        }
+    }
+
+    /// Checks whether `s` has to be quoted so that it's read back as the same string.
+    fn needs_quotes(s: &str) -> bool {
+        s.is_empty()
+            || s.starts_with(|c: char| c.is_whitespace() || "-?:,[]{}#&*!|>'\"%@`.~".contains(c))
+            || s.ends_with(char::is_whitespace)
+            || s.contains(|c: char| c.is_control() || ",[]{}".contains(c))
+            || s.contains(": ")
+            || s.contains(" #")
+            || ["true", "false", "null"].contains(&s.to_lowercase().as_str())
+            || s.parse::<f64>().is_ok()
+            || s.starts_with("0x")
+            || s.starts_with("0o")
+    }
+
+    fn scalar(value: &Yaml, options: &WriteOptions) -> String {
+        match value {
+            Yaml::String(s) if needs_quotes(s) => {
+                let mut quoted = String::from("\"");
+                for c in s.chars() {
+                    match c {
+                        '"' => quoted.push_str("\\\""),
+                        '\\' => quoted.push_str("\\\\"),
+                        '\n' => quoted.push_str("\\n"),
+                        '\t' => quoted.push_str("\\t"),
+                        '\r' => quoted.push_str("\\r"),
+                        c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+                        c => quoted.push(c),
+                    }
+                }
+                quoted.push('"');
+                quoted
+            },
+            Yaml::String(s) => s.clone(),
+            Yaml::Integer(i) => i.to_string(),
+            Yaml::Real(r) => match r.parse::<f64>() {
+                Ok(float) => options.float(float, r.clone()),
+                Err(_) => r.clone(),
+            },
+            Yaml::Boolean(b) => b.to_string(),
+            Yaml::Null => "null".to_string(),
+            Yaml::Array(list) => format!("[{}]", list.iter().map(|v| scalar(v, options)).collect::<Vec<_>>().join(", ")),
+            Yaml::Hash(hash) => format!("{{{}}}", hash.iter()
+                .map(|(k, v)| format!("{}: {}", scalar(k, options), scalar(v, options)))
+                .collect::<Vec<_>>()
+                .join(", ")),
+            Yaml::Alias(_) | Yaml::BadValue => unreachable!("aliases and bad values can't be converted into yaml"),
+        }
+    }
+
+    /// Writes `value`, which follows a key within a map at `depth`.
+    fn entry(value: &Yaml, options: &WriteOptions, depth: usize, out: &mut String) {
+        if is_inline(value, options) {
+            out.push(' ');
+            out.push_str(&scalar(value, options));
+            out.push('\n');
+        } else {
+            out.push('\n');
+            block(value, options, depth + 1, out);
+        }
+    }
+
+    /// Writes every entry of `value`, which is a list or map, indented to `depth`.
+    fn block(value: &Yaml, options: &WriteOptions, depth: usize, out: &mut String) {
+        match value {
+            // Keys are already sorted when converting maps.
+            Yaml::Hash(hash) => for (k, v) in hash {
+                out.push_str(&options.pad(depth));
+                out.push_str(&scalar(k, options));
+                out.push(':');
+                entry(v, options, depth, out);
+            },
+            Yaml::Array(list) => for item in list {
+                out.push_str(&options.pad(depth));
+                if is_inline(item, options) {
+                    out.push_str("- ");
+                    out.push_str(&scalar(item, options));
+                    out.push('\n');
+                } else {
+                    // The first entry of the item follows the dash, and the rest are aligned with it.
+                    let mut nested = String::new();
+                    block(item, options, depth + 1, &mut nested);
+                    out.push('-');
+                    out.push_str(&" ".repeat(options.indent - 1));
+                    out.push_str(&nested[options.pad(depth + 1).len()..]);
+                }
+            },
+            _ => unreachable!("only lists and maps are written as blocks"),
+        }
+    }
+
+    pub(super) fn document(root: &Yaml, options: &WriteOptions, out: &mut String) {
+        if is_inline(root, options) {
+            out.push_str(&scalar(root, options));
+        } else {
+            block(root, options, 0, out);
+        }
+    }
+}
+
+pub(crate) fn write<W: Write>(map: &CfgMap, mut writer: W, format: Format, options: &WriteOptions) -> Result<(), CfgError> {
+    let text = serialize(map, format, options)?;
+    writer.write_all(text.as_bytes())
+        .and_then(|_| writer.flush())
+        .map_err(|e| CfgError::SaveFailed(e.to_string()))
+}
+
+/// Counts the temporary files created by this process, so that concurrent writes each use their own.
+static TEMP_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Creates a temporary file next to `path`, whose name is unique to this process and write. Any file at `path`
+/// has its permissions copied over before anything is written, so the document is never more readable than before.
+fn create_temp(path: &Path) -> io::Result<(PathBuf, File)> {
+    let name = path.file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("`{}` isn't a file", path.display())))?;
+
+    loop {
+        let mut temp_name = std::ffi::OsString::from(".");
+        temp_name.push(name);
+        temp_name.push(format!(".{}.{}.tmp", std::process::id(), TEMP_FILES.fetch_add(1, Ordering::Relaxed)));
+        let temp = path.with_file_name(temp_name);
+
+        // A file left behind by an earlier process with the same id is skipped, rather than written over.
+        let file = match OpenOptions::new().write(true).create_new(true).open(&temp) {
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            file => file?,
+        };
+
+        if let Ok(metadata) = fs::metadata(path) {
+            if let Err(e) = file.set_permissions(metadata.permissions()) {
+                let _ = fs::remove_file(&temp);
+                return Err(e);
+            }
+        }
+        return Ok((temp, file));
+    }
+}
+
+/// Writes `map` to the file at `path`, by writing it to a temporary file next to it, and renaming that over `path`.
+/// This way, the file at `path` always contains either the old or new document, even if writing is interrupted,
+/// and keeps the permissions it had before.
+pub(crate) fn write_file(map: &CfgMap, path: &Path, format: Format, options: &WriteOptions) -> Result<(), CfgError> {
+    let text = serialize(map, format, options)?;
+    let (temp, mut file) = create_temp(path).map_err(|e| CfgError::SaveFailed(e.to_string()))?;
+
+    let written = (|| -> io::Result<()> {
+        file.write_all(text.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temp, path)?;
+
+        // The rename itself is only durable once the directory containing it is synced.
+        #[cfg(unix)]
+        {
+            let parent = path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or_else(|| Path::new("."));
+            File::open(parent)?.sync_all()?;
+        }
+        Ok(())
+    })();
+
+    written.map_err(|e| {
+        let _ = fs::remove_file(&temp);
+        CfgError::SaveFailed(e.to_string())
+    })
+}
+
+#[cfg(all(test, feature = "from_json", feature = "from_toml", feature = "from_yaml"))]
+mod test {
+    use crate::{CfgError, CfgMap, CfgValue::*, Checkable, Condition, Format, WriteOptions};
+
+    #[test]
+    fn formatting() {
+        let mut cmap = CfgMap::new();
+        cmap.add("db", Map(CfgMap::new())).unwrap();
+        cmap.add("db/ports", list![5432, 5433]).unwrap();
+        cmap.add("db/tls", Map(CfgMap::new())).unwrap();
+        cmap.add("db/tls/enabled", Bool(true)).unwrap();
+
+        let write = |format, options: &WriteOptions| {
+            let mut written = Vec::new();
+            cmap.to_writer_with(&mut written, format, options).unwrap();
+            String::from_utf8(written).unwrap()
+        };
+
+        let inline = WriteOptions::new().inline(2);
+        assert_eq!(write(Format::Toml, &WriteOptions::new()), "[db]\nports = [5432, 5433]\n\n[db.tls]\nenabled = true\n");
+        assert_eq!(write(Format::Toml, &inline), "[db]\nports = [5432, 5433]\ntls = { enabled = true }\n");
+        assert_eq!(write(Format::Yaml, &WriteOptions::new()), "db:\n  ports:\n    - 5432\n    - 5433\n  tls:\n    enabled: true\n");
+        assert_eq!(write(Format::Yaml, &inline.indent(3)), "db:\n   ports: [5432, 5433]\n   tls: {enabled: true}\n");
+        assert_eq!(write(Format::Json, &inline.indent(0).trailing_newline(false)), "{\n\"db\": {\n\"ports\": [5432, 5433],\n\"tls\": {\"enabled\": true}\n}\n}");
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut cmap = CfgMap::new();
+        cmap.add("name", Str("app".into())).unwrap();
+        cmap.add("ratio", Float(2.0)).unwrap();
+        cmap.add("db", Map(CfgMap::new())).unwrap();
+        cmap.add("db/ports", list![5432, 5433]).unwrap();
+        cmap.add("db/replica", Map(CfgMap::new())).unwrap();
+        cmap.add("db/replica/host", Str("true".into())).unwrap();
+        cmap.add("servers", List(vec![Map(CfgMap::new()), list![1, 2]])).unwrap();
+        cmap.add("servers/0/name", Str("a: b".into())).unwrap();
+
+        let dir = std::env::temp_dir().join(format!("cfgmap-writer-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        for format in &[Format::Json, Format::Toml, Format::Yaml] {
+            let path = dir.join("config");
+            cmap.to_file(&path, *format).unwrap();
+            assert_eq!(CfgMap::from_file(&path, *format).unwrap(), cmap);
+
+            let mut written = Vec::new();
+            cmap.to_writer(&mut written, *format).unwrap();
+            assert_eq!(CfgMap::from_reader(written.as_slice(), *format).unwrap(), cmap);
+        }
+
+        // Every option still writes a document that reads back the same.
+        let options = WriteOptions::new().indent(4).inline(2).trailing_newline(false);
+        for format in &[Format::Json, Format::Toml, Format::Yaml] {
+            let mut written = Vec::new();
+            cmap.to_writer_with(&mut written, *format, &options).unwrap();
+            assert!(!written.ends_with(b"\n"));
+            assert_eq!(CfgMap::from_reader(written.as_slice(), *format).unwrap(), cmap);
+        }
+
+        // Nothing is written if the map can't be serialized.
+        let mut null = cmap.clone();
+        null.add("missing", Null).unwrap();
+        assert!(matches!(null.to_file(dir.join("null"), Format::Toml), Err(CfgError::Unrepresentable(_))));
+        assert!(!dir.join("null").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn float_formatting() {
+        let mut cmap = CfgMap::new();
+        cmap.add("ratio", Float(0.1 + 0.2)).unwrap();
+        cmap.add("scale", Float(2.0)).unwrap();
+        cmap.add("limits", List(vec![Float(1.25), Float(-3.0)])).unwrap();
+
+        let write = |format, options: &WriteOptions| {
+            let mut written = Vec::new();
+            cmap.to_writer_with(&mut written, format, options).unwrap();
+            String::from_utf8(written).unwrap()
+        };
+
+        let rounded = WriteOptions::new().inline(2).float_precision(1);
+        assert_eq!(write(Format::Json, &rounded), "{\n  \"limits\": [1.2, -3.0],\n  \"ratio\": 0.3,\n  \"scale\": 2.0\n}\n");
+        assert_eq!(write(Format::Toml, &rounded), "limits = [1.2, -3.0]\nratio = 0.3\nscale = 2.0\n");
+        assert_eq!(write(Format::Yaml, &rounded), "limits: [1.2, -3.0]\nratio: 0.3\nscale: 2.0\n");
+
+        let integral = WriteOptions::new().inline(2).integral_floats(false);
+        assert_eq!(write(Format::Toml, &integral), "limits = [1.25, -3]\nratio = 0.30000000000000004\nscale = 2\n");
+        assert_eq!(write(Format::Yaml, &integral.float_precision(0)), "limits: [1, -3]\nratio: 0\nscale: 2\n");
+
+        // Floats written without their fractional part are read back as integers.
+        let read = CfgMap::from_reader(write(Format::Json, &integral).as_bytes(), Format::Json).unwrap();
+        assert_eq!(read.get("scale"), Some(&Int(2)));
+    }
+
+    #[test]
+    fn unsigned_integers() {
+        let mut cmap = CfgMap::new();
+        cmap.add("id", UInt(u64::MAX)).unwrap();
+        cmap.add("ids", List(vec![Int(-1), UInt(1 << 63)])).unwrap();
+
+        for format in &[Format::Json, Format::Yaml] {
+            let mut written = Vec::new();
+            cmap.to_writer(&mut written, *format).unwrap();
+            assert_eq!(CfgMap::from_reader(written.as_slice(), *format).unwrap(), cmap);
+        }
+
+        let error = cmap.to_writer(Vec::new(), Format::Toml).unwrap_err();
+        assert!(matches!(error, CfgError::Unrepresentable(_)));
+    }
+
+    #[test]
+    fn opaque_values() {
+        let source = "codes:\n  404: missing\n  500: failed\nname: app\n";
+        let cmap = CfgMap::from_reader(source.as_bytes(), Format::Yaml).unwrap();
+        assert!(cmap.get("codes").unwrap().is_opaque());
+        assert_eq!(cmap.get("codes").unwrap().as_opaque().unwrap().0, "yaml");
+        assert!(cmap.get("codes").check_that(Condition::IsOpaque));
+
+        let mut written = Vec::new();
+        cmap.to_writer(&mut written, Format::Yaml).unwrap();
+        assert_eq!(String::from_utf8(written).unwrap(), source);
+
+        for format in &[Format::Json, Format::Toml] {
+            let error = cmap.to_writer(Vec::new(), *format).unwrap_err();
+            assert!(matches!(error, CfgError::Unrepresentable(_)));
+        }
+
+        let error = CfgMap::from_reader("1: one\n".as_bytes(), Format::Yaml).unwrap_err();
+        assert!(matches!(error, CfgError::Unrepresentable(_)));
+    }
+
+    #[test]
+    fn runtime_info_isnt_written() {
+        let mut cmap = CfgMap::new();
+        cmap.add("name", Str("app".into())).unwrap();
+        let original = cmap.clone();
+        cmap.attach_runtime_info::<&str>(&[]).unwrap();
+
+        for format in &[Format::Json, Format::Toml, Format::Yaml] {
+            let mut written = Vec::new();
+            cmap.to_writer(&mut written, *format).unwrap();
+            assert!(!String::from_utf8(written.clone()).unwrap().contains(crate::META_KEY));
+            assert_eq!(CfgMap::from_reader(written.as_slice(), *format).unwrap(), original);
+        }
+    }
+
+    #[test]
+    fn atomic_writes() {
+        let dir = std::env::temp_dir().join(format!("cfgmap-atomic-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+
+        // Concurrent writers each use their own temporary file, so every write succeeds.
+        let writers: Vec<_> = (0..8).map(|i| {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                let mut cmap = CfgMap::new();
+                cmap.add("writer", Int(i)).unwrap();
+                for _ in 0..10 {
+                    cmap.to_file(&path, Format::Toml).unwrap();
+                }
+            })
+        }).collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        assert!(CfgMap::from_file(&path, Format::Toml).unwrap().get("writer").is_some());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+            CfgMap::new().to_file(&path, Format::Toml).unwrap();
+            assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}